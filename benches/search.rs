@@ -0,0 +1,135 @@
+use navmesh::*;
+use std::time::{Duration, Instant};
+
+const ITERATIONS: usize = 200;
+
+fn measure<F>(name: &str, mut f: F) -> Duration
+where
+    F: FnMut(),
+{
+    f();
+    let timer = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let elapsed = timer.elapsed() / ITERATIONS as u32;
+    println!("{:<40} {:>12?} / iter", name, elapsed);
+    elapsed
+}
+
+fn make_grid(size: usize) -> NavGrid {
+    let cells = (0..size * size)
+        .map(|index| {
+            let col = index % size;
+            let row = index / size;
+            // vertical walls with gaps alternating between bottom and top row.
+            let wall = col % 8 == 4;
+            let gap = if col % 16 < 8 {
+                row == size - 1
+            } else {
+                row == 0
+            };
+            !wall || gap
+        })
+        .collect::<Vec<_>>();
+    NavGrid::new(size, size, cells).unwrap()
+}
+
+fn make_mesh(size: usize) -> NavMesh {
+    let vertices = (0..=size)
+        .flat_map(|row| (0..=size).map(move |col| (col as Scalar, row as Scalar, 0.0).into()))
+        .collect::<Vec<_>>();
+    let triangles = (0..size)
+        .flat_map(|row| {
+            (0..size).flat_map(move |col| {
+                let a = (row * (size + 1) + col) as u32;
+                let b = a + 1;
+                let c = a + size as u32 + 2;
+                let d = a + size as u32 + 1;
+                vec![(a, b, c).into(), (c, d, a).into()]
+            })
+        })
+        .collect::<Vec<_>>();
+    NavMesh::new(vertices, triangles).unwrap()
+}
+
+fn main() {
+    let size = 128;
+    let grid = make_grid(size);
+    let from = (0, 0);
+    let to = (size - 1, size - 1);
+    let allocating = measure("NavGrid::find_path_custom", || {
+        grid.find_path_custom(from, to, |_, _, _| true).unwrap();
+    });
+    let mut context = NavSearchContext::with_capacity(size * size);
+    let reusing = measure("NavGrid::find_path_with_context", || {
+        grid.find_path_with_context(&mut context, from, to, &NavDefaultCost)
+            .unwrap()
+            .count();
+    });
+    println!(
+        "{:<40} {:>12.2}x",
+        "speedup",
+        allocating.as_secs_f64() / reusing.as_secs_f64()
+    );
+
+    // short queries on big grid are dominated by search state setup.
+    let to = (3, 3);
+    let allocating = measure("NavGrid::find_path_custom (short)", || {
+        grid.find_path_custom(from, to, |_, _, _| true).unwrap();
+    });
+    let reusing = measure("NavGrid::find_path_with_context (short)", || {
+        grid.find_path_with_context(&mut context, from, to, &NavDefaultCost)
+            .unwrap()
+            .count();
+    });
+    println!(
+        "{:<40} {:>12.2}x",
+        "speedup",
+        allocating.as_secs_f64() / reusing.as_secs_f64()
+    );
+
+    // open map with a few walls is the best case for jump point search.
+    let mut grid = make_grid(size);
+    let from = (0, 0);
+    let to = (size - 1, size - 1);
+    grid.set_search_mode(NavGridSearchMode::AStar);
+    let astar = measure("NavGrid::find_path (A*)", || {
+        grid.find_path(from, to).unwrap();
+    });
+    grid.set_search_mode(NavGridSearchMode::JumpPoint);
+    let jump_point = measure("NavGrid::find_path (jump point)", || {
+        grid.find_path(from, to).unwrap();
+    });
+    println!(
+        "{:<40} {:>12.2}x",
+        "speedup",
+        astar.as_secs_f64() / jump_point.as_secs_f64()
+    );
+
+    let size = 64;
+    let mesh = make_mesh(size);
+    let from = (0.1, 0.2, 0.0).into();
+    let to = (size as Scalar - 0.1, size as Scalar - 0.2, 0.0).into();
+    let allocating = measure("NavMesh::find_path", || {
+        mesh.find_path(from, to, NavQuery::Accuracy, NavPathMode::Accuracy)
+            .unwrap();
+    });
+    let mut context = NavSearchContext::with_capacity(mesh.triangles().len());
+    let reusing = measure("NavMesh::find_path_with_context", || {
+        mesh.find_path_with_context(
+            &mut context,
+            from,
+            to,
+            NavQuery::Accuracy,
+            NavPathMode::Accuracy,
+            &NavDefaultCost,
+        )
+        .unwrap();
+    });
+    println!(
+        "{:<40} {:>12.2}x",
+        "speedup",
+        allocating.as_secs_f64() / reusing.as_secs_f64()
+    );
+}
@@ -2,13 +2,29 @@
 #[macro_use]
 extern crate approx;
 
+mod nav_chunked_grid;
+mod nav_cost;
+mod nav_field;
+mod nav_graph;
 mod nav_grid;
+mod nav_hex_grid;
 mod nav_islands;
 mod nav_mesh;
+mod nav_navigable;
 mod nav_net;
+mod nav_octree;
+mod nav_path;
+mod nav_replanner;
+mod nav_search;
 mod nav_vec3;
+mod nav_voxel_grid;
+mod nav_world;
 
-pub use crate::{nav_grid::*, nav_islands::*, nav_mesh::*, nav_net::*, nav_vec3::*};
+pub use crate::{
+    nav_chunked_grid::*, nav_cost::*, nav_field::*, nav_graph::*, nav_grid::*, nav_hex_grid::*,
+    nav_islands::*, nav_mesh::*, nav_navigable::*, nav_net::*, nav_octree::*, nav_path::*,
+    nav_replanner::*, nav_search::*, nav_vec3::*, nav_voxel_grid::*, nav_world::*,
+};
 
 use serde::{Deserialize, Serialize};
 use std::{
@@ -21,6 +37,11 @@ pub type Scalar = f64;
 #[cfg(not(feature = "scalar64"))]
 pub type Scalar = f32;
 
+#[cfg(feature = "scalar64")]
+pub(crate) const SCALAR_SQRT_2: Scalar = std::f64::consts::SQRT_2;
+#[cfg(not(feature = "scalar64"))]
+pub(crate) const SCALAR_SQRT_2: Scalar = std::f32::consts::SQRT_2;
+
 /// Error data.
 #[derive(Debug, Clone)]
 pub enum Error {
@@ -40,9 +61,21 @@ pub enum Error {
     /// Either cols or rows count is zero.
     /// (cols count, rows count)
     EmptyCells(usize, usize),
+    /// Trying to use voxel cells container with size not matching cols, rows and layers count.
+    /// (cells count, cols count, rows count, layers count)
+    CellsCountDoesNotMatchColsRowsLayers(usize, usize, usize, usize),
+    /// Either cols, rows or layers count is zero.
+    /// (cols count, rows count, layers count)
+    EmptyVoxels(usize, usize, usize),
     /// Trying to use cell coordinate out of bounds.
     /// (col, row, cols count, rows count)
     InvalidCellCoordinate(usize, usize, usize, usize),
+    /// Trying to load grid chunk of size different than grid chunk size.
+    /// (chunk size, grid chunk size)
+    ChunkSizeDoesNotMatch(usize, usize),
+    /// Trying to use grid transform with cell size that is not positive or not finite.
+    /// (cell size)
+    InvalidCellSize(Scalar),
 }
 
 /// Result data.
@@ -89,7 +122,12 @@ mod tests {
         foo::<NavNet>();
         foo::<NavGrid>();
         foo::<NavFreeGrid>();
+        foo::<NavChunkedGrid>();
+        foo::<NavHexGrid>();
+        foo::<NavVoxelGrid>();
+        foo::<NavOctree>();
         foo::<NavIslands<(), ()>>();
+        foo::<NavWorld<(), ()>>();
     }
 
     #[test]
@@ -160,6 +198,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_line_between_points() {
         assert_eq!(
             true,
@@ -214,6 +253,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::useless_vec)]
     fn test_spatials() {
         {
             let vertices = vec![
@@ -309,11 +349,11 @@ mod tests {
         ];
         let mesh = NavMesh::new(vertices.clone(), triangles.clone()).unwrap();
         {
-            let path = mesh.find_path_triangles(0, 0).unwrap().0;
+            let path = mesh.find_path_triangles(0, 0).unwrap().points;
             assert_eq!(path, vec![0]);
         }
         {
-            let path = mesh.find_path_triangles(2, 5).unwrap().0;
+            let path = mesh.find_path_triangles(2, 5).unwrap().points;
             assert_eq!(path, vec![2, 3, 0, 1, 4, 5]);
         }
         {
@@ -324,7 +364,8 @@ mod tests {
                     NavQuery::Accuracy,
                     NavPathMode::MidPoints,
                 )
-                .unwrap();
+                .unwrap()
+                .points;
             assert_eq!(
                 path.into_iter()
                     .map(|v| (
@@ -342,7 +383,8 @@ mod tests {
                     NavQuery::Accuracy,
                     NavPathMode::Accuracy,
                 )
-                .unwrap();
+                .unwrap()
+                .points;
             assert_eq!(
                 path.into_iter()
                     .map(|v| (
@@ -362,7 +404,8 @@ mod tests {
                     NavQuery::Accuracy,
                     NavPathMode::MidPoints,
                 )
-                .unwrap();
+                .unwrap()
+                .points;
             assert_eq!(
                 path.into_iter()
                     .map(|v| (
@@ -380,7 +423,8 @@ mod tests {
                     NavQuery::Accuracy,
                     NavPathMode::Accuracy,
                 )
-                .unwrap();
+                .unwrap()
+                .points;
             assert_eq!(
                 path.into_iter()
                     .map(|v| (
@@ -400,7 +444,8 @@ mod tests {
                     NavQuery::Accuracy,
                     NavPathMode::MidPoints,
                 )
-                .unwrap();
+                .unwrap()
+                .points;
             assert_eq!(
                 path.into_iter()
                     .map(|v| (
@@ -418,7 +463,8 @@ mod tests {
                     NavQuery::Accuracy,
                     NavPathMode::Accuracy,
                 )
-                .unwrap();
+                .unwrap()
+                .points;
             assert_eq!(
                 path.into_iter()
                     .map(|v| (
@@ -438,7 +484,8 @@ mod tests {
                     NavQuery::Accuracy,
                     NavPathMode::MidPoints,
                 )
-                .unwrap();
+                .unwrap()
+                .points;
             assert_eq!(
                 path.into_iter()
                     .map(|v| (
@@ -456,7 +503,8 @@ mod tests {
                     NavQuery::Accuracy,
                     NavPathMode::Accuracy,
                 )
-                .unwrap();
+                .unwrap()
+                .points;
             assert_eq!(
                 path.into_iter()
                     .map(|v| (
@@ -483,7 +531,7 @@ mod tests {
         ];
         let mesh = NavMesh::new(vertices.clone(), triangles.clone()).unwrap();
         {
-            let path = mesh.find_path_triangles(0, 2).unwrap().0;
+            let path = mesh.find_path_triangles(0, 2).unwrap().points;
             assert_eq!(path, vec![0, 1, 2]);
         }
         {
@@ -494,7 +542,8 @@ mod tests {
                     NavQuery::Accuracy,
                     NavPathMode::MidPoints,
                 )
-                .unwrap();
+                .unwrap()
+                .points;
             assert_eq!(
                 path.into_iter()
                     .map(|v| (
@@ -512,7 +561,8 @@ mod tests {
                     NavQuery::Accuracy,
                     NavPathMode::Accuracy,
                 )
-                .unwrap();
+                .unwrap()
+                .points;
             assert_eq!(
                 path.into_iter()
                     .map(|v| (
@@ -541,7 +591,7 @@ mod tests {
         ];
         let mesh = NavMesh::new(vertices.clone(), triangles.clone()).unwrap();
         {
-            let path = mesh.find_path_triangles(1, 2).unwrap().0;
+            let path = mesh.find_path_triangles(1, 2).unwrap().points;
             assert_eq!(path, vec![1, 0, 3, 2]);
         }
         {
@@ -552,7 +602,8 @@ mod tests {
                     NavQuery::Accuracy,
                     NavPathMode::MidPoints,
                 )
-                .unwrap();
+                .unwrap()
+                .points;
             assert_eq!(
                 path.into_iter()
                     .map(|v| (
@@ -570,7 +621,8 @@ mod tests {
                     NavQuery::Accuracy,
                     NavPathMode::Accuracy,
                 )
-                .unwrap();
+                .unwrap()
+                .points;
             assert_eq!(
                 path.into_iter()
                     .map(|v| (
@@ -590,7 +642,8 @@ mod tests {
                     NavQuery::Accuracy,
                     NavPathMode::MidPoints,
                 )
-                .unwrap();
+                .unwrap()
+                .points;
             assert_eq!(
                 path.into_iter()
                     .map(|v| (
@@ -608,7 +661,8 @@ mod tests {
                     NavQuery::Accuracy,
                     NavPathMode::Accuracy,
                 )
-                .unwrap();
+                .unwrap()
+                .points;
             assert_eq!(
                 path.into_iter()
                     .map(|v| (
@@ -628,7 +682,8 @@ mod tests {
                     NavQuery::Accuracy,
                     NavPathMode::MidPoints,
                 )
-                .unwrap();
+                .unwrap()
+                .points;
             assert_eq!(
                 path.into_iter()
                     .map(|v| (
@@ -646,7 +701,8 @@ mod tests {
                     NavQuery::Accuracy,
                     NavPathMode::Accuracy,
                 )
-                .unwrap();
+                .unwrap()
+                .points;
             assert_eq!(
                 path.into_iter()
                     .map(|v| (
@@ -661,6 +717,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::excessive_precision, clippy::useless_vec)]
     fn test_thicken() {
         let source = NavMesh::new(
             vec![
@@ -760,7 +817,7 @@ mod tests {
             vec![true, true, true, true, false, true, true, true, true],
         )
         .unwrap();
-        let path = grid.find_path((0, 0), (1, 2)).unwrap();
+        let path = grid.find_path((0, 0), (1, 2)).unwrap().points;
         assert_eq!(path, vec![(0, 0), (0, 1), (0, 2), (1, 2)]);
         assert_eq!(grid.find_path((0, 0), (1, 1)), None);
 
@@ -768,26 +825,14 @@ mod tests {
             2,
             2,
             vec![
-                NavGridConnection {
-                    from: (0, 0),
-                    to: (1, 0),
-                },
-                NavGridConnection {
-                    from: (1, 0),
-                    to: (1, 1),
-                },
-                NavGridConnection {
-                    from: (1, 1),
-                    to: (0, 1),
-                },
-                NavGridConnection {
-                    from: (0, 1),
-                    to: (0, 0),
-                },
+                NavGridConnection::new((0, 0), (1, 0)),
+                NavGridConnection::new((1, 0), (1, 1)),
+                NavGridConnection::new((1, 1), (0, 1)),
+                NavGridConnection::new((0, 1), (0, 0)),
             ],
         )
         .unwrap();
-        let path = grid.find_path((0, 0), (0, 1)).unwrap();
+        let path = grid.find_path((0, 0), (0, 1)).unwrap().points;
         assert_eq!(path, vec![(0, 0), (1, 0), (1, 1), (0, 1)]);
         let mut islands = grid.find_islands();
         for island in &mut islands {
@@ -811,64 +856,2195 @@ mod tests {
         );
 
         let grid = NavFreeGrid::new(vec![
-            NavFreeGridConnection {
-                from: (0, 0),
-                to: (0, 2),
-            },
-            NavFreeGridConnection {
-                from: (0, 2),
-                to: (-1, -1),
-            },
+            NavFreeGridConnection::new((0, 0), (0, 2)),
+            NavFreeGridConnection::new((0, 2), (-1, -1)),
         ]);
-        let path = grid.find_path((0, 0), (-1, -1)).unwrap();
+        let path = grid.find_path((0, 0), (-1, -1)).unwrap().points;
         assert_eq!(path, vec![(0, 0), (0, 2), (-1, -1)]);
     }
 
     #[test]
-    fn test_islands() {
-        let grid_a = NavGrid::new(2, 2, vec![true, true, true, false]).unwrap();
-        let grid_b = NavGrid::new(2, 2, vec![true, true, false, true]).unwrap();
-        let island_a = NavIslandPortal {
-            island: grid_a.id(),
-            portal: None,
-        };
-        let island_a_portal = NavIslandPortal {
-            island: grid_a.id(),
-            portal: Some((1, 0)),
-        };
-        let island_b_portal = NavIslandPortal {
-            island: grid_b.id(),
-            portal: Some((0, 0)),
+    fn test_serialization() {
+        let mut grid = NavGrid::with_connectivity(
+            3,
+            3,
+            vec![true, true, true, false, false, true, true, true, true],
+            NavGridConnectivity::EightNoCornerCutting,
+        )
+        .unwrap();
+        grid.set_cell_cost(2, 1, 3.0);
+        let data = serde_json::to_string(&grid).unwrap();
+        assert!(!data.contains("graph"));
+        let loaded = serde_json::from_str::<NavGrid>(&data).unwrap();
+        assert_eq!(
+            loaded.find_path((0, 0), (0, 2)).unwrap(),
+            grid.find_path((0, 0), (0, 2)).unwrap()
+        );
+        assert_eq!(loaded.find_islands().len(), grid.find_islands().len());
+        // costs range is restored, so heuristic and jump point search keep working.
+        let mut uniform = NavGrid::new(3, 1, vec![true; 3]).unwrap();
+        uniform.set_cell_cost(1, 0, 2.0);
+        let mut loaded =
+            serde_json::from_str::<NavGrid>(&serde_json::to_string(&uniform).unwrap()).unwrap();
+        assert_eq!(loaded.find_path((0, 0), (2, 0)).unwrap().cost, 4.0);
+        loaded.set_cell_cost(0, 0, 0.5);
+        uniform.set_cell_cost(0, 0, 0.5);
+        assert_eq!(loaded.heuristic_scale(), uniform.heuristic_scale());
+        // grids saved with graph by older versions get their connections from it.
+        let legacy = r#"{
+            "id": "a0629750-941e-43f0-8c6a-d8be47f74211",
+            "cols": 3,
+            "rows": 2,
+            "cells": [true, true, true, false, true, true],
+            "costs": [1.0, 1.0, 1.0, 1.0, 1.0, 1.0],
+            "graph": {
+                "nodes": [null, null, null, null, null],
+                "node_holes": [],
+                "edge_property": "directed",
+                "edges": [
+                    [0, 1, null], [1, 0, null], [1, 2, null], [2, 1, null], [3, 4, null],
+                    [4, 3, null], [1, 3, null], [3, 1, null], [2, 4, null], [4, 2, null]
+                ]
+            },
+            "nodes": [0, 1, 2, null, 3, 4],
+            "nodes_map": {"2": 2, "1": 1, "0": 0, "4": 5, "3": 4}
+        }"#;
+        let mut loaded = serde_json::from_str::<NavGrid>(legacy).unwrap();
+        assert_eq!(loaded.connectivity(), Some(NavGridConnectivity::Four));
+        assert_eq!(loaded.find_path((0, 0), (2, 1)).unwrap().length, 3.0);
+        assert_eq!(loaded.set_cell_walkable(0, 1, true), Some(false));
+        assert_eq!(loaded.find_path((0, 1), (0, 0)).unwrap().length, 1.0);
+        let legacy = r#"{
+            "id": "c019b253-31c4-48a5-9e25-90438d5de51e",
+            "cols": 3,
+            "rows": 1,
+            "cells": [true, true, true],
+            "costs": [1.0, 1.0, 1.0],
+            "graph": {
+                "nodes": [null, null, null],
+                "node_holes": [],
+                "edge_property": "directed",
+                "edges": [[0, 1, null], [1, 2, null], [2, 1, null]]
+            },
+            "nodes": [0, 1, 2],
+            "nodes_map": {"2": 2, "0": 0, "1": 1}
+        }"#;
+        let loaded = serde_json::from_str::<NavGrid>(legacy).unwrap();
+        assert_eq!(loaded.connectivity(), None);
+        assert_eq!(loaded.find_path((0, 0), (2, 0)).unwrap().length, 2.0);
+        assert_eq!(loaded.find_path((2, 0), (0, 0)), None);
+        let broken = legacy.replace("[true, true, true]", "[true, false, true]");
+        assert!(serde_json::from_str::<NavGrid>(&broken).is_err());
+
+        let net = NavNet::new(
+            vec![
+                (0.0, 0.0, 0.0).into(),
+                (1.0, 0.0, 0.0).into(),
+                (1.0, 1.0, 0.0).into(),
+            ],
+            vec![NavConnection(0, 1), NavConnection(1, 2)],
+        )
+        .unwrap();
+        let loaded = serde_json::from_str::<NavNet>(&serde_json::to_string(&net).unwrap()).unwrap();
+        assert_eq!(
+            loaded
+                .find_path((0.0, -0.1, 0.0).into(), (1.1, 1.0, 0.0).into())
+                .unwrap(),
+            net.find_path((0.0, -0.1, 0.0).into(), (1.1, 1.0, 0.0).into())
+                .unwrap()
+        );
+
+        let mut grid = NavFreeGrid::from_cells(
+            [(0, 0), (1, 0), (2, 0), (2, 1), (-1, 5)],
+            NavGridConnectivity::Four,
+        );
+        grid.set_cell_cost(1, 0, 2.0);
+        let loaded =
+            serde_json::from_str::<NavFreeGrid>(&serde_json::to_string(&grid).unwrap()).unwrap();
+        assert_eq!(loaded.index(2, 1), grid.index(2, 1));
+        assert_eq!(loaded.index(-1, 5), Some(4));
+        assert_eq!(
+            loaded.find_path((0, 0), (2, 1)).unwrap(),
+            grid.find_path((0, 0), (2, 1)).unwrap()
+        );
+        grid.add_connection(
+            NavFreeGridConnection::new((2, 1), (-1, 5))
+                .with_cost(3.0)
+                .with_both_ways(false),
+        );
+        let data = serde_json::to_string(&grid).unwrap();
+        let loaded = serde_json::from_str::<NavFreeGrid>(&data).unwrap();
+        assert_eq!(
+            loaded.find_path((0, 0), (-1, 5)).unwrap(),
+            grid.find_path((0, 0), (-1, 5)).unwrap()
+        );
+        assert_eq!(loaded.find_path((-1, 5), (0, 0)), None);
+        let broken = data.replace("[3,4,3.0]", "[3,40,3.0]");
+        assert_ne!(broken, data);
+        assert!(serde_json::from_str::<NavFreeGrid>(&broken).is_err());
+        // free grids saved by older versions have undirected graph without weights.
+        let legacy = r#"{
+            "id": "77d5a76e-d6d2-47ac-90a1-0b5028e86224",
+            "cells": [[1, 1], [1, 0], [0, 0]],
+            "costs": [1.0, 1.0, 1.0],
+            "graph": {
+                "nodes": [null, null, null],
+                "node_holes": [],
+                "edge_property": "undirected",
+                "edges": [[2, 1, null], [1, 0, null]]
+            },
+            "nodes": [0, 1, 2],
+            "nodes_map": {"2": 2, "1": 1, "0": 0}
+        }"#;
+        let loaded = serde_json::from_str::<NavFreeGrid>(legacy).unwrap();
+        assert_eq!(
+            loaded.find_path((0, 0), (1, 1)).unwrap().points,
+            vec![(0, 0), (1, 0), (1, 1)]
+        );
+        assert_eq!(loaded.find_path((1, 1), (0, 0)).unwrap().length, 2.0);
+
+        let portal = |island: usize, portal| NavIslandPortal { island, portal };
+        let islands = NavIslands::new(
+            vec![
+                NavIslandsConnection {
+                    from: portal(0, None),
+                    to: portal(0, Some(0)),
+                    distance: 1.0,
+                },
+                NavIslandsConnection {
+                    from: portal(0, Some(0)),
+                    to: portal(1, None),
+                    distance: 2.0,
+                },
+            ],
+            true,
+        );
+        let data = serde_json::to_string(&islands).unwrap();
+        let loaded = serde_json::from_str::<NavIslands<usize, usize>>(&data).unwrap();
+        assert_eq!(loaded.portals().len(), islands.portals().len());
+        assert_eq!(
+            loaded
+                .find_path(&portal(1, None), &portal(0, None))
+                .unwrap(),
+            islands
+                .find_path(&portal(1, None), &portal(0, None))
+                .unwrap()
+        );
+        assert!(data.contains(r#""costs":[1.0,1.0,1.0]"#));
+        let broken = data.replace(r#""costs":[1.0,1.0,1.0]"#, r#""costs":[1.0]"#);
+        assert!(serde_json::from_str::<NavIslands<usize, usize>>(&broken).is_err());
+    }
+
+    #[test]
+    fn test_search_context() {
+        let mut context = NavSearchContext::default();
+        let grid = NavGrid::new(
+            3,
+            3,
+            vec![true, true, true, true, false, true, true, true, true],
+        )
+        .unwrap();
+        let path = grid
+            .find_path_with_context(&mut context, (0, 0), (1, 2), &NavDefaultCost)
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(path, grid.find_path((0, 0), (1, 2)).unwrap().points);
+        let capacity = context.nodes.capacity();
+        assert!(grid
+            .find_path_with_context(&mut context, (0, 0), (1, 1), &NavDefaultCost)
+            .is_none());
+        assert_eq!(context.nodes.capacity(), capacity);
+        context.generation = u32::MAX;
+        let path = grid
+            .find_path_with_context(&mut context, (2, 2), (0, 0), &NavDefaultCost)
+            .unwrap()
+            .count();
+        assert_eq!(path, 5);
+        assert_eq!(context.generation, 1);
+
+        let vertices = vec![
+            (0.0, 0.0, 0.0).into(), // 0
+            (1.0, 0.0, 0.0).into(), // 1
+            (2.0, 0.0, 1.0).into(), // 2
+            (0.0, 1.0, 0.0).into(), // 3
+            (1.0, 1.0, 0.0).into(), // 4
+            (2.0, 1.0, 1.0).into(), // 5
+        ];
+        let triangles = vec![
+            (0, 1, 4).into(), // 0
+            (4, 3, 0).into(), // 1
+            (1, 2, 5).into(), // 2
+            (5, 4, 1).into(), // 3
+        ];
+        let mesh = NavMesh::new(vertices, triangles).unwrap();
+        for mode in [NavPathMode::Accuracy, NavPathMode::MidPoints] {
+            let expected = mesh
+                .find_path(
+                    (0.0, 1.0, 0.0).into(),
+                    (1.2, 0.4, 0.2).into(),
+                    NavQuery::Accuracy,
+                    mode,
+                )
+                .unwrap();
+            let path = mesh
+                .find_path_with_context(
+                    &mut context,
+                    (0.0, 1.0, 0.0).into(),
+                    (1.2, 0.4, 0.2).into(),
+                    NavQuery::Accuracy,
+                    mode,
+                    &NavDefaultCost,
+                )
+                .unwrap();
+            assert_eq!(path, expected.points.as_slice());
+        }
+        let (path, _) = mesh
+            .find_path_triangles_with_context(&mut context, 1, 2, &NavDefaultCost)
+            .unwrap();
+        assert_eq!(path, &[1, 0, 3, 2]);
+    }
+
+    #[test]
+    fn test_cost() {
+        struct Blocked((usize, usize));
+
+        impl NavCost<(usize, usize)> for Blocked {
+            fn edge_cost(
+                &self,
+                _from: (usize, usize),
+                to: (usize, usize),
+                base: Scalar,
+            ) -> Option<Scalar> {
+                if to == self.0 {
+                    None
+                } else {
+                    Some(base)
+                }
+            }
+
+            fn heuristic(&self, node: (usize, usize), goal: (usize, usize)) -> Option<Scalar> {
+                Some((node.0.abs_diff(goal.0) + node.1.abs_diff(goal.1)) as Scalar)
+            }
+        }
+
+        let grid = NavGrid::new(3, 3, vec![true; 9]).unwrap();
+        let path = grid
+            .find_path_with_cost((0, 0), (2, 0), &Blocked((1, 0)))
+            .unwrap()
+            .points;
+        assert_eq!(path, vec![(0, 0), (0, 1), (1, 1), (2, 1), (2, 0)]);
+        assert_eq!(
+            grid.find_path_with_cost((0, 0), (2, 0), &NavDefaultCost),
+            grid.find_path((0, 0), (2, 0))
+        );
+        assert!(grid
+            .find_path_with_cost((0, 0), (1, 0), &Blocked((1, 0)))
+            .is_none());
+
+        let net = NavNet::new(
+            vec![
+                (0.0, 0.0, 0.0).into(),
+                (1.0, 0.0, 0.0).into(),
+                (1.0, 1.0, 0.0).into(),
+            ],
+            vec![
+                NavConnection(0, 1),
+                NavConnection(1, 2),
+                NavConnection(0, 2),
+            ],
+        )
+        .unwrap();
+        let cost = |a: usize, b: usize, base: Scalar| {
+            if (a == 0 && b == 2) || (a == 2 && b == 0) {
+                Some(base * 10.0)
+            } else {
+                Some(base)
+            }
         };
-        let island_b = NavIslandPortal {
-            island: grid_b.id(),
-            portal: None,
+        let path = net
+            .find_path_with_cost((0.0, 0.0, 0.0).into(), (1.0, 1.0, 0.0).into(), &cost)
+            .unwrap()
+            .points;
+        assert_eq!(path.len(), 3);
+    }
+
+    #[test]
+    fn test_cost_model() {
+        let mut grid = NavGrid::new(3, 2, vec![true; 6]).unwrap();
+        grid.set_cell_cost(1, 0, 2.5);
+        let direct = vec![(0, 0), (1, 0), (2, 0)];
+        let detour = vec![(0, 0), (0, 1), (1, 1), (2, 1), (2, 0)];
+        for (model, expected) in [
+            (NavCostModel::Multiplicative, &detour),
+            (NavCostModel::Additive, &direct),
+            (NavCostModel::AreaWeighted, &direct),
+            (NavCostModel::MaxOfPair, &detour),
+        ] {
+            let path = grid
+                .find_path_with_cost((0, 0), (2, 0), &model)
+                .unwrap()
+                .points;
+            assert_eq!(&path, expected);
+            grid.set_cost_model(model);
+            assert_eq!(&grid.find_path((0, 0), (2, 0)).unwrap().points, expected);
+        }
+        assert_eq!(NavCostModel::Multiplicative.heuristic_scale(0.5), 0.25);
+        assert_eq!(NavCostModel::Additive.heuristic_scale(0.0), 0.0);
+        assert_eq!(NavCostModel::Additive.heuristic_scale(2.0), 1.0);
+        // neutral costs keep weights in every model.
+        for model in [
+            NavCostModel::Multiplicative,
+            NavCostModel::Additive,
+            NavCostModel::AreaWeighted,
+            NavCostModel::MaxOfPair,
+        ] {
+            assert_relative_eq!(
+                model.combine(SCALAR_SQRT_2, 1.0, 1.0),
+                SCALAR_SQRT_2,
+                epsilon = 1e-6
+            );
+        }
+        assert_eq!(NavCostModel::MaxOfPair.heuristic_scale(-1.0), 0.0);
+
+        let vertices = vec![
+            (0.0, 0.0, 0.0).into(), // 0
+            (1.0, 0.0, 0.0).into(), // 1
+            (2.0, 0.0, 1.0).into(), // 2
+            (0.0, 1.0, 0.0).into(), // 3
+            (1.0, 1.0, 0.0).into(), // 4
+            (2.0, 1.0, 1.0).into(), // 5
+        ];
+        let triangles = vec![
+            (0, 1, 4).into(), // 0
+            (4, 3, 0).into(), // 1
+            (1, 2, 5).into(), // 2
+            (5, 4, 1).into(), // 3
+        ];
+        let mut mesh = NavMesh::new(vertices, triangles).unwrap();
+        let path = mesh
+            .find_path_triangles_with_cost(1, 0, &NavCostModel::AreaWeighted)
+            .unwrap();
+        assert_eq!(path.points, vec![1, 0]);
+        let center_a = mesh.areas()[1].center;
+        let center_b = mesh.areas()[0].center;
+        let midpoint = NavVec3::new(0.5, 0.5, 0.0);
+        assert_relative_eq!(
+            path.cost,
+            (midpoint - center_a).magnitude() + (center_b - midpoint).magnitude()
+        );
+        mesh.set_cost_model(NavCostModel::AreaWeighted);
+        assert_eq!(
+            mesh.find_path_triangles_with_cost(1, 0, &NavDefaultCost),
+            Some(path)
+        );
+        // connection costs are split at shared edge midpoint in both directions.
+        mesh.set_area_cost(0, 3.0);
+        assert_relative_eq!(
+            NavGraph::edge_cost(&mesh, 1, 0, 0.0).unwrap(),
+            (midpoint - center_a).magnitude() + (center_b - midpoint).magnitude() * 3.0
+        );
+        assert_relative_eq!(
+            NavGraph::edge_cost(&mesh, 0, 1, 0.0).unwrap(),
+            (midpoint - center_b).magnitude() * 3.0 + (center_a - midpoint).magnitude()
+        );
+        assert_eq!(NavGraph::edge_cost(&mesh, 1, 2, 0.0), None);
+        assert_eq!(NavGraph::edge_cost(&mesh, 1, 9, 0.0), None);
+    }
+
+    #[test]
+    fn test_nav_world() {
+        let portal = |island: &str, portal| NavIslandPortal {
+            island: island.to_owned(),
+            portal,
         };
         let islands = NavIslands::new(
             vec![
                 NavIslandsConnection {
-                    from: island_a.clone(),
-                    to: island_a_portal.clone(),
+                    from: portal("ground", None),
+                    to: portal("ground", Some("stairs")),
                     distance: 1.0,
                 },
                 NavIslandsConnection {
-                    from: island_a_portal.clone(),
-                    to: island_b_portal.clone(),
-                    distance: 0.0,
+                    from: portal("ground", Some("stairs")),
+                    to: portal("floor", Some("stairs")),
+                    distance: 1.0,
                 },
                 NavIslandsConnection {
-                    from: island_b_portal.clone(),
-                    to: island_b.clone(),
+                    from: portal("floor", Some("stairs")),
+                    to: portal("floor", None),
                     distance: 1.0,
                 },
             ],
             true,
         );
-        let (distance, path) = islands.find_path(&island_a, &island_b).unwrap();
+        let mut world = NavWorld::new(islands);
+        let mesh = NavMesh::new(
+            vec![
+                (0.0, 0.0, 0.0).into(),
+                (2.0, 0.0, 0.0).into(),
+                (2.0, 2.0, 0.0).into(),
+                (0.0, 2.0, 0.0).into(),
+            ],
+            vec![(0, 1, 2).into(), (2, 3, 0).into()],
+        )
+        .unwrap();
+        world.set_island("ground".to_owned(), mesh);
+        let mut grid = NavGrid::new(2, 2, vec![true; 4]).unwrap();
+        grid.set_transform(Some(NavGridTransform {
+            origin: (0.0, 0.0, 3.0).into(),
+            ..Default::default()
+        }))
+        .unwrap();
+        world.set_island("floor".to_owned(), grid);
+        world.set_portal_location(portal("ground", Some("stairs")), (2.0, 2.0, 0.0).into());
+        assert!(world
+            .find_path(
+                &"ground".to_owned(),
+                (0.0, 0.0, 0.0).into(),
+                &"floor".to_owned(),
+                (0.5, 0.5, 3.0).into(),
+            )
+            .is_none());
+        world.set_portal_location(portal("floor", Some("stairs")), (1.5, 1.5, 3.0).into());
+
+        let path = world
+            .find_path(
+                &"ground".to_owned(),
+                (0.0, 0.0, 0.0).into(),
+                &"floor".to_owned(),
+                (0.5, 0.5, 3.0).into(),
+            )
+            .unwrap();
+        // floor grid is four-connected, so path goes around through one of corner cells.
+        assert_eq!(path.points.len(), 5);
         assert_eq!(
-            path,
+            path.points[..3],
+            [
+                (0.0, 0.0, 0.0).into(),
+                (2.0, 2.0, 0.0).into(),
+                (1.5, 1.5, 3.0).into(),
+            ]
+        );
+        assert_eq!(path.points.last(), Some(&(0.5, 0.5, 3.0).into()));
+        assert_relative_eq!(
+            path.length,
+            path.points
+                .windows(2)
+                .map(|pair| (pair[1] - pair[0]).magnitude())
+                .sum::<Scalar>()
+        );
+
+        let path = world
+            .find_path(
+                &"floor".to_owned(),
+                (0.5, 0.5, 3.0).into(),
+                &"floor".to_owned(),
+                (1.5, 0.5, 3.0).into(),
+            )
+            .unwrap();
+        assert_eq!(path.points.len(), 2);
+        assert!(world
+            .find_path(
+                &"basement".to_owned(),
+                (0.0, 0.0, 0.0).into(),
+                &"floor".to_owned(),
+                (0.5, 0.5, 3.0).into(),
+            )
+            .is_none());
+
+        // points placed exactly at portal locations or at each other.
+        let path = world
+            .find_path(
+                &"ground".to_owned(),
+                (2.0, 2.0, 0.0).into(),
+                &"floor".to_owned(),
+                (1.5, 1.5, 3.0).into(),
+            )
+            .unwrap();
+        assert_eq!(
+            path.points,
+            vec![(2.0, 2.0, 0.0).into(), (1.5, 1.5, 3.0).into()]
+        );
+        let path = world
+            .find_path(
+                &"ground".to_owned(),
+                (1.0, 1.0, 0.0).into(),
+                &"ground".to_owned(),
+                (1.0, 1.0, 0.0).into(),
+            )
+            .unwrap();
+        assert_eq!(path.points, vec![(1.0, 1.0, 0.0).into()]);
+        assert_eq!(path.cost, 0.0);
+        assert!(world
+            .island(&"ground".to_owned())
+            .unwrap()
+            .find_path((2.0, 2.0, 0.0).into(), (2.0, 2.0, 0.0).into())
+            .is_some());
+
+        // grid costs are measured in world units too.
+        let mut grid = NavGrid::new(3, 1, vec![true; 3]).unwrap();
+        grid.set_transform(Some(NavGridTransform {
+            cell_size: 2.0,
+            ..Default::default()
+        }))
+        .unwrap();
+        world.set_island("floor".to_owned(), grid);
+        let path = world
+            .find_path(
+                &"floor".to_owned(),
+                (1.0, 1.0, 0.0).into(),
+                &"floor".to_owned(),
+                (5.0, 1.0, 0.0).into(),
+            )
+            .unwrap();
+        assert_eq!(path.length, 4.0);
+        assert_eq!(path.cost, 4.0);
+
+        // nav mesh connections map has non-string keys, so it cannot be stored as JSON.
+        world.remove_island(&"ground".to_owned());
+        let loaded = serde_json::from_str::<NavWorld<String, String>>(
+            &serde_json::to_string(&world).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            loaded.find_path(
+                &"floor".to_owned(),
+                (1.0, 1.0, 0.0).into(),
+                &"floor".to_owned(),
+                (5.0, 1.0, 0.0).into(),
+            ),
+            world.find_path(
+                &"floor".to_owned(),
+                (1.0, 1.0, 0.0).into(),
+                &"floor".to_owned(),
+                (5.0, 1.0, 0.0).into(),
+            )
+        );
+    }
+
+    #[test]
+    fn test_nav_graph() {
+        struct Blocked(usize);
+
+        impl NavCost<usize> for Blocked {
+            fn edge_cost(&self, _from: usize, to: usize, base: Scalar) -> Option<Scalar> {
+                if to == self.0 {
+                    None
+                } else {
+                    Some(base)
+                }
+            }
+        }
+
+        let mut grid = NavGrid::new(
+            3,
+            3,
+            vec![true, true, true, true, false, true, true, true, false],
+        )
+        .unwrap();
+        grid.set_cell_cost(1, 0, 3.0);
+        let expected = grid.find_path((0, 0), (2, 1)).unwrap();
+        let path = grid.find_graph_path(0, 5).unwrap();
+        assert_eq!(
+            path.points
+                .iter()
+                .map(|n| grid.coord(*n).unwrap())
+                .collect::<Vec<_>>(),
+            expected.points
+        );
+        assert_relative_eq!(path.cost, expected.cost);
+        assert_relative_eq!(path.length, expected.length);
+        assert_eq!(
+            grid.find_graph_islands().len(),
+            grid.find_islands().len() + 2
+        );
+        assert!(grid.find_graph_path_with_cost(0, 5, &Blocked(3)).is_some());
+        assert!(grid.find_graph_path_with_cost(0, 5, &Blocked(1)).is_none());
+        let mut context = NavSearchContext::default();
+        let path = grid
+            .find_graph_path_with_context(&mut context, 0, 5, &NavDefaultCost)
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(path.len(), expected.points.len());
+
+        let portal = |island| NavIslandPortal::<u8, ()> {
+            island,
+            portal: None,
+        };
+        let islands = NavIslands::new(
+            vec![
+                NavIslandsConnection {
+                    from: portal(0),
+                    to: portal(1),
+                    distance: 2.0,
+                },
+                NavIslandsConnection {
+                    from: portal(1),
+                    to: portal(2),
+                    distance: 3.0,
+                },
+            ],
+            false,
+        );
+        let from = islands.index(&portal(0)).unwrap();
+        let to = islands.index(&portal(2)).unwrap();
+        let path = islands.find_graph_path(from, to).unwrap();
+        assert_eq!(path.length, 5.0);
+        assert!(islands.find_graph_path(to, from).is_none());
+        assert_eq!(islands.find_graph_islands().len(), 3);
+
+        let mut grid = NavHexGrid::hexagon(2, NavHexOrientation::Flat);
+        grid.set_cell_walkable((0, 0), false);
+        grid.set_cell_cost((1, -1), 3.0);
+        let expected = grid.find_path((-2, 0), (2, 0)).unwrap();
+        let (from, to) = (grid.index((-2, 0)).unwrap(), grid.index((2, 0)).unwrap());
+        let path = grid.find_graph_path(from, to).unwrap();
+        assert_eq!(path.points.len(), expected.points.len());
+        assert_relative_eq!(path.cost, expected.cost);
+        assert_eq!(grid.find_graph_islands().len(), 2);
+
+        let mut grid = NavVoxelGrid::filled(3, 3, 2, true, NavVoxelConnectivity::Six).unwrap();
+        grid.fill_box((1, 0, 0), (1, 2, 2), false);
+        let expected = grid.find_path((0, 0, 0), (2, 0, 1)).unwrap();
+        let (from, to) = (grid.index(0, 0, 0).unwrap(), grid.index(2, 0, 1).unwrap());
+        let path = grid.find_graph_path(from, to).unwrap();
+        assert_eq!(
+            path.points
+                .iter()
+                .map(|n| grid.coord(*n).unwrap())
+                .collect::<Vec<_>>(),
+            expected.points
+        );
+        assert_relative_eq!(path.cost, expected.cost);
+
+        let mut grid = NavChunkedGrid::new(2).unwrap();
+        for coord in [(-1, 0), (1, 0)] {
+            grid.load_chunk(NavGridChunk::filled(coord, 2, true).unwrap())
+                .unwrap();
+        }
+        let (from, to) = (grid.index((-2, 0)).unwrap(), grid.index((3, 1)).unwrap());
+        assert!(grid.find_graph_path(from, to).is_none());
+        grid.load_chunk(NavGridChunk::filled((0, 0), 2, true).unwrap())
+            .unwrap();
+        let expected = grid.find_path((-2, 0), (3, 1)).unwrap();
+        let path = grid.find_graph_path(from, to).unwrap();
+        assert_eq!(grid.coord(path.points[0]), Some((-2, 0)));
+        assert_eq!(path.points.len(), expected.points.len());
+        assert_relative_eq!(path.cost, expected.cost);
+        assert_eq!(grid.index((9, 9)), None);
+
+        // neighbours outside of graph nodes are skipped.
+        struct Line(usize);
+
+        impl NavGraph for Line {
+            fn node_count(&self) -> usize {
+                self.0
+            }
+
+            fn neighbors(&self, node: usize) -> impl Iterator<Item = (usize, Scalar)> + '_ {
+                [(node + 1, 1.0), (node + 100, 1.0)].into_iter()
+            }
+
+            fn node_position(&self, _node: usize) -> Option<NavVec3> {
+                None
+            }
+        }
+
+        let line = Line(3);
+        assert_eq!(line.find_graph_path(0, 2).unwrap().points, vec![0, 1, 2]);
+        assert!(line.find_graph_path(2, 0).is_none());
+        assert!(line.find_graph_path(0, 100).is_none());
+        assert_eq!(line.find_graph_islands().len(), 3);
+    }
+
+    #[test]
+    fn test_navigable() {
+        fn avoid<N>(
+            nav: &mut N,
+            from: N::Point,
+            to: N::Point,
+            node: N::Node,
+        ) -> Option<NavPath<N::Point>>
+        where
+            N: Navigable,
+            N::Node: PartialEq,
+        {
+            assert_eq!(nav.find_islands().len(), 1);
+            assert!(nav.neighbors(node.clone()).count() > 0);
+            assert_eq!(nav.set_node_cost(node.clone(), 4.0), Some(1.0));
+            assert_eq!(nav.node_cost(node.clone()), Some(4.0));
+            nav.set_cost_model(NavCostModel::Additive);
+            assert_eq!(nav.cost_model(), NavCostModel::Additive);
+            assert!(nav.find_path(from.clone(), to.clone()).is_some());
+            nav.find_path_custom(from, to, |a, b, _| a != node && b != node)
+        }
+
+        let mut grid = NavGrid::new(3, 2, vec![true; 6]).unwrap();
+        let path = avoid(&mut grid, (0, 0), (2, 0), (1, 0)).unwrap();
+        assert_eq!(path.points, vec![(0, 0), (0, 1), (1, 1), (2, 1), (2, 0)]);
+
+        let mut grid = NavFreeGrid::from_cells([(0, 0), (1, 0), (2, 0)], NavGridConnectivity::Four);
+        assert!(avoid(&mut grid, (0, 0), (2, 0), (1, 0)).is_none());
+
+        let mut net = NavNet::new(
+            vec![
+                (0.0, 0.0, 0.0).into(),
+                (1.0, 0.0, 0.0).into(),
+                (2.0, 0.0, 0.0).into(),
+                (1.0, 1.0, 0.0).into(),
+                (0.0, -1.0, 0.0).into(),
+                (2.0, -1.0, 0.0).into(),
+            ],
+            vec![
+                NavConnection(0, 1),
+                NavConnection(1, 2),
+                NavConnection(0, 3),
+                NavConnection(3, 2),
+                NavConnection(4, 0),
+                NavConnection(2, 5),
+            ],
+        )
+        .unwrap();
+        let path = avoid(
+            &mut net,
+            (0.0, -1.0, 0.0).into(),
+            (2.0, -1.0, 0.0).into(),
+            1,
+        )
+        .unwrap();
+        assert!(path.points.contains(&(1.0, 1.0, 0.0).into()));
+
+        let portal = |island| NavIslandPortal::<u8, ()> {
+            island,
+            portal: None,
+        };
+        let mut islands = NavIslands::new(
+            vec![
+                NavIslandsConnection {
+                    from: portal(0),
+                    to: portal(1),
+                    distance: 1.0,
+                },
+                NavIslandsConnection {
+                    from: portal(1),
+                    to: portal(2),
+                    distance: 1.0,
+                },
+            ],
+            true,
+        );
+        assert!(avoid(&mut islands, portal(0), portal(2), portal(1)).is_none());
+
+        let mut grid = NavHexGrid::hexagon(1, NavHexOrientation::Pointy);
+        let path = avoid(&mut grid, (-1, 0), (1, 0), (0, 0)).unwrap();
+        assert_eq!(path.points.len(), 4);
+
+        let mut grid = NavVoxelGrid::filled(3, 2, 1, true, NavVoxelConnectivity::Six).unwrap();
+        let path = avoid(&mut grid, (0, 0, 0), (2, 0, 0), (1, 0, 0)).unwrap();
+        assert_eq!(path.points.len(), 5);
+
+        let mut grid = NavChunkedGrid::new(2).unwrap();
+        grid.load_chunk(NavGridChunk::filled((0, 0), 2, true).unwrap())
+            .unwrap();
+        grid.load_chunk(NavGridChunk::filled((1, 0), 2, true).unwrap())
+            .unwrap();
+        let path = avoid(&mut grid, (0, 0), (3, 0), (2, 0)).unwrap();
+        assert!(!path.points.contains(&(2, 0)));
+    }
+
+    #[test]
+    fn test_nav_path() {
+        let mut grid = NavGrid::new(
+            3,
+            3,
+            vec![true, true, true, false, false, true, true, false, true],
+        )
+        .unwrap();
+        grid.set_cell_cost(1, 0, 2.0);
+        let path = grid.find_path((0, 0), (2, 2)).unwrap();
+        assert_eq!(path.points, vec![(0, 0), (1, 0), (2, 0), (2, 1), (2, 2)]);
+        assert_eq!(path.length, 4.0);
+        assert!(path.cost > path.length);
+        assert!(!path.partial);
+        assert_eq!(grid.find_path_partial((0, 0), (2, 2)), Some(path));
+
+        assert_eq!(grid.find_path((0, 0), (1, 2)), None);
+        let path = grid.find_path_partial((0, 0), (1, 2)).unwrap();
+        assert!(path.partial);
+        assert_eq!(path.points.last(), Some(&(2, 2)));
+
+        let world = grid.find_path_world(grid.cell_to_world((0, 0)), grid.cell_to_world((2, 2)));
+        assert_relative_eq!(world.unwrap().length, 4.0);
+
+        let net = NavNet::new(
+            vec![
+                (0.0, 0.0, 0.0).into(),
+                (3.0, 0.0, 0.0).into(),
+                (3.0, 4.0, 0.0).into(),
+            ],
+            vec![NavConnection(0, 1), NavConnection(1, 2)],
+        )
+        .unwrap();
+        let path = net
+            .find_path((0.0, 0.0, 0.0).into(), (3.0, 4.0, 0.0).into())
+            .unwrap();
+        assert_relative_eq!(path.length, 7.0);
+
+        // cost matches path points also when they lie on connections.
+        let net = NavNet::new(
+            vec![
+                (0.0, 0.0, 0.0).into(),
+                (10.0, 0.0, 0.0).into(),
+                (20.0, 0.0, 0.0).into(),
+                (10.0, 10.0, 0.0).into(),
+            ],
+            vec![
+                NavConnection(0, 1),
+                NavConnection(1, 2),
+                NavConnection(1, 3),
+            ],
+        )
+        .unwrap();
+        let path = net
+            .find_path((1.0, 0.0, 0.0).into(), (5.0, 0.0, 0.0).into())
+            .unwrap();
+        assert_eq!(
+            path.points,
+            vec![(1.0, 0.0, 0.0).into(), (5.0, 0.0, 0.0).into()]
+        );
+        assert_relative_eq!(path.cost, 4.0);
+        assert_relative_eq!(path.length, 4.0);
+        let mut distances = vec![];
+        let path = net.find_path_custom(
+            (1.0, 0.0, 0.0).into(),
+            (5.0, 0.0, 0.0).into(),
+            |distance, _, _| {
+                distances.push(distance);
+                false
+            },
+        );
+        assert_eq!(path, None);
+        assert_eq!(distances, vec![16.0]);
+        let path = net
+            .find_path((2.0, 0.0, 0.0).into(), (10.0, 8.0, 0.0).into())
+            .unwrap();
+        assert_eq!(
+            path.points,
+            vec![
+                (2.0, 0.0, 0.0).into(),
+                (10.0, 0.0, 0.0).into(),
+                (10.0, 8.0, 0.0).into(),
+            ]
+        );
+        assert_relative_eq!(path.cost, 16.0);
+        assert_relative_eq!(path.length, 16.0);
+        let path = net
+            .find_path((2.0, 1.0, 0.0).into(), (15.0, -1.0, 0.0).into())
+            .unwrap();
+        assert_relative_eq!(path.cost, 13.0);
+        assert_relative_eq!(path.length, 13.0);
+    }
+
+    #[test]
+    fn test_find_path_to_any() {
+        let grid = NavGrid::new(
+            5,
+            2,
+            vec![true, true, true, true, true, true, false, true, true, true],
+        )
+        .unwrap();
+        let (target, path) = grid
+            .find_path_to_any((1, 0), &[(4, 0), (0, 1), (9, 9)])
+            .unwrap();
+        assert_eq!(target, 1);
+        assert_eq!(path.points, vec![(1, 0), (0, 0), (0, 1)]);
+        assert!(grid.find_path_to_any((1, 0), &[(1, 1), (9, 9)]).is_none());
+        assert!(grid.find_path_to_any((1, 0), &[]).is_none());
+
+        let net = NavNet::new(
+            vec![
+                (0.0, 0.0, 0.0).into(),
+                (1.0, 0.0, 0.0).into(),
+                (2.0, 0.0, 0.0).into(),
+                (2.0, 1.0, 0.0).into(),
+                (5.0, 1.0, 0.0).into(),
+            ],
+            vec![
+                NavConnection(0, 1),
+                NavConnection(1, 2),
+                NavConnection(2, 3),
+                NavConnection(3, 4),
+            ],
+        )
+        .unwrap();
+        let (target, path) = net
+            .find_path_to_any(
+                (0.0, 0.0, 0.0).into(),
+                &[(5.0, 1.0, 0.0).into(), (2.0, 1.0, 0.0).into()],
+            )
+            .unwrap();
+        assert_eq!(target, 1);
+        assert_eq!(path.points.last(), Some(&(2.0, 1.0, 0.0).into()));
+        assert_eq!(
+            net.find_path_to_any((0.0, 0.0, 0.0).into(), &[(5.0, 1.0, 0.0).into()]),
+            net.find_path((0.0, 0.0, 0.0).into(), (5.0, 1.0, 0.0).into())
+                .map(|path| (0, path))
+        );
+
+        // target lying on start connection does not win over closer one behind vertex.
+        let net = NavNet::new(
+            vec![
+                (0.0, 0.0, 0.0).into(),
+                (10.0, 0.0, 0.0).into(),
+                (10.0, 10.0, 0.0).into(),
+            ],
+            vec![NavConnection(0, 1), NavConnection(1, 2)],
+        )
+        .unwrap();
+        let far = (0.5, 0.0, 0.0).into();
+        let near = (10.0, 0.5, 0.0).into();
+        let (target, path) = net
+            .find_path_to_any((9.5, 0.0, 0.0).into(), &[far, near])
+            .unwrap();
+        assert_eq!(target, 1);
+        assert_eq!(
+            path.points,
+            vec![(9.5, 0.0, 0.0).into(), (10.0, 0.0, 0.0).into(), near]
+        );
+        assert_relative_eq!(path.cost, 1.0);
+        let (target, path) = net
+            .find_path_to_any((9.5, 0.0, 0.0).into(), &[near, far])
+            .unwrap();
+        assert_eq!(target, 0);
+        assert_relative_eq!(path.cost, 1.0);
+        let (target, path) = net
+            .find_path_to_any((1.5, 0.0, 0.0).into(), &[near, far])
+            .unwrap();
+        assert_eq!(target, 1);
+        assert_eq!(path.points, vec![(1.5, 0.0, 0.0).into(), far]);
+        assert_relative_eq!(path.cost, 1.0);
+    }
+
+    #[test]
+    fn test_flow_field() {
+        let mut grid = NavGrid::new(
+            4,
+            3,
+            vec![
+                true, true, true, true, //
+                true, false, false, true, //
+                true, true, true, true, //
+            ],
+        )
+        .unwrap();
+        grid.set_cell_cost(3, 1, 3.0);
+        let field = grid.flow_field((3, 2), |_, _| true).unwrap();
+        for row in 0..3 {
+            for col in 0..4 {
+                let expected = grid
+                    .find_path((col, row), (3, 2))
+                    .map(|path| path.points.len());
+                assert_eq!(field.path(col, row).map(|path| path.len()), expected);
+            }
+        }
+        assert_eq!(field.cost(0, 0), Some(5.0));
+        assert_eq!(field.direction(3, 0), Some((0, 1)));
+        assert!(grid.flow_field((1, 1), |_, _| true).is_none());
+        let field = grid.flow_field((3, 2), |from, _| from != (0, 1)).unwrap();
+        assert_eq!(field.next(0, 0), Some((1, 0)));
+
+        let grid = NavGrid::with_connections(
+            3,
+            1,
+            vec![
+                NavGridConnection::new((0, 0), (1, 0)),
+                NavGridConnection::new((1, 0), (2, 0)),
+            ],
+        )
+        .unwrap();
+        let field = grid.flow_field((1, 0), |_, _| true).unwrap();
+        assert_eq!(field.next(0, 0), Some((1, 0)));
+        assert_eq!(field.cost(2, 0), None);
+        let json = serde_json::to_string(&field).unwrap();
+        let field: NavFlowField = serde_json::from_str(&json).unwrap();
+        assert_eq!(field.cost(0, 0), Some(1.0));
+        assert_eq!(field.cost(2, 0), None);
+        assert_eq!(field.path(2, 0), None);
+
+        let mesh = NavMesh::new(
+            vec![
+                (0.0, 0.0, 0.0).into(),
+                (1.0, 0.0, 0.0).into(),
+                (1.0, 1.0, 0.0).into(),
+                (0.0, 1.0, 0.0).into(),
+                (5.0, 0.0, 0.0).into(),
+                (6.0, 0.0, 0.0).into(),
+                (6.0, 1.0, 0.0).into(),
+            ],
+            vec![(0, 1, 2).into(), (2, 3, 0).into(), (4, 5, 6).into()],
+        )
+        .unwrap();
+        let field = mesh.distance_field(0).unwrap();
+        let json = serde_json::to_string(&field).unwrap();
+        let loaded: NavDistanceField = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.cost(1), field.cost(1));
+        assert!(loaded.cost(1).is_some());
+        assert_eq!(loaded.cost(2), None);
+        assert_eq!(loaded.path(1), Some(vec![1, 0]));
+    }
+
+    #[test]
+    fn test_grid_connectivity() {
+        let cells = vec![true, false, true, true, true, true, true, true, true];
+        let grid = NavGrid::new(3, 3, cells.clone()).unwrap();
+        assert_eq!(grid.connectivity(), Some(NavGridConnectivity::Four));
+        assert_eq!(grid.find_path((0, 0), (2, 0)).unwrap().points.len(), 5);
+        let grid =
+            NavGrid::with_connectivity(3, 3, cells.clone(), NavGridConnectivity::Eight).unwrap();
+        assert_eq!(grid.neighbors(1, 1).unwrap().count(), 7);
+        assert_eq!(
+            grid.find_path((0, 0), (2, 0)).unwrap().points,
+            vec![(0, 0), (1, 1), (2, 0)]
+        );
+        assert_eq!(
+            grid.find_path((0, 2), (2, 2)).unwrap().points,
+            vec![(0, 2), (1, 2), (2, 2)]
+        );
+        let grid =
+            NavGrid::with_connectivity(3, 3, cells, NavGridConnectivity::EightNoCornerCutting)
+                .unwrap();
+        assert_eq!(grid.neighbors(1, 1).unwrap().count(), 5);
+        assert_eq!(grid.find_path((0, 0), (2, 0)).unwrap().points.len(), 5);
+        assert_eq!(
+            grid.find_path((0, 1), (2, 2)).unwrap().points,
+            vec![(0, 1), (1, 2), (2, 2)]
+        );
+        assert_relative_eq!(
+            NavGridConnectivity::Eight.distance((0, 0), (3, 1)),
+            2.0 + SCALAR_SQRT_2
+        );
+        assert_relative_eq!(NavGridConnectivity::Four.distance((0, 0), (3, 1)), 4.0);
+    }
+
+    #[test]
+    fn test_jump_point() {
+        fn path_length(grid: &NavGrid, path: &[(usize, usize)]) -> Scalar {
+            path.windows(2)
+                .map(|pair| {
+                    assert!(grid
+                        .neighbors(pair[0].0, pair[0].1)
+                        .unwrap()
+                        .any(|n| n == pair[1]));
+                    if pair[0].0 != pair[1].0 && pair[0].1 != pair[1].1 {
+                        SCALAR_SQRT_2
+                    } else {
+                        1.0
+                    }
+                })
+                .sum()
+        }
+
+        let mut seed = 42u32;
+        let mut random = move || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 16) % 100
+        };
+        for connectivity in [
+            NavGridConnectivity::Four,
+            NavGridConnectivity::Eight,
+            NavGridConnectivity::EightNoCornerCutting,
+        ] {
+            for _ in 0..20 {
+                let cells = (0..16 * 16).map(|_| random() >= 30).collect::<Vec<_>>();
+                let mut grid = NavGrid::with_connectivity(16, 16, cells, connectivity).unwrap();
+                for _ in 0..10 {
+                    let from = ((random() % 16) as usize, (random() % 16) as usize);
+                    let to = ((random() % 16) as usize, (random() % 16) as usize);
+                    grid.set_search_mode(NavGridSearchMode::AStar);
+                    let expected = grid.find_path(from, to).map(|path| path.points);
+                    grid.set_search_mode(NavGridSearchMode::Auto);
+                    let path = grid.find_path(from, to).map(|path| path.points);
+                    assert_eq!(path.is_some(), expected.is_some());
+                    if let (Some(path), Some(expected)) = (path, expected) {
+                        assert_eq!(path.first(), Some(&from));
+                        assert_eq!(path.last(), Some(&to));
+                        assert_relative_eq!(
+                            path_length(&grid, &path),
+                            path_length(&grid, &expected),
+                            epsilon = 1e-4
+                        );
+                    }
+                }
+            }
+        }
+
+        let mut grid = NavGrid::new(3, 2, vec![true; 6]).unwrap();
+        grid.set_cell_cost(1, 0, 10.0);
+        assert_eq!(grid.find_path((0, 0), (2, 0)).unwrap().points.len(), 5);
+        grid.set_search_mode(NavGridSearchMode::JumpPoint);
+        assert_eq!(
+            grid.find_path((0, 0), (2, 0)).unwrap().points,
+            vec![(0, 0), (1, 0), (2, 0)]
+        );
+    }
+
+    #[test]
+    fn test_any_angle() {
+        let grid = NavGrid::new(5, 5, vec![true; 25]).unwrap();
+        assert!(grid.line_of_sight((0, 0), (4, 2)));
+        assert_eq!(
+            grid.find_path_any_angle((0, 0), (4, 2)).unwrap().points,
+            vec![(0.5, 0.5), (4.5, 2.5)]
+        );
+        let path = grid.find_path((0, 0), (4, 2)).unwrap().points;
+        assert_eq!(grid.string_pull(&path), vec![(0.5, 0.5), (4.5, 2.5)]);
+
+        let grid = NavGrid::new(
+            5,
+            5,
+            vec![
+                true, true, false, true, true, //
+                true, true, false, true, true, //
+                true, true, false, true, true, //
+                true, true, false, true, true, //
+                true, true, true, true, true, //
+            ],
+        )
+        .unwrap();
+        assert!(!grid.line_of_sight((0, 0), (4, 0)));
+        assert!(!grid.line_of_sight((1, 3), (3, 4)));
+        let path = grid.find_path_any_angle((0, 0), (4, 0)).unwrap().points;
+        assert_eq!(path.first(), Some(&(0.5, 0.5)));
+        assert_eq!(path.last(), Some(&(4.5, 0.5)));
+        assert!(path.len() < 9);
+        let pulled = grid.string_pull(&grid.find_path((0, 0), (4, 0)).unwrap().points);
+        assert!(pulled.len() < 9);
+        for points in [path, pulled] {
+            for pair in points.windows(2) {
+                let from = (pair[0].0 as usize, pair[0].1 as usize);
+                let to = (pair[1].0 as usize, pair[1].1 as usize);
+                assert!(grid.line_of_sight(from, to));
+            }
+        }
+
+        let grid = NavGrid::new(2, 2, vec![true, false, false, true]).unwrap();
+        assert!(!grid.line_of_sight((0, 0), (1, 1)));
+        assert!(grid.find_path_any_angle((0, 0), (1, 1)).is_none());
+    }
+
+    #[test]
+    fn test_dynamic_cells() {
+        let mut grid = NavGrid::new(5, 5, vec![true; 25]).unwrap();
+        grid.fill_rect(2, 0, 1, 5, false);
+        assert_eq!(grid.find_islands().len(), 2);
+        assert!(grid.find_path((0, 0), (4, 0)).is_none());
+        assert_eq!(grid.set_cell_walkable(2, 2, true), Some(false));
+        assert_eq!(grid.find_islands().len(), 1);
+        assert_eq!(grid.find_path((0, 2), (4, 2)).unwrap().points.len(), 5);
+        assert_eq!(grid.set_cell_walkable(2, 2, false), Some(true));
+        assert_eq!(grid.find_islands().len(), 2);
+        assert!(grid.find_path((0, 2), (4, 2)).is_none());
+        assert_eq!(grid.set_cell_walkable(5, 0, true), None);
+
+        grid.fill_rect(0, 0, 5, 5, true);
+        grid.fill_circle(2, 2, 1.0, false);
+        assert!(!grid.cells()[2 * 5 + 2]);
+        assert!(!grid.cells()[5 + 2]);
+        assert!(grid.cells()[5 + 1]);
+        assert_eq!(grid.find_islands().len(), 1);
+        assert_eq!(grid.find_path((1, 1), (3, 1)).unwrap().points.len(), 5);
+
+        let mut grid = NavGrid::with_connectivity(
+            2,
+            2,
+            vec![true; 4],
+            NavGridConnectivity::EightNoCornerCutting,
+        )
+        .unwrap();
+        assert_eq!(grid.find_path((0, 0), (1, 1)).unwrap().points.len(), 2);
+        grid.set_cell_walkable(1, 0, false);
+        assert_eq!(grid.find_path((0, 0), (1, 1)).unwrap().points.len(), 3);
+        grid.set_cell_walkable(1, 0, true);
+        assert_eq!(grid.find_path((0, 0), (1, 1)).unwrap().points.len(), 2);
+
+        let mut grid = NavGrid::with_connections(
+            2,
+            2,
+            vec![
+                NavGridConnection::new((0, 0), (1, 0)),
+                NavGridConnection::new((1, 0), (1, 1)),
+            ],
+        )
+        .unwrap();
+        grid.set_cell_walkable(1, 0, false);
+        assert!(grid.find_path((0, 0), (1, 1)).is_none());
+        grid.set_cell_walkable(1, 0, true);
+        assert_eq!(grid.find_path((0, 0), (1, 1)).unwrap().points.len(), 3);
+    }
+
+    #[test]
+    fn test_free_grid_editing() {
+        let connections = (0..100_000)
+            .map(|i| NavFreeGridConnection::new((i, -i), (i + 1, -i - 1)))
+            .collect::<Vec<_>>();
+        let grid = NavFreeGrid::new(connections);
+        assert_eq!(grid.cells().len(), 100_001);
+        assert_eq!(grid.index(500, -500), Some(500));
+        assert_eq!(grid.find_path((0, 0), (9, -9)).unwrap().points.len(), 10);
+
+        let mut grid = NavFreeGrid::new(vec![NavFreeGridConnection::new((0, 0), (1, 0))]);
+        assert_eq!(grid.add_cell(1, 0), 1);
+        assert_eq!(grid.add_cell(5, 5), 2);
+        assert!(grid.find_path((0, 0), (5, 5)).is_none());
+        assert!(grid.add_connection(NavFreeGridConnection::new((1, 0), (2, 0))));
+        assert!(!grid.add_connection(NavFreeGridConnection::new((2, 0), (1, 0))));
+        assert!(grid.add_connection(NavFreeGridConnection::new((2, 0), (5, 5))));
+        assert_eq!(
+            grid.find_path((0, 0), (5, 5)).unwrap().points,
+            vec![(0, 0), (1, 0), (2, 0), (5, 5)]
+        );
+        assert!(grid.remove_connection(NavFreeGridConnection::new((2, 0), (1, 0))));
+        assert!(!grid.remove_connection(NavFreeGridConnection::new((2, 0), (1, 0))));
+        assert!(grid.find_path((0, 0), (5, 5)).is_none());
+        assert!(grid.add_connection(NavFreeGridConnection::new((0, 0), (2, 0))));
+        grid.set_cell_cost(2, 0, 3.0);
+        assert!(grid.remove_cell(1, 0));
+        assert!(!grid.remove_cell(1, 0));
+        assert_eq!(grid.cells().len(), 3);
+        assert_eq!(grid.index(1, 0), None);
+        for (index, cell) in grid.cells().iter().enumerate() {
+            assert_eq!(grid.index(cell.0, cell.1), Some(index));
+        }
+        assert_eq!(grid.cells_costs()[grid.index(2, 0).unwrap()], 3.0);
+        assert_eq!(
+            grid.find_path((0, 0), (5, 5)).unwrap().points,
+            vec![(0, 0), (2, 0), (5, 5)]
+        );
+    }
+
+    #[test]
+    fn test_grid_connections_costs() {
+        // conveyor belt along top row and slope between bottom corners.
+        let grid = NavGrid::with_connections(
+            3,
+            2,
+            vec![
+                NavGridConnection::new((0, 0), (1, 0)).with_cost(0.1),
+                NavGridConnection::new((1, 0), (2, 0)).with_cost(0.1),
+                NavGridConnection::new((0, 0), (0, 1)).with_both_ways(true),
+                NavGridConnection::new((0, 1), (2, 1)).with_cost(5.0),
+                NavGridConnection::new((2, 1), (0, 1)).with_cost(1.0),
+                NavGridConnection::new((2, 0), (2, 1)).with_both_ways(true),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            grid.find_path((0, 1), (2, 1)).unwrap().points,
+            vec![(0, 1), (0, 0), (1, 0), (2, 0), (2, 1)]
+        );
+        assert_eq!(
+            grid.find_path((2, 1), (0, 0)).unwrap().points,
+            vec![(2, 1), (0, 1), (0, 0)]
+        );
+        assert_eq!(
+            grid.find_path((1, 0), (0, 0)).unwrap().points,
+            vec![(1, 0), (2, 0), (2, 1), (0, 1), (0, 0)]
+        );
+        let mut costs = vec![];
+        let path = grid
+            .find_path_custom((0, 1), (2, 1), |_, _, cost| {
+                costs.push(cost);
+                cost < 2.0
+            })
+            .unwrap();
+        assert_eq!(path.points.len(), 5);
+        assert!(costs.contains(&5.0));
+        assert_eq!(
+            grid.find_path_custom((0, 1), (2, 1), |_, to, _| to != (1, 0))
+                .map(|path| path.points),
+            Some(vec![(0, 1), (2, 1)])
+        );
+
+        let mut grid = NavFreeGrid::new(vec![
+            NavFreeGridConnection::new((0, 0), (1, 0)),
+            NavFreeGridConnection::new((1, 0), (2, 0)),
+            NavFreeGridConnection::new((0, 0), (2, 0))
+                .with_cost(3.0)
+                .with_both_ways(false),
+        ]);
+        assert_eq!(grid.find_path((0, 0), (2, 0)).unwrap().points.len(), 3);
+        assert!(!grid.add_connection(
+            NavFreeGridConnection::new((0, 0), (2, 0))
+                .with_cost(1.5)
+                .with_both_ways(false)
+        ));
+        assert_eq!(
+            grid.find_path((0, 0), (2, 0)).unwrap().points,
+            vec![(0, 0), (2, 0)]
+        );
+        assert_eq!(grid.find_path((2, 0), (0, 0)).unwrap().points.len(), 3);
+        assert!(grid
+            .remove_connection(NavFreeGridConnection::new((1, 0), (0, 0)).with_both_ways(false)));
+        assert_eq!(grid.find_path((1, 0), (0, 0)), None);
+        assert_eq!(grid.find_path((0, 0), (1, 0)).unwrap().points.len(), 2);
+        assert_eq!(
+            grid.find_path_custom((0, 0), (2, 0), |_, _, cost| cost < 1.5)
+                .map(|path| path.points),
+            Some(vec![(0, 0), (1, 0), (2, 0)])
+        );
+
+        // connections saved without cost and direction keep their old meaning.
+        let connection =
+            serde_json::from_str::<NavGridConnection>(r#"{"from":[0,0],"to":[1,0]}"#).unwrap();
+        assert_eq!(connection, NavGridConnection::new((0, 0), (1, 0)));
+        assert!(!connection.both_ways());
+        let connection =
+            serde_json::from_str::<NavFreeGridConnection>(r#"{"from":[0,0],"to":[1,0]}"#).unwrap();
+        assert_eq!(connection, NavFreeGridConnection::new((0, 0), (1, 0)));
+        assert!(connection.both_ways());
+        assert!(NavFreeGridConnection::default().both_ways());
+        assert_eq!(
+            NavGridConnection::new((0, 0), (1, 0))
+                .with_cost(Scalar::NAN)
+                .cost(),
+            Some(0.0)
+        );
+        let connection = serde_json::from_str::<NavFreeGridConnection>(
+            r#"{"from":[0,0],"to":[1,0],"cost":-2.0}"#,
+        )
+        .unwrap();
+        assert_eq!(connection.cost(), Some(0.0));
+        assert_eq!(
+            connection,
+            NavFreeGridConnection::new((0, 0), (1, 0)).with_cost(-2.0)
+        );
+        let connection = serde_json::from_str::<NavGridConnection>(
+            r#"{"from":[0,0],"to":[1,0],"cost":2.5,"both_ways":true}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            connection,
+            NavGridConnection::new((0, 0), (1, 0))
+                .with_cost(2.5)
+                .with_both_ways(true)
+        );
+    }
+
+    #[test]
+    fn test_free_grid_from_cells() {
+        let cells = [(0, 0), (1, 0), (0, 1), (1, 1), (2, 2), (-1, 0)];
+        let grid = NavFreeGrid::from_cells(cells, NavGridConnectivity::Four);
+        assert_eq!(grid.cells().len(), 6);
+        let mut neighbors = grid.neighbors(0, 0).unwrap().collect::<Vec<_>>();
+        neighbors.sort();
+        assert_eq!(neighbors, vec![(-1, 0), (0, 1), (1, 0)]);
+        assert_eq!(grid.neighbors(2, 2).unwrap().count(), 0);
+        assert!(grid.find_path((0, 0), (2, 2)).is_none());
+
+        let grid = NavFreeGrid::from_cells(cells, NavGridConnectivity::Eight);
+        assert_eq!(
+            grid.find_path((0, 0), (2, 2)).unwrap().points,
+            vec![(0, 0), (1, 1), (2, 2)]
+        );
+        assert_eq!(grid.neighbors(-1, 0).unwrap().count(), 2);
+        let grid = NavFreeGrid::from_cells(cells, NavGridConnectivity::EightNoCornerCutting);
+        assert!(grid.find_path((0, 0), (2, 2)).is_none());
+        assert_eq!(grid.neighbors(-1, 0).unwrap().count(), 1);
+
+        let grid = NavFreeGrid::from_ascii_map(
+            "
+            ..#..
+            .##.#
+            .....
+            ",
+        );
+        assert_eq!(grid.cells().len(), 11);
+        assert_eq!(grid.index(2, 0), None);
+        assert_eq!(
+            grid.find_path((1, 0), (3, 0)).unwrap().points,
+            vec![
+                (1, 0),
+                (0, 0),
+                (0, 1),
+                (0, 2),
+                (1, 2),
+                (2, 2),
+                (3, 2),
+                (3, 1),
+                (3, 0)
+            ]
+        );
+        assert_eq!(
+            grid.neighbors(4, 0).unwrap().collect::<Vec<_>>(),
+            vec![(3, 0)]
+        );
+    }
+
+    #[test]
+    fn test_chunked_grid() {
+        assert!(matches!(
+            NavChunkedGrid::new(0),
+            Err(Error::EmptyCells(0, 0))
+        ));
+        let mut grid = NavChunkedGrid::with_connectivity(4, NavGridConnectivity::Eight).unwrap();
+        assert!(matches!(
+            grid.load_chunk(NavGridChunk::filled((0, 0), 3, true).unwrap()),
+            Err(Error::ChunkSizeDoesNotMatch(3, 4))
+        ));
+        assert_eq!(grid.chunk_coord((-1, 4)), (-1, 1));
+        assert_eq!(grid.chunk_coord((-5, -4)), (-2, -1));
+        for coord in [(-1, -1), (0, -1), (-1, 0), (0, 0)] {
+            assert!(grid
+                .load_chunk(NavGridChunk::filled(coord, 4, true).unwrap())
+                .unwrap()
+                .is_none());
+        }
+        assert!(!grid.is_cell_walkable((4, 0)));
+        assert!(!grid.set_cell_walkable((4, 0), false));
+        assert!(grid.is_chunk_loaded((1, 0)));
+        assert!(!grid.is_cell_walkable((5, 0)));
+        let path = grid.find_path((-4, -4), (3, 3)).unwrap().points;
+        assert_eq!(path.len(), 8);
+        assert_eq!(path[0], (-4, -4));
+        assert_eq!(path[7], (3, 3));
+
+        // wall along chunks border with single gap.
+        for row in -4..4 {
+            if row != 2 {
+                assert!(grid.set_cell_walkable((0, row), false));
+            }
+        }
+        let path = grid.find_path((-2, -3), (2, -3)).unwrap().points;
+        assert!(path.contains(&(0, 2)));
+        assert!(grid
+            .find_path_custom((-2, -3), (2, -3), |_, to, _| to != (0, 2))
+            .is_none());
+        assert_eq!(grid.set_cell_cost((0, 2), 100.0), 1.0);
+        assert!(grid
+            .find_path_custom((-2, -3), (2, -3), |_, _, cost| cost < 50.0)
+            .is_none());
+        let path = grid
+            .find_path_with_cost((-2, -3), (2, -3), &NavDefaultCost)
+            .unwrap()
+            .points;
+        assert!(path.contains(&(0, 2)));
+
+        // unloading chunk with gap splits the world until it gets streamed back.
+        let chunk = grid.unload_chunk((0, 0)).unwrap();
+        assert!(grid.unload_chunk((0, 0)).is_none());
+        assert_eq!(grid.find_path((-2, -3), (2, -3)), None);
+        grid.load_chunk(NavGridChunk::filled((5, 5), 4, false).unwrap())
+            .unwrap();
+        assert_eq!(grid.chunks().count(), 5);
+        grid.load_chunk(chunk).unwrap();
+        assert_eq!(grid.chunk((0, 0)).unwrap().cells_costs()[2 * 4], 100.0);
+        let path = grid.find_path((-2, -3), (2, -3)).unwrap().points;
+        assert!(path.contains(&(0, 2)));
+        assert_eq!(
+            grid.world_to_cell(grid.cell_to_world((-3, 2))),
+            Some((-3, 2))
+        );
+        assert_eq!(grid.world_to_cell(grid.cell_to_world((9, 2))), None);
+
+        // cells of chunks not loaded yet create them.
+        assert_eq!(grid.set_cell_cost((-9, 20), 2.0), 1.0);
+        assert!(!grid.set_cell_walkable((-9, 20), true));
+        assert!(grid.is_cell_walkable((-9, 20)));
+        assert!(!grid.is_cell_walkable((-10, 20)));
+        assert_eq!(grid.chunk((-3, 5)).unwrap().cells_costs()[3], 2.0);
+
+        let loaded =
+            serde_json::from_str::<NavChunkedGrid>(&serde_json::to_string(&grid).unwrap()).unwrap();
+        assert_eq!(loaded.chunks().count(), grid.chunks().count());
+        assert!(loaded.is_cell_walkable((-9, 20)));
+        assert_eq!(
+            loaded.find_path((-2, -3), (2, -3)),
+            grid.find_path((-2, -3), (2, -3))
+        );
+    }
+
+    #[test]
+    fn test_replanner() {
+        fn path_cost(grid: &NavGrid, path: &[(usize, usize)]) -> Scalar {
+            path.windows(2)
+                .map(|pair| {
+                    assert!(grid
+                        .neighbors(pair[0].0, pair[0].1)
+                        .unwrap()
+                        .any(|n| n == pair[1]));
+                    let weight = if pair[0].0 != pair[1].0 && pair[0].1 != pair[1].1 {
+                        SCALAR_SQRT_2
+                    } else {
+                        1.0
+                    };
+                    let a = grid.index(pair[0].0, pair[0].1).unwrap();
+                    let b = grid.index(pair[1].0, pair[1].1).unwrap();
+                    grid.cost_model()
+                        .combine(weight, grid.cells_costs()[a], grid.cells_costs()[b])
+                })
+                .sum()
+        }
+
+        let mut seed = 7u32;
+        let mut random = move || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 16) % 100
+        };
+        for connectivity in [
+            NavGridConnectivity::Four,
+            NavGridConnectivity::Eight,
+            NavGridConnectivity::EightNoCornerCutting,
+        ] {
+            for _ in 0..10 {
+                let cells = (0..16 * 16).map(|_| random() >= 25).collect::<Vec<_>>();
+                let mut grid = NavGrid::with_connectivity(16, 16, cells, connectivity).unwrap();
+                grid.set_search_mode(NavGridSearchMode::AStar);
+                let mut from = ((random() % 16) as usize, (random() % 16) as usize);
+                let to = ((random() % 16) as usize, (random() % 16) as usize);
+                let mut planner = NavGridReplanner::new(&grid, from, to).unwrap();
+                for _ in 0..10 {
+                    let path = planner.find_path(&grid).map(|path| path.points);
+                    let expected = grid.find_path(from, to).map(|path| path.points);
+                    assert_eq!(path.is_some(), expected.is_some());
+                    if let (Some(path), Some(expected)) = (path, expected) {
+                        assert_eq!(path.first(), Some(&from));
+                        assert_eq!(path.last(), Some(&to));
+                        assert_relative_eq!(
+                            path_cost(&grid, &path),
+                            path_cost(&grid, &expected),
+                            epsilon = 1e-3
+                        );
+                        if path.len() > 1 {
+                            from = path[1];
+                            planner.set_from(&grid, from).unwrap();
+                        }
+                    }
+                    let mut changed = vec![];
+                    for _ in 0..4 {
+                        let cell = ((random() % 16) as usize, (random() % 16) as usize);
+                        if cell == from || cell == to {
+                            continue;
+                        }
+                        if random() < 50 {
+                            grid.set_cell_walkable(cell.0, cell.1, random() < 50);
+                        } else {
+                            grid.set_cell_cost(cell.0, cell.1, 1.0 + (random() % 4) as Scalar);
+                        }
+                        changed.push(cell);
+                    }
+                    planner.cells_changed(&grid, &changed);
+                }
+            }
+        }
+
+        let grid = NavGrid::new(2, 2, vec![true; 4]).unwrap();
+        let other = NavGrid::new(2, 2, vec![true; 4]).unwrap();
+        let mut planner = NavGridReplanner::new(&grid, (0, 0), (1, 1)).unwrap();
+        assert!(planner.find_path(&other).is_none());
+        assert!(NavGridReplanner::new(&grid, (0, 0), (2, 2)).is_none());
+        // larger grid would index planner state out of bounds.
+        let other = NavGrid::new(4, 4, vec![true; 16]).unwrap();
+        assert!(planner.set_from(&other, (3, 3)).is_none());
+        planner.cells_changed(&other, &[(3, 3)]);
+        assert_eq!(planner.from(), (0, 0));
+        assert_eq!(planner.find_path(&grid).unwrap().points.len(), 3);
+    }
+
+    #[test]
+    fn test_grid_transform() {
+        let mut grid = NavGrid::new(3, 2, vec![true; 6]).unwrap();
+        assert_eq!(grid.cell_to_world((2, 1)), (2.5, 1.5, 0.0).into());
+        assert_eq!(grid.world_to_cell((2.5, 1.5, 0.0).into()), Some((2, 1)));
+        assert_eq!(grid.world_to_cell((3.5, 1.5, 0.0).into()), None);
+        assert_eq!(grid.world_to_cell((-0.5, 1.5, 0.0).into()), None);
+        assert!(matches!(
+            grid.set_transform(Some(NavGridTransform {
+                cell_size: 0.0,
+                ..Default::default()
+            })),
+            Err(Error::InvalidCellSize(_))
+        ));
+        assert!(grid
+            .set_transform(Some(NavGridTransform {
+                cell_size: Scalar::NAN,
+                ..Default::default()
+            }))
+            .is_err());
+        assert!(grid.transform().is_none());
+
+        grid.set_transform(Some(NavGridTransform {
+            origin: (1.0, 1.0, 2.0).into(),
+            cell_size: 2.0,
+            rotation: std::f64::consts::FRAC_PI_2 as Scalar,
+        }))
+        .unwrap();
+        let point = grid.cell_to_world((2, 1));
+        assert_relative_eq!(point.x, -2.0, epsilon = 1e-4);
+        assert_relative_eq!(point.y, 6.0, epsilon = 1e-4);
+        assert_relative_eq!(point.z, 2.0, epsilon = 1e-4);
+        assert_eq!(grid.world_to_cell((-2.0, 6.0, 0.0).into()), Some((2, 1)));
+        assert_eq!(grid.world_to_cell((2.0, 6.0, 0.0).into()), None);
+        let path = grid
+            .find_path_world((0.0, 2.0, 0.0).into(), (-2.0, 6.0, 0.0).into())
+            .unwrap()
+            .points;
+        assert_eq!(path.len(), 4);
+        assert_eq!(path[0], grid.cell_to_world((0, 0)));
+        assert_eq!(path[3], grid.cell_to_world((2, 1)));
+
+        let mut grid = NavFreeGrid::new(vec![NavFreeGridConnection::new((-1, 0), (0, 0))]);
+        grid.set_transform(Some(NavGridTransform {
+            cell_size: 0.5,
+            ..Default::default()
+        }))
+        .unwrap();
+        assert_eq!(grid.world_to_cell((-0.25, 0.25, 0.0).into()), Some((-1, 0)));
+        assert_eq!(grid.world_to_cell((0.75, 0.25, 0.0).into()), None);
+        assert_eq!(
+            grid.find_path_world((-0.4, 0.1, 0.0).into(), (0.4, 0.1, 0.0).into())
+                .unwrap()
+                .points,
+            vec![(-0.25, 0.25, 0.0).into(), (0.25, 0.25, 0.0).into()]
+        );
+    }
+
+    #[test]
+    fn test_hex_grid() {
+        assert_eq!(NavHexGrid::distance((0, 0), (2, -1)), 2);
+        assert_eq!(NavHexGrid::distance((-2, 3), (1, -1)), 4);
+        assert_eq!(NavHexGrid::axial_to_cube((1, -3)), (1, -3, 2));
+        assert_eq!(NavHexGrid::cube_to_axial((1, -3, 2)), (1, -3));
+        assert_eq!(
+            NavHexGrid::line((0, 0), (3, 0)),
+            vec![(0, 0), (1, 0), (2, 0), (3, 0)]
+        );
+        for to in [(3, -1), (-2, 3), (0, -4)] {
+            let line = NavHexGrid::line((0, 0), to);
+            assert_eq!(line.len(), NavHexGrid::distance((0, 0), to) + 1);
+            for pair in line.windows(2) {
+                assert_eq!(NavHexGrid::distance(pair[0], pair[1]), 1);
+            }
+        }
+
+        let mut grid = NavHexGrid::hexagon(3, NavHexOrientation::Flat);
+        assert_eq!(grid.cells().len(), 37);
+        assert_eq!(grid.range((0, 0), 1).len(), 7);
+        assert_eq!(grid.range((3, 0), 1).len(), 4);
+        assert_eq!(grid.ring((0, 0), 3).len(), 18);
+        assert_eq!(grid.ring((0, 0), 4).len(), 0);
+        assert_eq!(grid.neighbors((0, 0)).unwrap().count(), 6);
+        assert_eq!(grid.find_path((-3, 0), (3, 0)).unwrap().points.len(), 7);
+
+        // wall splitting grid in half along r axis.
+        for r in -3..=3 {
+            grid.set_cell_walkable((0, r), false);
+        }
+        assert_eq!(grid.find_islands().len(), 2);
+        assert!(grid.find_path((-3, 0), (3, 0)).is_none());
+        assert!(!grid.line_of_sight((-1, 0), (1, 0)));
+        grid.set_cell_walkable((0, 3), true);
+        assert_eq!(grid.find_islands().len(), 1);
+        let path = grid.find_path((-1, 0), (1, 0)).unwrap().points;
+        assert!(path.contains(&(0, 3)));
+        for pair in path.windows(2) {
+            assert_eq!(NavHexGrid::distance(pair[0], pair[1]), 1);
+        }
+
+        let mut grid = NavHexGrid::hexagon(2, NavHexOrientation::Pointy);
+        grid.set_cell_cost((1, 0), 10.0);
+        let path = grid.find_path((0, 0), (2, 0)).unwrap().points;
+        assert_eq!(path.len(), 4);
+        assert!(!path.contains(&(1, 0)));
+        let path = grid
+            .find_path_custom((0, 0), (2, 0), |_, _, cost| cost < 5.0)
+            .unwrap()
+            .points;
+        assert!(!path.contains(&(1, 0)));
+        assert!(grid
+            .find_path_custom((0, 0), (2, 0), |_, to, _| to != (2, 0))
+            .is_none());
+
+        for orientation in [NavHexOrientation::Pointy, NavHexOrientation::Flat] {
+            let mut grid = NavHexGrid::hexagon(3, orientation);
+            grid.set_transform(Some(NavGridTransform {
+                origin: (5.0, -2.0, 0.0).into(),
+                cell_size: 2.0,
+                rotation: 0.5,
+            }))
+            .unwrap();
+            for cell in grid.cells().to_vec() {
+                assert_eq!(grid.world_to_cell(grid.cell_to_world(cell)), Some(cell));
+            }
+            let a = grid.cell_to_world((0, 0));
+            let b = grid.cell_to_world((1, 0));
+            assert_relative_eq!(
+                (b - a).magnitude(),
+                2.0 * (3.0 as Scalar).sqrt(),
+                epsilon = 1e-4
+            );
+        }
+    }
+
+    #[test]
+    fn test_voxel_grid() {
+        fn path_length(path: &[(usize, usize, usize)]) -> Scalar {
+            path.windows(2)
+                .map(|pair| {
+                    let axes = (pair[0].0 != pair[1].0) as usize
+                        + (pair[0].1 != pair[1].1) as usize
+                        + (pair[0].2 != pair[1].2) as usize;
+                    (axes as Scalar).sqrt()
+                })
+                .sum()
+        }
+
+        assert!(matches!(
+            NavVoxelGrid::new(2, 2, 2, vec![true; 7]),
+            Err(Error::CellsCountDoesNotMatchColsRowsLayers(7, 2, 2, 2))
+        ));
+        assert!(matches!(
+            NavVoxelGrid::new(2, 0, 2, vec![]),
+            Err(Error::EmptyVoxels(2, 0, 2))
+        ));
+
+        for connectivity in [
+            NavVoxelConnectivity::Six,
+            NavVoxelConnectivity::Eighteen,
+            NavVoxelConnectivity::TwentySix,
+        ] {
+            let grid = NavVoxelGrid::filled(5, 4, 3, true, connectivity).unwrap();
+            for to in [(4, 3, 2), (4, 0, 0), (1, 3, 2), (4, 2, 1)] {
+                let path = grid.find_path((0, 0, 0), to).unwrap().points;
+                assert_eq!(path.first(), Some(&(0, 0, 0)));
+                assert_eq!(path.last(), Some(&to));
+                assert_relative_eq!(
+                    path_length(&path),
+                    connectivity.distance((0, 0, 0), to),
+                    epsilon = 1e-4
+                );
+            }
+            assert_eq!(grid.neighbors((2, 2, 1)).unwrap().count(), {
+                match connectivity {
+                    NavVoxelConnectivity::Six => 6,
+                    NavVoxelConnectivity::Eighteen => 18,
+                    NavVoxelConnectivity::TwentySix => 26,
+                }
+            });
+
+            // wall across whole grid with single hole in it.
+            let mut grid = NavVoxelGrid::filled(5, 5, 5, true, connectivity).unwrap();
+            grid.fill_box((2, 0, 0), (1, 5, 5), false);
+            assert!(grid.find_path((0, 0, 0), (4, 4, 4)).is_none());
+            grid.set_cell_walkable((2, 4, 4), true);
+            let path = grid.find_path((0, 0, 0), (4, 0, 0)).unwrap().points;
+            assert!(path.contains(&(2, 4, 4)));
+            for pair in path.windows(2) {
+                assert!(grid.neighbors(pair[0]).unwrap().any(|n| n == pair[1]));
+            }
+        }
+
+        let mut grid = NavVoxelGrid::filled(3, 1, 3, true, NavVoxelConnectivity::Six).unwrap();
+        assert_eq!(grid.cell_cost((1, 0, 0)), Some(1.0));
+        grid.set_cell_cost((1, 0, 0), 10.0);
+        let path = grid.find_path((0, 0, 0), (2, 0, 0)).unwrap().points;
+        assert_eq!(path.len(), 5);
+        assert!(!path.contains(&(1, 0, 0)));
+        let path = grid
+            .find_path_custom((0, 0, 0), (2, 0, 0), |_, _, cost| cost < 5.0)
+            .unwrap()
+            .points;
+        assert!(!path.contains(&(1, 0, 0)));
+        assert!(grid
+            .find_path_custom((0, 0, 0), (2, 0, 0), |_, to, _| to != (2, 0, 0))
+            .is_none());
+
+        // cells count not being multiple of bitset word size.
+        let mut grid = NavVoxelGrid::filled(5, 5, 5, true, NavVoxelConnectivity::Six).unwrap();
+        assert_eq!(grid.is_cell_walkable((4, 4, 4)), Some(true));
+        assert_eq!(grid.set_cell_walkable((4, 4, 4), false), Some(true));
+        assert_eq!(grid.is_cell_walkable((4, 4, 4)), Some(false));
+        assert_eq!(grid.is_cell_walkable((3, 4, 4)), Some(true));
+        assert_eq!(grid.is_cell_walkable((5, 4, 4)), None);
+
+        grid.set_transform(Some(NavGridTransform {
+            origin: (10.0, 0.0, -5.0).into(),
+            cell_size: 0.5,
+            rotation: 0.3,
+        }))
+        .unwrap();
+        for index in 0..125 {
+            let cell = grid.coord(index).unwrap();
+            assert_eq!(grid.world_to_cell(grid.cell_to_world(cell)), Some(cell));
+        }
+        let point = grid.cell_to_world((0, 0, 3));
+        assert_relative_eq!(point.z, -3.25, epsilon = 1e-4);
+        assert_eq!(grid.world_to_cell((10.0, 0.0, -6.0).into()), None);
+        let path = grid
+            .find_path_world(grid.cell_to_world((0, 0, 0)), grid.cell_to_world((0, 0, 4)))
+            .unwrap()
+            .points;
+        assert_eq!(path.len(), 5);
+    }
+
+    #[test]
+    fn test_octree() {
+        // floor and box obstacle standing on it in the middle of volume.
+        let vertices = vec![
+            (4.0, 4.0, 0.0).into(),
+            (6.0, 4.0, 0.0).into(),
+            (6.0, 6.0, 0.0).into(),
+            (4.0, 6.0, 0.0).into(),
+            (4.0, 4.0, 8.0).into(),
+            (6.0, 4.0, 8.0).into(),
+            (6.0, 6.0, 8.0).into(),
+            (4.0, 6.0, 8.0).into(),
+        ];
+        let triangles = vec![
+            (0, 1, 5).into(),
+            (5, 4, 0).into(),
+            (1, 2, 6).into(),
+            (6, 5, 1).into(),
+            (2, 3, 7).into(),
+            (7, 6, 2).into(),
+            (3, 0, 4).into(),
+            (4, 7, 3).into(),
+            (4, 5, 6).into(),
+            (6, 7, 4).into(),
+        ];
+        assert!(matches!(
+            NavOctree::new(
+                vertices.clone(),
+                vec![(0, 1, 8).into()],
+                ((0.0, 0.0, 0.0).into(), (10.0, 10.0, 10.0).into()),
+                4,
+                0.0,
+            ),
+            Err(Error::TriangleVerticeIndexOutOfBounds(0, 2, 8)),
+        ));
+        let octree = NavOctree::new(
+            vertices,
+            triangles,
+            ((0.0, 0.0, 0.0).into(), (10.0, 10.0, 10.0).into()),
+            5,
+            0.25,
+        )
+        .unwrap();
+        assert!(octree.nodes().len() < 8usize.pow(5));
+        assert_eq!(octree.find_node((4.0, 5.0, 4.0).into()), None);
+        assert_eq!(octree.find_node((5.0, 5.0, 11.0).into()), None);
+        assert!(octree.find_node((1.0, 1.0, 1.0).into()).is_some());
+        assert!(!octree.line_of_sight((2.0, 5.0, 2.0).into(), (8.0, 5.0, 2.0).into()));
+        assert!(octree.line_of_sight((2.0, 5.0, 9.5).into(), (8.0, 5.0, 9.5).into()));
+        for node in octree.nodes() {
+            let index = octree.find_node(node.center()).unwrap();
+            assert_eq!(octree.nodes()[index], *node);
+        }
+
+        let from = NavVec3::new(2.0, 5.0, 2.0);
+        let to = NavVec3::new(8.0, 5.0, 2.0);
+        let path = octree.find_path(from, to).unwrap().points;
+        assert_eq!(path.first(), Some(&from));
+        assert_eq!(path.last(), Some(&to));
+        assert!(path.len() >= 3);
+        for pair in path.windows(2) {
+            assert!(octree.line_of_sight(pair[0], pair[1]));
+        }
+        let length = path
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).magnitude())
+            .sum::<Scalar>();
+        assert!(length < 12.0);
+        assert_eq!(octree.find_path(from, (5.0, 5.0, 4.0).into()), None);
+        assert!(octree.find_path_custom(from, to, |_, _, _| false).is_none());
+
+        let islands = Navigable::find_islands(&octree);
+        assert_eq!(
+            islands.iter().map(|island| island.len()).sum::<usize>(),
+            octree.nodes().len()
+        );
+        let (a, b) = (
+            octree.find_node(from).unwrap(),
+            octree.find_node(to).unwrap(),
+        );
+        assert!(islands
+            .iter()
+            .any(|island| island.contains(&a) && island.contains(&b)));
+        assert_eq!(Navigable::node_cost(&octree, a), Some(1.0));
+        let path = octree.find_graph_path(a, b).unwrap();
+        assert_eq!(octree.node_position(a), Some(octree.nodes()[a].center()));
+        assert_eq!(path.points.first(), Some(&a));
+        assert_eq!(path.points.last(), Some(&b));
+        assert_relative_eq!(path.cost, path.length, epsilon = 1e-4);
+        assert_eq!(
+            Navigable::neighbors(&octree, octree.nodes().len()).count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_islands_edit() {
+        let portal = |island, portal| NavIslandPortal::<u8, u8> { island, portal };
+        let connection = |from, to| NavIslandsConnection {
+            from,
+            to,
+            distance: 1.0,
+        };
+        let mut islands = NavIslands::new(
+            vec![
+                connection(portal(0, None), portal(0, Some(0))),
+                connection(portal(0, Some(0)), portal(1, Some(0))),
+                connection(portal(1, Some(0)), portal(1, None)),
+            ],
+            true,
+        );
+        assert_eq!(islands.portals().len(), 4);
+        for (index, item) in islands.portals().iter().enumerate() {
+            assert_eq!(islands.index(item), Some(index));
+        }
+        assert!(islands
+            .find_path(&portal(0, None), &portal(1, None))
+            .is_some());
+
+        // bridge collapses.
+        assert!(islands.remove_connection(&portal(1, Some(0)), &portal(0, Some(0))));
+        assert!(!islands.remove_connection(&portal(0, Some(0)), &portal(1, Some(0))));
+        assert!(islands
+            .find_path(&portal(0, None), &portal(1, None))
+            .is_none());
+
+        // ferry goes only one way.
+        assert!(islands.add_connection(connection(portal(0, Some(1)), portal(1, Some(1))), false));
+        assert!(islands.add_connection(connection(portal(0, None), portal(0, Some(1))), true));
+        assert!(islands.add_connection(connection(portal(1, Some(1)), portal(1, None)), true));
+        assert!(!islands.add_connection(connection(portal(1, Some(1)), portal(1, None)), true));
+        assert!(islands
+            .find_path(&portal(0, None), &portal(1, None))
+            .is_some());
+        assert!(islands
+            .find_path(&portal(1, None), &portal(0, None))
+            .is_none());
+
+        assert!(islands.remove_portal(&portal(0, Some(1))));
+        assert!(!islands.remove_portal(&portal(0, Some(1))));
+        assert_eq!(islands.index(&portal(0, Some(1))), None);
+        assert_eq!(islands.portals().len(), islands.portals_costs().len());
+        for (index, item) in islands.portals().iter().enumerate() {
+            assert_eq!(islands.index(item), Some(index));
+        }
+        assert!(islands
+            .find_path(&portal(0, None), &portal(1, None))
+            .is_none());
+        assert!(islands
+            .find_path(&portal(1, Some(1)), &portal(1, None))
+            .is_some());
+    }
+
+    #[test]
+    fn test_islands_connections() {
+        let portal = |island, portal| NavIslandPortal::<u8, u8> { island, portal };
+        let connection = |from, to| NavIslandsConnection {
+            from,
+            to,
+            distance: 1.0,
+        };
+        // two doors between rooms.
+        let mut islands = NavIslands::new(
+            vec![
+                connection(portal(0, None), portal(0, Some(0))),
+                connection(portal(0, Some(0)), portal(1, Some(0))),
+                connection(portal(1, Some(0)), portal(1, None)),
+                connection(portal(0, None), portal(0, Some(1))),
+                connection(portal(0, Some(1)), portal(1, Some(1))),
+                connection(portal(1, Some(1)), portal(1, None)),
+            ],
+            true,
+        );
+        let door = |index| (portal(0, Some(index)), portal(1, Some(index)));
+        let route = |islands: &NavIslands<u8, u8>, from, to| {
+            islands
+                .find_path(&portal(from, None), &portal(to, None))
+                .map(|path| path.points.into_iter().cloned().collect::<Vec<_>>())
+        };
+        let path_cost = |islands: &NavIslands<u8, u8>, from, to| {
+            islands
+                .find_path(&portal(from, None), &portal(to, None))
+                .map(|path| path.cost)
+        };
+        let (a, b) = door(0);
+        assert_eq!(islands.is_connection_both_ways(&b, &a), Some(true));
+        assert_eq!(islands.set_connection_cost(&a, &b, 10.0), Some(1.0));
+        assert_eq!(islands.connection_cost(&b, &a), Some(10.0));
+        let path = route(&islands, 0, 1).unwrap();
+        assert!(path.contains(&portal(0, Some(1))));
+        assert_eq!(
+            islands
+                .find_path(&portal(0, None), &portal(1, None))
+                .unwrap()
+                .length,
+            3.0
+        );
+
+        // second door becomes two one way connections and gets locked from the outside only.
+        let (c, d) = door(1);
+        assert!(!islands.add_connection(connection(c.clone(), d.clone()), false));
+        assert_eq!(islands.is_connection_both_ways(&c, &d), Some(false));
+        assert_eq!(islands.is_connection_both_ways(&d, &c), Some(false));
+        assert_eq!(islands.set_connection_enabled(&c, &d, false), Some(true));
+        assert_eq!(islands.is_connection_enabled(&c, &d), Some(false));
+        assert_eq!(islands.is_connection_enabled(&d, &c), Some(true));
+        let path = route(&islands, 0, 1).unwrap();
+        assert!(path.contains(&portal(0, Some(0))));
+        let path = route(&islands, 1, 0).unwrap();
+        assert!(path.contains(&portal(1, Some(1))));
+
+        // both doors locked.
+        assert_eq!(islands.set_connection_enabled(&b, &a, false), Some(true));
+        assert_eq!(islands.is_connection_enabled(&a, &b), Some(false));
+        assert!(route(&islands, 0, 1).is_none());
+        assert!(route(&islands, 1, 0).is_some());
+        assert_eq!(islands.set_connection_enabled(&d, &c, false), Some(true));
+        assert!(route(&islands, 1, 0).is_none());
+        assert_eq!(islands.find_islands().len(), 2);
+
+        // re-adding connection keeps it locked, but updates its distance.
+        assert!(!islands.add_connection(connection(a.clone(), b.clone()), true));
+        assert_eq!(islands.is_connection_enabled(&a, &b), Some(false));
+        assert_eq!(islands.set_connection_distance(&a, &b, 2.0), Some(1.0));
+        assert_eq!(islands.connection_distance(&b, &a), Some(2.0));
+        assert_eq!(islands.set_connection_cost(&a, &b, 1.0), Some(10.0));
+        assert_eq!(islands.set_connection_enabled(&a, &b, true), Some(false));
+        let path = islands
+            .find_path(&portal(0, None), &portal(1, None))
+            .unwrap();
+        assert_eq!(path.length, 4.0);
+        let path = route(&islands, 1, 0).unwrap();
+        assert!(path.contains(&portal(1, Some(0))));
+        assert_eq!(
+            islands.set_connection_enabled(&portal(0, None), &portal(1, None), true),
+            None
+        );
+
+        // first door split into directions gets locked from the outside only.
+        assert_eq!(islands.set_connection_both_ways(&b, &a, false), Some(true));
+        assert_eq!(islands.is_connection_both_ways(&a, &b), Some(false));
+        assert_eq!(islands.set_connection_enabled(&a, &b, false), Some(true));
+        assert_eq!(islands.is_connection_enabled(&b, &a), Some(true));
+        assert!(route(&islands, 0, 1).is_none());
+        assert!(route(&islands, 1, 0).is_some());
+        assert_eq!(islands.set_connection_both_ways(&a, &b, true), Some(false));
+        assert_eq!(islands.set_connection_enabled(&a, &b, true), Some(false));
+        assert_eq!(islands.is_connection_enabled(&b, &a), Some(true));
+        assert_eq!(
+            islands.set_connection_both_ways(&portal(0, None), &portal(1, None), false),
+            None
+        );
+
+        // one way connection gets removed alone.
+        assert!(islands.remove_connection(&c, &d));
+        assert_eq!(islands.connection_distance(&d, &c), Some(1.0));
+        assert!(islands.remove_connection(&b, &a));
+        assert_eq!(islands.connection_distance(&a, &b), None);
+        assert!(route(&islands, 0, 1).is_none());
+
+        // graph stored before connections got cost, enabled state and direction.
+        let legacy = r#"{
+            "id": "ec91169a-cf35-42ca-9eea-6a8673c16f47",
+            "costs": [1.0, 1.0, 1.0],
+            "portals": [
+                {"island": 0, "portal": null},
+                {"island": 0, "portal": 0},
+                {"island": 1, "portal": null}
+            ],
+            "graph": {
+                "nodes": [null, null, null],
+                "node_holes": [],
+                "edge_property": "directed",
+                "edges": [[0, 1, 1.0], [1, 0, 1.0], [1, 2, 2.0], [2, 1, 2.0], [0, 2, 5.0]]
+            },
+            "nodes": [0, 1, 2],
+            "nodes_map": {"0": 0, "1": 1, "2": 2}
+        }"#;
+        let mut islands = serde_json::from_str::<NavIslands<u8, u8>>(legacy).unwrap();
+        assert_eq!(islands.index(&portal(1, None)), Some(2));
+        assert_eq!(islands.cost_model(), NavCostModel::default());
+        let path = route(&islands, 1, 0).unwrap();
+        assert_eq!(
+            path,
+            vec![portal(1, None), portal(0, Some(0)), portal(0, None)]
+        );
+        assert_eq!(path_cost(&islands, 0, 1), Some(3.0));
+        assert_eq!(
+            islands.is_connection_both_ways(&portal(0, None), &portal(0, Some(0))),
+            Some(true)
+        );
+        assert_eq!(
+            islands.is_connection_both_ways(&portal(0, None), &portal(1, None)),
+            Some(false)
+        );
+        assert_eq!(
+            islands.set_connection_enabled(&portal(0, Some(0)), &portal(1, None), false),
+            Some(true)
+        );
+        assert!(route(&islands, 1, 0).is_none());
+        assert_eq!(path_cost(&islands, 0, 1), Some(5.0));
+        let loaded =
+            serde_json::from_str::<NavIslands<u8, u8>>(&serde_json::to_string(&islands).unwrap())
+                .unwrap();
+        assert_eq!(
+            loaded.is_connection_enabled(&portal(1, None), &portal(0, Some(0))),
+            Some(false)
+        );
+        assert_eq!(
+            loaded.is_connection_both_ways(&portal(1, None), &portal(0, Some(0))),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn test_islands() {
+        let grid_a = NavGrid::new(2, 2, vec![true, true, true, false]).unwrap();
+        let grid_b = NavGrid::new(2, 2, vec![true, true, false, true]).unwrap();
+        let island_a = NavIslandPortal {
+            island: grid_a.id(),
+            portal: None,
+        };
+        let island_a_portal = NavIslandPortal {
+            island: grid_a.id(),
+            portal: Some((1, 0)),
+        };
+        let island_b_portal = NavIslandPortal {
+            island: grid_b.id(),
+            portal: Some((0, 0)),
+        };
+        let island_b = NavIslandPortal {
+            island: grid_b.id(),
+            portal: None,
+        };
+        let islands = NavIslands::new(
+            vec![
+                NavIslandsConnection {
+                    from: island_a.clone(),
+                    to: island_a_portal.clone(),
+                    distance: 1.0,
+                },
+                NavIslandsConnection {
+                    from: island_a_portal.clone(),
+                    to: island_b_portal.clone(),
+                    distance: 0.0,
+                },
+                NavIslandsConnection {
+                    from: island_b_portal.clone(),
+                    to: island_b.clone(),
+                    distance: 1.0,
+                },
+            ],
+            true,
+        );
+        let path = islands.find_path(&island_a, &island_b).unwrap();
+        assert_eq!(
+            path.points,
             vec![&island_a, &island_a_portal, &island_b_portal, &island_b]
         );
-        assert!((distance - 2.0).abs() < 1.0e-6);
+        assert!((path.cost - 2.0).abs() < 1.0e-6);
     }
 }
@@ -0,0 +1,714 @@
+use crate::{
+    nav_graph::search_graph,
+    nav_path::{cells_distance, path_length},
+    nav_search::flood_islands,
+    Error, NavCost, NavCostModel, NavGraph, NavGridConnectivity, NavGridTransform, NavPath,
+    NavResult, NavSearchContext, NavVec3, Navigable, Scalar, SCALAR_SQRT_2,
+};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use typid::ID;
+
+// orthogonal offsets go first so four-connectivity takes only them.
+const OFFSETS: [(isize, isize); 8] = [
+    (1, 0),
+    (0, 1),
+    (-1, 0),
+    (0, -1),
+    (1, 1),
+    (-1, 1),
+    (-1, -1),
+    (1, -1),
+];
+
+/// Square piece of `NavChunkedGrid`, loaded and unloaded (and serialized) as a whole.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NavGridChunk {
+    coord: (isize, isize),
+    size: usize,
+    cells: Vec<bool>,
+    costs: Vec<Scalar>,
+    // lowest cell cost, used to scale heuristic.
+    min_cost: Scalar,
+}
+
+impl NavGridChunk {
+    /// Create chunk of given chunk coordinate with `size * size` cells in row-major order.
+    pub fn new(coord: (isize, isize), size: usize, cells: Vec<bool>) -> NavResult<Self> {
+        if size == 0 {
+            return Err(Error::EmptyCells(size, size));
+        }
+        if size * size != cells.len() {
+            return Err(Error::CellsCountDoesNotMatchColsRows(
+                cells.len(),
+                size,
+                size,
+            ));
+        }
+        Ok(Self {
+            coord,
+            size,
+            costs: vec![1.0; cells.len()],
+            cells,
+            min_cost: 1.0,
+        })
+    }
+
+    pub fn filled(coord: (isize, isize), size: usize, walkable: bool) -> NavResult<Self> {
+        Self::new(coord, size, vec![walkable; size * size])
+    }
+
+    #[inline]
+    pub fn coord(&self) -> (isize, isize) {
+        self.coord
+    }
+
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    #[inline]
+    pub fn cells(&self) -> &[bool] {
+        &self.cells
+    }
+
+    #[inline]
+    pub fn cells_costs(&self) -> &[Scalar] {
+        &self.costs
+    }
+
+    /// Tells if cell of given local coordinate is walkable.
+    pub fn is_cell_walkable(&self, col: usize, row: usize) -> bool {
+        self.index(col, row)
+            .map(|index| self.cells[index])
+            .unwrap_or(false)
+    }
+
+    /// Change walkability of cell of given local coordinate. Returns previous walkability or
+    /// `None` if cell is out of chunk.
+    pub fn set_cell_walkable(&mut self, col: usize, row: usize, walkable: bool) -> Option<bool> {
+        let index = self.index(col, row)?;
+        Some(std::mem::replace(&mut self.cells[index], walkable))
+    }
+
+    /// Change cost of cell of given local coordinate. Returns previous cost or `None` if cell is
+    /// out of chunk.
+    pub fn set_cell_cost(&mut self, col: usize, row: usize, cost: Scalar) -> Option<Scalar> {
+        let index = self.index(col, row)?;
+        let cost = cost.max(0.0);
+        let old = std::mem::replace(&mut self.costs[index], cost);
+        if cost < self.min_cost {
+            self.min_cost = cost;
+        } else if old <= self.min_cost {
+            self.min_cost = self.costs.iter().copied().fold(Scalar::MAX, Scalar::min);
+        }
+        Some(old)
+    }
+
+    #[inline]
+    fn index(&self, col: usize, row: usize) -> Option<usize> {
+        if col < self.size && row < self.size {
+            Some(row * self.size + col)
+        } else {
+            None
+        }
+    }
+}
+
+/// Nav chunked grid identifier.
+pub type NavChunkedGridID = ID<NavChunkedGrid>;
+
+/// Unbounded grid made of square chunks loaded on demand.
+///
+/// Cells use signed world coordinates and belong to chunk `(col / size, row / size)` (rounded
+/// down). Cells of chunks that are not loaded are not walkable, so paths are found only through
+/// currently streamed part of the world, crossing chunk borders freely.
+///
+/// # Example
+/// ```
+/// use navmesh::*;
+///
+/// let mut grid = NavChunkedGrid::new(4).unwrap();
+/// grid.load_chunk(NavGridChunk::filled((0, 0), 4, true).unwrap())
+///     .unwrap();
+/// grid.load_chunk(NavGridChunk::filled((-1, 0), 4, true).unwrap())
+///     .unwrap();
+/// let path = grid.find_path((-4, 0), (3, 0)).unwrap().points;
+/// assert_eq!(path.len(), 8);
+///
+/// // chunks can be stored and loaded again later.
+/// let chunk = grid.unload_chunk((-1, 0)).unwrap();
+/// assert_eq!(grid.find_path((-4, 0), (3, 0)), None);
+/// grid.load_chunk(chunk).unwrap();
+/// assert_eq!(grid.find_path((-4, 0), (3, 0)).unwrap().points.len(), 8);
+/// ```
+// serde impls are written by hand below to rebuild chunks map after loading.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(remote = "Self")]
+pub struct NavChunkedGrid {
+    id: NavChunkedGridID,
+    chunk_size: usize,
+    connectivity: NavGridConnectivity,
+    #[serde(default)]
+    cost_model: NavCostModel,
+    #[serde(default)]
+    transform: Option<NavGridTransform>,
+    // loaded chunks slots, cell index is slot index * chunk area + local cell index.
+    chunks: Vec<Option<NavGridChunk>>,
+    // {chunk coord: slot index}
+    #[serde(skip)]
+    chunks_map: HashMap<(isize, isize), usize>,
+    free_slots: Vec<usize>,
+}
+
+impl NavChunkedGrid {
+    pub fn new(chunk_size: usize) -> NavResult<Self> {
+        Self::with_connectivity(chunk_size, NavGridConnectivity::Four)
+    }
+
+    pub fn with_connectivity(
+        chunk_size: usize,
+        connectivity: NavGridConnectivity,
+    ) -> NavResult<Self> {
+        if chunk_size == 0 {
+            return Err(Error::EmptyCells(chunk_size, chunk_size));
+        }
+        Ok(Self {
+            id: NavChunkedGridID::new(),
+            chunk_size,
+            connectivity,
+            cost_model: NavCostModel::default(),
+            transform: None,
+            chunks: vec![],
+            chunks_map: HashMap::new(),
+            free_slots: vec![],
+        })
+    }
+
+    #[inline]
+    pub fn id(&self) -> NavChunkedGridID {
+        self.id
+    }
+
+    #[inline]
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    #[inline]
+    pub fn connectivity(&self) -> NavGridConnectivity {
+        self.connectivity
+    }
+
+    #[inline]
+    pub fn cost_model(&self) -> NavCostModel {
+        self.cost_model
+    }
+
+    #[inline]
+    pub fn set_cost_model(&mut self, model: NavCostModel) {
+        self.cost_model = model;
+    }
+
+    #[inline]
+    pub fn transform(&self) -> Option<NavGridTransform> {
+        self.transform
+    }
+
+    /// Set world placement of grid.
+    ///
+    /// # Returns
+    /// `Ok` or `Err` with `Error::InvalidCellSize` if transform cell size is not positive.
+    pub fn set_transform(&mut self, transform: Option<NavGridTransform>) -> NavResult<()> {
+        if let Some(transform) = &transform {
+            transform.validate()?;
+        }
+        self.transform = transform;
+        Ok(())
+    }
+
+    /// Load chunk, replacing and returning chunk already loaded at its coordinate.
+    ///
+    /// # Returns
+    /// `Err` with `Error::ChunkSizeDoesNotMatch` if chunk size differs from grid chunk size.
+    pub fn load_chunk(&mut self, chunk: NavGridChunk) -> NavResult<Option<NavGridChunk>> {
+        if chunk.size != self.chunk_size {
+            return Err(Error::ChunkSizeDoesNotMatch(chunk.size, self.chunk_size));
+        }
+        if let Some(slot) = self.chunks_map.get(&chunk.coord) {
+            return Ok(self.chunks[*slot].replace(chunk));
+        }
+        self.insert_chunk(chunk);
+        Ok(None)
+    }
+
+    /// Unload chunk of given chunk coordinate and give it back, so it can be stored.
+    pub fn unload_chunk(&mut self, coord: (isize, isize)) -> Option<NavGridChunk> {
+        let slot = self.chunks_map.remove(&coord)?;
+        self.free_slots.push(slot);
+        self.chunks[slot].take()
+    }
+
+    #[inline]
+    pub fn is_chunk_loaded(&self, coord: (isize, isize)) -> bool {
+        self.chunks_map.contains_key(&coord)
+    }
+
+    pub fn chunk(&self, coord: (isize, isize)) -> Option<&NavGridChunk> {
+        let slot = self.chunks_map.get(&coord)?;
+        self.chunks[*slot].as_ref()
+    }
+
+    pub fn chunk_mut(&mut self, coord: (isize, isize)) -> Option<&mut NavGridChunk> {
+        let slot = self.chunks_map.get(&coord)?;
+        self.chunks[*slot].as_mut()
+    }
+
+    pub fn chunks(&self) -> impl Iterator<Item = &NavGridChunk> {
+        self.chunks.iter().filter_map(|chunk| chunk.as_ref())
+    }
+
+    /// Coordinate of chunk containing given cell.
+    #[inline]
+    pub fn chunk_coord(&self, cell: (isize, isize)) -> (isize, isize) {
+        let size = self.chunk_size as isize;
+        (cell.0.div_euclid(size), cell.1.div_euclid(size))
+    }
+
+    /// Tells if cell is walkable. Cells of chunks that are not loaded are not walkable.
+    pub fn is_cell_walkable(&self, cell: (isize, isize)) -> bool {
+        self.walkable_index(cell).is_some()
+    }
+
+    /// Change cell walkability and return previous one. Cell chunk that is not loaded gets
+    /// created with all cells not walkable.
+    pub fn set_cell_walkable(&mut self, cell: (isize, isize), walkable: bool) -> bool {
+        let (col, row) = self.local_coord(cell);
+        self.chunk_or_create(self.chunk_coord(cell))
+            .set_cell_walkable(col, row, walkable)
+            .unwrap()
+    }
+
+    /// Change cell cost and return previous one. Cell chunk that is not loaded gets created
+    /// with all cells not walkable.
+    pub fn set_cell_cost(&mut self, cell: (isize, isize), cost: Scalar) -> Scalar {
+        let (col, row) = self.local_coord(cell);
+        self.chunk_or_create(self.chunk_coord(cell))
+            .set_cell_cost(col, row, cost)
+            .unwrap()
+    }
+
+    /// World position of given cell center. Grid without transform uses default one.
+    pub fn cell_to_world(&self, cell: (isize, isize)) -> NavVec3 {
+        self.transform.unwrap_or_default().cell_to_world(cell)
+    }
+
+    /// Cell containing given world position or `None` if its chunk is not loaded.
+    pub fn world_to_cell(&self, point: NavVec3) -> Option<(isize, isize)> {
+        let cell = self.transform.unwrap_or_default().world_to_cell(point);
+        if self.is_chunk_loaded(self.chunk_coord(cell)) {
+            Some(cell)
+        } else {
+            None
+        }
+    }
+
+    pub fn neighbors(
+        &self,
+        cell: (isize, isize),
+    ) -> Option<impl Iterator<Item = (isize, isize)> + '_> {
+        let index = self.walkable_index(cell)?;
+        Some(
+            self.successors(index)
+                .filter_map(|(index, _)| self.coord(index)),
+        )
+    }
+
+    pub fn find_path(
+        &self,
+        from: (isize, isize),
+        to: (isize, isize),
+    ) -> Option<NavPath<(isize, isize)>> {
+        self.find_path_custom(from, to, |_, _, _| true)
+    }
+
+    pub fn find_path_world(&self, from: NavVec3, to: NavVec3) -> Option<NavPath<NavVec3>> {
+        let from = self.world_to_cell(from)?;
+        let to = self.world_to_cell(to)?;
+        let path = self.find_path(from, to)?;
+        let points = path
+            .points
+            .into_iter()
+            .map(|cell| self.cell_to_world(cell))
+            .collect();
+        Some(NavPath::from_points(points, path.cost))
+    }
+
+    // filter params: first col-row, second col-row, connection cost.
+    // connections rejected by filter cannot be traversed.
+    pub fn find_path_custom<F>(
+        &self,
+        from: (isize, isize),
+        to: (isize, isize),
+        mut filter: F,
+    ) -> Option<NavPath<(isize, isize)>>
+    where
+        F: FnMut((isize, isize), (isize, isize), Scalar) -> bool,
+    {
+        let mut context = NavSearchContext::default();
+        let model = self.cost_model;
+        let scale = model.heuristic_scale(self.min_cost());
+        let cost = self.find_path_inner(
+            &mut context,
+            from,
+            to,
+            |a, b, w| {
+                let cost = self.connection_cost(model, a, b, w);
+                if filter(self.coord(a).unwrap(), self.coord(b).unwrap(), cost) {
+                    Some(cost)
+                } else {
+                    None
+                }
+            },
+            |n, goal| self.heuristic(scale, n, goal),
+        )?;
+        Some(self.make_path(&context.path, cost))
+    }
+
+    // cost params: first col-row, second col-row.
+    pub fn find_path_with_cost<C>(
+        &self,
+        from: (isize, isize),
+        to: (isize, isize),
+        cost: &C,
+    ) -> Option<NavPath<(isize, isize)>>
+    where
+        C: NavCost<(isize, isize)>,
+    {
+        let mut context = NavSearchContext::default();
+        let cost = self.find_path_cost_inner(&mut context, from, to, cost)?;
+        Some(self.make_path(&context.path, cost))
+    }
+
+    // cost params: first col-row, second col-row.
+    pub fn find_path_with_context<'a, C>(
+        &'a self,
+        context: &'a mut NavSearchContext,
+        from: (isize, isize),
+        to: (isize, isize),
+        cost: &C,
+    ) -> Option<impl Iterator<Item = (isize, isize)> + 'a>
+    where
+        C: NavCost<(isize, isize)>,
+    {
+        self.find_path_cost_inner(context, from, to, cost)?;
+        Some(context.path.iter().filter_map(move |n| self.coord(*n)))
+    }
+
+    // leaves found path in context and returns its cost.
+    fn find_path_cost_inner<C>(
+        &self,
+        context: &mut NavSearchContext,
+        from: (isize, isize),
+        to: (isize, isize),
+        cost: &C,
+    ) -> Option<Scalar>
+    where
+        C: NavCost<(isize, isize)>,
+    {
+        let model = cost.cost_model().unwrap_or(self.cost_model);
+        let scale = model.heuristic_scale(self.min_cost());
+        self.find_path_inner(
+            context,
+            from,
+            to,
+            |a, b, w| {
+                let (ca, cb) = (self.coord(a).unwrap(), self.coord(b).unwrap());
+                cost.edge_cost(ca, cb, self.connection_cost(model, a, b, w))
+            },
+            |n, goal| {
+                cost.heuristic(self.coord(n).unwrap(), self.coord(goal).unwrap())
+                    .unwrap_or_else(|| self.heuristic(scale, n, goal))
+            },
+        )
+    }
+
+    /// Groups of walkable cells of loaded chunks connected with each other.
+    pub fn find_islands(&self) -> Vec<Vec<(isize, isize)>> {
+        flood_islands(
+            self.chunks.len() * self.chunk_area(),
+            |n| {
+                self.coord(n)
+                    .is_some_and(|cell| self.is_cell_walkable(cell))
+            },
+            |n| self.successors(n).map(|(n, _)| n),
+        )
+        .into_iter()
+        .map(|island| island.into_iter().filter_map(|n| self.coord(n)).collect())
+        .collect()
+    }
+
+    fn make_path(&self, path: &[usize], cost: Scalar) -> NavPath<(isize, isize)> {
+        let points = path
+            .iter()
+            .filter_map(|n| self.coord(*n))
+            .collect::<Vec<_>>();
+        let length = path_length(&points, |a, b| {
+            cells_distance(a.0.abs_diff(b.0), a.1.abs_diff(b.1))
+        });
+        NavPath::new(points, cost, length)
+    }
+
+    fn find_path_inner<E, H>(
+        &self,
+        context: &mut NavSearchContext,
+        from: (isize, isize),
+        to: (isize, isize),
+        edge_cost: E,
+        heuristic: H,
+    ) -> Option<Scalar>
+    where
+        E: FnMut(usize, usize, Scalar) -> Option<Scalar>,
+        H: FnMut(usize, usize) -> Scalar,
+    {
+        let start_index = self.walkable_index(from)?;
+        let end_index = self.walkable_index(to)?;
+        let (_, cost) = search_graph(
+            self,
+            context,
+            start_index,
+            &[end_index],
+            edge_cost,
+            heuristic,
+        )?;
+        Some(cost)
+    }
+
+    fn successors(&self, index: usize) -> impl Iterator<Item = (usize, Scalar)> + '_ {
+        let (col, row) = self.coord(index).unwrap_or_default();
+        let offsets = match self.connectivity {
+            NavGridConnectivity::Four => &OFFSETS[..4],
+            NavGridConnectivity::Eight | NavGridConnectivity::EightNoCornerCutting => &OFFSETS,
+        };
+        offsets.iter().filter_map(move |(dc, dr)| {
+            let other = self.walkable_index((col + dc, row + dr))?;
+            if *dc != 0 && *dr != 0 {
+                if self.connectivity == NavGridConnectivity::EightNoCornerCutting
+                    && (!self.is_cell_walkable((col + dc, row))
+                        || !self.is_cell_walkable((col, row + dr)))
+                {
+                    return None;
+                }
+                Some((other, SCALAR_SQRT_2))
+            } else {
+                Some((other, 1.0))
+            }
+        })
+    }
+
+    #[inline]
+    fn connection_cost(&self, model: NavCostModel, a: usize, b: usize, weight: Scalar) -> Scalar {
+        model.combine(weight, self.cell_cost(a), self.cell_cost(b))
+    }
+
+    fn heuristic(&self, scale: Scalar, a: usize, b: usize) -> Scalar {
+        let (ca, cb) = (self.coord(a).unwrap(), self.coord(b).unwrap());
+        let delta = (ca.0.abs_diff(cb.0), ca.1.abs_diff(cb.1));
+        self.connectivity.distance((0, 0), delta) * scale
+    }
+
+    // lowest cell cost of loaded chunks.
+    fn min_cost(&self) -> Scalar {
+        self.chunks()
+            .map(|chunk| chunk.min_cost)
+            .fold(Scalar::MAX, Scalar::min)
+    }
+
+    // puts chunk into free slot, chunk coord must not be loaded yet.
+    fn insert_chunk(&mut self, chunk: NavGridChunk) -> usize {
+        let coord = chunk.coord;
+        let slot = match self.free_slots.pop() {
+            Some(slot) => {
+                self.chunks[slot] = Some(chunk);
+                slot
+            }
+            None => {
+                self.chunks.push(Some(chunk));
+                self.chunks.len() - 1
+            }
+        };
+        self.chunks_map.insert(coord, slot);
+        slot
+    }
+
+    fn chunk_or_create(&mut self, coord: (isize, isize)) -> &mut NavGridChunk {
+        let slot = match self.chunks_map.get(&coord) {
+            Some(slot) => *slot,
+            None => self.insert_chunk(NavGridChunk::filled(coord, self.chunk_size, false).unwrap()),
+        };
+        self.chunks[slot].as_mut().unwrap()
+    }
+
+    #[inline]
+    fn chunk_area(&self) -> usize {
+        self.chunk_size * self.chunk_size
+    }
+
+    #[inline]
+    fn local_coord(&self, cell: (isize, isize)) -> (usize, usize) {
+        let size = self.chunk_size as isize;
+        (
+            cell.0.rem_euclid(size) as usize,
+            cell.1.rem_euclid(size) as usize,
+        )
+    }
+
+    fn walkable_index(&self, cell: (isize, isize)) -> Option<usize> {
+        self.index(cell).filter(|index| {
+            let area = self.chunk_area();
+            self.chunks[index / area]
+                .as_ref()
+                .is_some_and(|chunk| chunk.cells[index % area])
+        })
+    }
+
+    fn cell_cost(&self, index: usize) -> Scalar {
+        let area = self.chunk_area();
+        self.chunks[index / area]
+            .as_ref()
+            .map(|chunk| chunk.costs[index % area])
+            .unwrap_or(1.0)
+    }
+
+    /// Cell index used as `NavGraph` node, or `None` if cell chunk is not loaded. Indices stay
+    /// the same until chunk gets unloaded.
+    pub fn index(&self, cell: (isize, isize)) -> Option<usize> {
+        let slot = *self.chunks_map.get(&self.chunk_coord(cell))?;
+        let chunk = self.chunks[slot].as_ref()?;
+        let (col, row) = self.local_coord(cell);
+        Some(slot * self.chunk_area() + chunk.index(col, row)?)
+    }
+
+    /// Cell of given `NavGraph` node index, or `None` if there is no loaded chunk for it.
+    pub fn coord(&self, index: usize) -> Option<(isize, isize)> {
+        let area = self.chunk_area();
+        let chunk = self.chunks.get(index / area)?.as_ref()?;
+        let local = index % area;
+        let size = self.chunk_size as isize;
+        Some((
+            chunk.coord.0 * size + (local % self.chunk_size) as isize,
+            chunk.coord.1 * size + (local / self.chunk_size) as isize,
+        ))
+    }
+}
+
+impl Serialize for NavChunkedGrid {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Self::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for NavChunkedGrid {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut result = Self::deserialize(deserializer)?;
+        for (slot, chunk) in result.chunks.iter().enumerate() {
+            if let Some(chunk) = chunk {
+                if chunk.size != result.chunk_size {
+                    return Err(D::Error::custom(
+                        "NavChunkedGrid chunk size does not match grid chunk size",
+                    ));
+                }
+                result.chunks_map.insert(chunk.coord, slot);
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl Navigable for NavChunkedGrid {
+    type Node = (isize, isize);
+    type Point = (isize, isize);
+
+    fn find_path(
+        &self,
+        from: (isize, isize),
+        to: (isize, isize),
+    ) -> Option<NavPath<(isize, isize)>> {
+        self.find_path(from, to)
+    }
+
+    fn find_path_custom<F>(
+        &self,
+        from: (isize, isize),
+        to: (isize, isize),
+        filter: F,
+    ) -> Option<NavPath<(isize, isize)>>
+    where
+        F: FnMut((isize, isize), (isize, isize), Scalar) -> bool,
+    {
+        self.find_path_custom(from, to, filter)
+    }
+
+    fn find_islands(&self) -> Vec<Vec<(isize, isize)>> {
+        self.find_islands()
+    }
+
+    fn neighbors(&self, cell: (isize, isize)) -> impl Iterator<Item = (isize, isize)> + '_ {
+        self.neighbors(cell).into_iter().flatten()
+    }
+
+    // cells of chunks that are not loaded have no cost.
+    fn node_cost(&self, cell: (isize, isize)) -> Option<Scalar> {
+        let (col, row) = self.local_coord(cell);
+        let chunk = self.chunk(self.chunk_coord(cell))?;
+        Some(chunk.costs[chunk.index(col, row)?])
+    }
+
+    fn set_node_cost(&mut self, cell: (isize, isize), cost: Scalar) -> Option<Scalar> {
+        Some(self.set_cell_cost(cell, cost))
+    }
+
+    fn cost_model(&self) -> NavCostModel {
+        self.cost_model()
+    }
+
+    fn set_cost_model(&mut self, model: NavCostModel) {
+        self.set_cost_model(model);
+    }
+}
+
+// cell index is chunk slot index * chunk area + local cell index.
+impl NavGraph for NavChunkedGrid {
+    fn node_count(&self) -> usize {
+        self.chunks.len() * self.chunk_area()
+    }
+
+    // cells that are not walkable cannot be left.
+    fn neighbors(&self, node: usize) -> impl Iterator<Item = (usize, Scalar)> + '_ {
+        self.coord(node)
+            .filter(|cell| self.is_cell_walkable(*cell))
+            .map(|_| self.successors(node))
+            .into_iter()
+            .flatten()
+    }
+
+    fn edge_cost(&self, from: usize, to: usize, weight: Scalar) -> Option<Scalar> {
+        Some(self.connection_cost(self.cost_model, from, to, weight))
+    }
+
+    // scans loaded chunks for the lowest cost.
+    fn heuristic(&self, node: usize, goal: usize) -> Scalar {
+        self.heuristic(self.cost_model.heuristic_scale(self.min_cost()), node, goal)
+    }
+
+    fn node_position(&self, node: usize) -> Option<NavVec3> {
+        self.coord(node).map(|cell| self.cell_to_world(cell))
+    }
+}
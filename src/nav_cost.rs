@@ -0,0 +1,203 @@
+use crate::Scalar;
+use serde::{Deserialize, Serialize};
+
+/// Path finding cost provider.
+///
+/// Navigation structures compute base cost of every traversed edge (by default connection
+/// weight multiplied by costs of both connected nodes) and ask cost provider for the final
+/// value, so it can be scaled, replaced or rejected per query. `T` is the node type of given
+/// navigation structure: triangle index for `NavMesh`, vertex index for `NavNet`, cell
+/// coordinate for `NavGrid` and `NavFreeGrid` and island portal for `NavIslands`.
+///
+/// # Example
+/// ```
+/// use navmesh::*;
+///
+/// // bottom row is a river flowing left - moving against it is expensive.
+/// struct River;
+///
+/// impl NavCost<(usize, usize)> for River {
+///     fn edge_cost(&self, from: (usize, usize), to: (usize, usize), base: Scalar) -> Option<Scalar> {
+///         if from.1 == 0 && to.1 == 0 && to.0 > from.0 {
+///             Some(base * 10.0)
+///         } else {
+///             Some(base)
+///         }
+///     }
+/// }
+///
+/// let grid = NavGrid::new(3, 2, vec![true; 6]).unwrap();
+/// let path = grid.find_path_with_cost((0, 0), (2, 0), &River).unwrap().points;
+/// assert_eq!(path, vec![(0, 0), (0, 1), (1, 1), (2, 1), (2, 0)]);
+/// let path = grid.find_path_with_cost((2, 0), (0, 0), &River).unwrap().points;
+/// assert_eq!(path, vec![(2, 0), (1, 0), (0, 0)]);
+/// ```
+pub trait NavCost<T> {
+    /// Cost of moving between two connected nodes.
+    ///
+    /// # Arguments
+    /// * `from` - node that path leaves.
+    /// * `to` - node that path enters.
+    /// * `base` - cost calculated by navigation structure.
+    ///
+    /// # Returns
+    /// `Some` with traversal cost or `None` if connection cannot be traversed.
+    fn edge_cost(&self, _from: T, _to: T, base: Scalar) -> Option<Scalar> {
+        Some(base)
+    }
+
+    /// Estimated cost of reaching goal from given node.
+    ///
+    /// To keep found paths shortest, estimation must never be greater than the real cost, so
+    /// providers that make edges cheaper than base cost should return `Some(0.0)`.
+    ///
+    /// # Arguments
+    /// * `node` - node to estimate from.
+    /// * `goal` - path target node.
+    ///
+    /// # Returns
+    /// `Some` with estimation or `None` to use estimation of navigation structure (distance
+    /// based for `NavGrid`, zero for others).
+    fn heuristic(&self, _node: T, _goal: T) -> Option<Scalar> {
+        None
+    }
+
+    /// Cost model used to calculate base costs for this query.
+    ///
+    /// # Returns
+    /// `Some` with cost model overriding one set on navigation structure or `None` to keep it.
+    fn cost_model(&self) -> Option<NavCostModel> {
+        None
+    }
+}
+
+/// Cost provider that keeps costs calculated by navigation structure.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct NavDefaultCost;
+
+impl<T> NavCost<T> for NavDefaultCost {}
+
+impl<T, F> NavCost<T> for F
+where
+    F: Fn(T, T, Scalar) -> Option<Scalar>,
+{
+    fn edge_cost(&self, from: T, to: T, base: Scalar) -> Option<Scalar> {
+        self(from, to, base)
+    }
+}
+
+/// Rule of combining connection weight with costs of both connected nodes into base cost.
+///
+/// Connection weights are distances in every navigation structure: cells distances for grids,
+/// straight distances between vertices for `NavNet` and distances between triangles centers
+/// going through shared edge midpoint for `NavMesh` (even though `NavMesh` and `NavNet` filters
+/// get squared distances), so all models produce costs in distance units. Node cost of 1 is
+/// neutral in every model.
+///
+/// # Heuristic admissibility
+/// Heuristic must never overestimate remaining cost, otherwise found paths might not be the
+/// shortest ones. Since connection weights are never smaller than straight distance between
+/// nodes, heuristic equal to straight distance multiplied by `heuristic_scale` of the lowest
+/// node cost is admissible:
+/// * `Multiplicative` - scale is squared lowest cost, so costs below 1 shrink it quickly and
+///   zero cost (which makes traversal free) disables heuristic completely.
+/// * `Additive` - scale is 1 while no node is cheaper than 1, otherwise heuristic is disabled,
+///   since connection cost can then get below its weight by any fraction.
+/// * `AreaWeighted` and `MaxOfPair` - scale is the lowest cost.
+///
+/// # Example
+/// ```
+/// use navmesh::*;
+///
+/// let mut grid = NavGrid::new(3, 1, vec![true; 3]).unwrap();
+/// grid.set_cell_cost(1, 0, 3.0);
+/// assert_eq!(NavCostModel::Multiplicative.combine(1.0, 1.0, 3.0), 3.0);
+/// assert_eq!(NavCostModel::Additive.combine(1.0, 1.0, 3.0), 2.0);
+/// assert_eq!(NavCostModel::AreaWeighted.combine(1.0, 1.0, 3.0), 2.0);
+/// assert_eq!(NavCostModel::MaxOfPair.combine(1.0, 1.0, 3.0), 3.0);
+///
+/// grid.set_cost_model(NavCostModel::AreaWeighted);
+/// let path = grid.find_path_with_cost((0, 0), (2, 0), &NavDefaultCost).unwrap().points;
+/// assert_eq!(path, vec![(0, 0), (1, 0), (2, 0)]);
+/// // cost model can be also selected per query.
+/// let path = grid.find_path_with_cost((0, 0), (2, 0), &NavCostModel::Additive).unwrap().points;
+/// assert_eq!(path, vec![(0, 0), (1, 0), (2, 0)]);
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NavCostModel {
+    /// `weight * from cost * to cost`.
+    #[default]
+    Multiplicative,
+    /// `weight + (from cost + to cost) / 2 - 1`, never below zero - every traversed node adds
+    /// its cost above neutral 1 once.
+    Additive,
+    /// `from cost * weight inside from node + to cost * weight inside to node`.
+    AreaWeighted,
+    /// `weight * max(from cost, to cost)`.
+    MaxOfPair,
+}
+
+impl NavCostModel {
+    /// Combine connection weight with costs of connected nodes, assuming connection is split
+    /// evenly between both nodes.
+    ///
+    /// # Arguments
+    /// * `weight` - connection weight.
+    /// * `from_cost` - cost of node that path leaves.
+    /// * `to_cost` - cost of node that path enters.
+    #[inline]
+    pub fn combine(self, weight: Scalar, from_cost: Scalar, to_cost: Scalar) -> Scalar {
+        let half = weight * 0.5;
+        self.combine_split(half, half, from_cost, to_cost)
+    }
+
+    /// Combine connection weight with costs of connected nodes.
+    ///
+    /// # Arguments
+    /// * `from_weight` - part of connection weight inside node that path leaves.
+    /// * `to_weight` - part of connection weight inside node that path enters.
+    /// * `from_cost` - cost of node that path leaves.
+    /// * `to_cost` - cost of node that path enters.
+    #[inline]
+    pub fn combine_split(
+        self,
+        from_weight: Scalar,
+        to_weight: Scalar,
+        from_cost: Scalar,
+        to_cost: Scalar,
+    ) -> Scalar {
+        let weight = from_weight + to_weight;
+        match self {
+            Self::Multiplicative => weight * from_cost * to_cost,
+            Self::Additive => (weight + (from_cost + to_cost) * 0.5 - 1.0).max(0.0),
+            Self::AreaWeighted => from_weight * from_cost + to_weight * to_cost,
+            Self::MaxOfPair => weight * from_cost.max(to_cost),
+        }
+    }
+
+    /// Factor that straight distance heuristic has to be multiplied by to stay admissible.
+    ///
+    /// # Arguments
+    /// * `min_cost` - lowest node cost in navigation structure.
+    #[inline]
+    pub fn heuristic_scale(self, min_cost: Scalar) -> Scalar {
+        let min_cost = min_cost.max(0.0);
+        match self {
+            Self::Multiplicative => min_cost * min_cost,
+            Self::Additive => {
+                if min_cost >= 1.0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Self::AreaWeighted | Self::MaxOfPair => min_cost,
+        }
+    }
+}
+
+impl<T> NavCost<T> for NavCostModel {
+    fn cost_model(&self) -> Option<NavCostModel> {
+        Some(*self)
+    }
+}
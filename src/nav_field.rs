@@ -0,0 +1,203 @@
+use crate::{NavVec3, Scalar};
+use serde::{Deserialize, Serialize};
+
+const NO_NEXT: usize = usize::MAX;
+
+/// Grid flow field leading every cell towards single goal cell.
+///
+/// Flow field stores integrated cost of reaching goal and best next cell for each grid cell,
+/// so any number of agents can follow it without running their own path finding.
+///
+/// # Example
+/// ```
+/// use navmesh::*;
+///
+/// let grid = NavGrid::new(
+///     3,
+///     3,
+///     vec![true, true, true, true, false, true, true, true, true],
+/// )
+/// .unwrap();
+/// let field = grid.flow_field((2, 2), |_, _| true).unwrap();
+/// assert_eq!(field.cost(2, 2), Some(0.0));
+/// assert_eq!(field.cost(0, 0), Some(4.0));
+/// assert_eq!(field.cost(1, 1), None);
+/// assert_eq!(field.next(2, 1), Some((2, 2)));
+/// assert_eq!(field.direction(1, 2), Some((1, 0)));
+/// assert_eq!(field.path(0, 0).unwrap().len(), 5);
+/// ```
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct NavFlowField {
+    cols: usize,
+    rows: usize,
+    goal: (usize, usize),
+    costs: Vec<Option<Scalar>>,
+    next: Vec<usize>,
+}
+
+impl NavFlowField {
+    pub(crate) fn new(
+        cols: usize,
+        rows: usize,
+        goal: (usize, usize),
+        costs: Vec<Option<Scalar>>,
+        next: Vec<Option<usize>>,
+    ) -> Self {
+        Self {
+            cols,
+            rows,
+            goal,
+            costs,
+            next: next.into_iter().map(|n| n.unwrap_or(NO_NEXT)).collect(),
+        }
+    }
+
+    #[inline]
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    #[inline]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    #[inline]
+    pub fn goal(&self) -> (usize, usize) {
+        self.goal
+    }
+
+    /// Integrated cost of reaching goal from given cell or `None` if goal is unreachable.
+    pub fn cost(&self, col: usize, row: usize) -> Option<Scalar> {
+        *self.costs.get(self.index(col, row)?)?
+    }
+
+    /// Next cell on the way to goal or `None` for goal cell and cells that cannot reach it.
+    pub fn next(&self, col: usize, row: usize) -> Option<(usize, usize)> {
+        let next = *self.next.get(self.index(col, row)?)?;
+        if next == NO_NEXT {
+            None
+        } else {
+            Some((next % self.cols, next / self.cols))
+        }
+    }
+
+    /// Offset from given cell to next cell on the way to goal.
+    pub fn direction(&self, col: usize, row: usize) -> Option<(isize, isize)> {
+        let (next_col, next_row) = self.next(col, row)?;
+        Some((
+            next_col as isize - col as isize,
+            next_row as isize - row as isize,
+        ))
+    }
+
+    /// Cells leading from given cell to goal, including both.
+    pub fn path(&self, col: usize, row: usize) -> Option<Vec<(usize, usize)>> {
+        self.cost(col, row)?;
+        let mut result = vec![(col, row)];
+        let mut current = (col, row);
+        while let Some(next) = self.next(current.0, current.1) {
+            result.push(next);
+            current = next;
+        }
+        Some(result)
+    }
+
+    fn index(&self, col: usize, row: usize) -> Option<usize> {
+        if col < self.cols && row < self.rows {
+            Some(row * self.cols + col)
+        } else {
+            None
+        }
+    }
+}
+
+/// Nav mesh distance field leading every triangle towards single goal triangle.
+///
+/// # Example
+/// ```
+/// use navmesh::*;
+///
+/// let vertices = vec![
+///     (0.0, 0.0, 0.0).into(), // 0
+///     (1.0, 0.0, 0.0).into(), // 1
+///     (2.0, 0.0, 1.0).into(), // 2
+///     (0.0, 1.0, 0.0).into(), // 3
+///     (1.0, 1.0, 0.0).into(), // 4
+///     (2.0, 1.0, 1.0).into(), // 5
+/// ];
+/// let triangles = vec![
+///     (0, 1, 4).into(), // 0
+///     (4, 3, 0).into(), // 1
+///     (1, 2, 5).into(), // 2
+///     (5, 4, 1).into(), // 3
+/// ];
+///
+/// let mesh = NavMesh::new(vertices, triangles).unwrap();
+/// let field = mesh.distance_field(2).unwrap();
+/// assert_eq!(field.cost(2), Some(0.0));
+/// assert_eq!(field.next(1), Some(0));
+/// assert_eq!(field.path(1).unwrap(), vec![1, 0, 3, 2]);
+/// ```
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct NavDistanceField {
+    goal: usize,
+    costs: Vec<Option<Scalar>>,
+    next: Vec<usize>,
+    directions: Vec<NavVec3>,
+}
+
+impl NavDistanceField {
+    pub(crate) fn new(
+        goal: usize,
+        costs: Vec<Option<Scalar>>,
+        next: Vec<Option<usize>>,
+        directions: Vec<NavVec3>,
+    ) -> Self {
+        Self {
+            goal,
+            costs,
+            next: next.into_iter().map(|n| n.unwrap_or(NO_NEXT)).collect(),
+            directions,
+        }
+    }
+
+    #[inline]
+    pub fn goal(&self) -> usize {
+        self.goal
+    }
+
+    /// Integrated cost of reaching goal from given triangle or `None` if goal is unreachable.
+    pub fn cost(&self, triangle: usize) -> Option<Scalar> {
+        *self.costs.get(triangle)?
+    }
+
+    /// Next triangle on the way to goal or `None` for goal triangle and triangles that cannot
+    /// reach it.
+    pub fn next(&self, triangle: usize) -> Option<usize> {
+        let next = *self.next.get(triangle)?;
+        if next == NO_NEXT {
+            None
+        } else {
+            Some(next)
+        }
+    }
+
+    /// Normalized direction from given triangle center to next triangle center.
+    pub fn direction(&self, triangle: usize) -> Option<NavVec3> {
+        self.next(triangle)?;
+        self.directions.get(triangle).copied()
+    }
+
+    /// Triangles leading from given triangle to goal, including both.
+    pub fn path(&self, triangle: usize) -> Option<Vec<usize>> {
+        self.cost(triangle)?;
+        let mut result = vec![triangle];
+        let mut current = triangle;
+        while let Some(next) = self.next(current) {
+            result.push(next);
+            current = next;
+        }
+        Some(result)
+    }
+}
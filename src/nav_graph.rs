@@ -0,0 +1,229 @@
+use crate::{
+    nav_search::astar_any, NavCost, NavDefaultCost, NavPath, NavSearchContext, NavVec3, Scalar,
+};
+use petgraph::{algo::tarjan_scc, graph::NodeIndex, Directed, Graph};
+
+/// Graph that path finding, islands and cost machinery can run over.
+///
+/// Nodes are identified by dense indices in range `0..node_count()`, so searches keep their
+/// state in flat buffers of `NavSearchContext` - custom graphs (road network stored in ECS for
+/// example) map their own node handles onto such indices. All navigation structures implement
+/// it too and run their own searches through it, with triangles (`NavMesh`), vertices
+/// (`NavNet`), cells (`NavGrid`, `NavFreeGrid`, `NavChunkedGrid`, `NavHexGrid`,
+/// `NavVoxelGrid`), free leaves (`NavOctree`) or portals (`NavIslands`) as nodes and their cost
+/// models applied in `edge_cost`.
+///
+/// # Example
+/// ```
+/// use navmesh::*;
+///
+/// struct Roads {
+///     crossings: Vec<NavVec3>,
+///     roads: Vec<(usize, usize)>,
+///     closed: Vec<usize>,
+/// }
+///
+/// impl NavGraph for Roads {
+///     fn node_count(&self) -> usize {
+///         self.crossings.len()
+///     }
+///
+///     fn neighbors(&self, node: usize) -> impl Iterator<Item = (usize, Scalar)> + '_ {
+///         self.roads.iter().filter_map(move |(a, b)| {
+///             let other = if *a == node { *b } else if *b == node { *a } else { return None };
+///             Some((other, (self.crossings[other] - self.crossings[node]).magnitude()))
+///         })
+///     }
+///
+///     fn edge_cost(&self, _from: usize, to: usize, weight: Scalar) -> Option<Scalar> {
+///         if self.closed.contains(&to) {
+///             None
+///         } else {
+///             Some(weight)
+///         }
+///     }
+///
+///     fn heuristic(&self, node: usize, goal: usize) -> Scalar {
+///         (self.crossings[goal] - self.crossings[node]).magnitude()
+///     }
+///
+///     fn node_position(&self, node: usize) -> Option<NavVec3> {
+///         self.crossings.get(node).copied()
+///     }
+/// }
+///
+/// let mut roads = Roads {
+///     crossings: vec![
+///         (0.0, 0.0, 0.0).into(),
+///         (1.0, 0.0, 0.0).into(),
+///         (2.0, 0.0, 0.0).into(),
+///         (1.0, 2.0, 0.0).into(),
+///     ],
+///     roads: vec![(0, 1), (1, 2), (0, 3), (3, 2)],
+///     closed: vec![],
+/// };
+/// assert_eq!(roads.find_graph_path(0, 2).unwrap().points, vec![0, 1, 2]);
+/// roads.closed.push(1);
+/// let path = roads.find_graph_path(0, 2).unwrap();
+/// assert_eq!(path.points, vec![0, 3, 2]);
+/// // closed crossing cannot be entered, so it makes island of its own.
+/// assert_eq!(roads.find_graph_islands().len(), 2);
+/// ```
+pub trait NavGraph {
+    fn node_count(&self) -> usize;
+
+    /// Nodes that can be entered directly from given node, paired with connection weights.
+    fn neighbors(&self, node: usize) -> impl Iterator<Item = (usize, Scalar)> + '_;
+
+    /// Cost of moving between two connected nodes.
+    ///
+    /// # Returns
+    /// `Some` with traversal cost or `None` if connection cannot be traversed.
+    fn edge_cost(&self, _from: usize, _to: usize, weight: Scalar) -> Option<Scalar> {
+        Some(weight)
+    }
+
+    /// Estimated cost of reaching goal from given node, which must never be greater than the
+    /// real cost.
+    fn heuristic(&self, _node: usize, _goal: usize) -> Scalar {
+        0.0
+    }
+
+    /// World position of node, `None` if graph has no spatial layout.
+    fn node_position(&self, node: usize) -> Option<NavVec3>;
+
+    /// Find shortest path between two nodes.
+    ///
+    /// Path length is measured between nodes positions (or with connections weights when nodes
+    /// have no positions).
+    fn find_graph_path(&self, from: usize, to: usize) -> Option<NavPath<usize>> {
+        self.find_graph_path_with_cost(from, to, &NavDefaultCost)
+    }
+
+    // cost params: nodes indices, edge cost.
+    fn find_graph_path_with_cost<C>(
+        &self,
+        from: usize,
+        to: usize,
+        cost: &C,
+    ) -> Option<NavPath<usize>>
+    where
+        C: NavCost<usize>,
+    {
+        let mut context = NavSearchContext::default();
+        let cost = find_graph_path_inner(self, &mut context, from, to, cost)?;
+        Some(make_graph_path(self, &context.path, cost))
+    }
+
+    // cost params: nodes indices, edge cost.
+    fn find_graph_path_with_context<'a, C>(
+        &'a self,
+        context: &'a mut NavSearchContext,
+        from: usize,
+        to: usize,
+        cost: &C,
+    ) -> Option<impl Iterator<Item = usize> + 'a>
+    where
+        C: NavCost<usize>,
+    {
+        find_graph_path_inner(self, context, from, to, cost)?;
+        Some(context.path.iter().copied())
+    }
+
+    /// Groups of nodes connected with each other through traversable connections.
+    fn find_graph_islands(&self) -> Vec<Vec<usize>> {
+        let count = self.node_count();
+        let mut graph = Graph::<(), (), Directed>::with_capacity(count, count);
+        for _ in 0..count {
+            graph.add_node(());
+        }
+        for from in 0..count {
+            for (to, weight) in self.neighbors(from) {
+                if to < count && self.edge_cost(from, to, weight).is_some() {
+                    graph.add_edge(NodeIndex::new(from), NodeIndex::new(to), ());
+                }
+            }
+        }
+        tarjan_scc(&graph)
+            .into_iter()
+            .map(|v| v.into_iter().map(|n| n.index()).collect::<Vec<_>>())
+            .filter(|v| !v.is_empty())
+            .collect()
+    }
+}
+
+fn find_graph_path_inner<G, C>(
+    graph: &G,
+    context: &mut NavSearchContext,
+    from: usize,
+    to: usize,
+    cost: &C,
+) -> Option<Scalar>
+where
+    G: NavGraph + ?Sized,
+    C: NavCost<usize>,
+{
+    if to >= graph.node_count() {
+        return None;
+    }
+    let (_, cost) = search_graph(
+        graph,
+        context,
+        from,
+        &[to],
+        |a, b, w| cost.edge_cost(a, b, graph.edge_cost(a, b, w)?),
+        |n, goal| {
+            cost.heuristic(n, goal)
+                .unwrap_or_else(|| graph.heuristic(n, goal))
+        },
+    )?;
+    Some(cost)
+}
+
+/// A* search over graph neighbours that terminates at first reached node of `goals` list.
+///
+/// Navigation structures route their searches through it, passing edge costs and heuristic
+/// (getting node index and goal node index) of their query. Found path is stored in
+/// `context.path`. Returns position of reached goal in `goals` list and path cost.
+pub(crate) fn search_graph<G, E, H>(
+    graph: &G,
+    context: &mut NavSearchContext,
+    from: usize,
+    goals: &[usize],
+    edge_cost: E,
+    heuristic: H,
+) -> Option<(usize, Scalar)>
+where
+    G: NavGraph + ?Sized,
+    E: FnMut(usize, usize, Scalar) -> Option<Scalar>,
+    H: FnMut(usize, usize) -> Scalar,
+{
+    astar_any(
+        context,
+        graph.node_count(),
+        from,
+        goals,
+        |n| graph.neighbors(n),
+        edge_cost,
+        heuristic,
+    )
+}
+
+fn make_graph_path<G>(graph: &G, path: &[usize], cost: Scalar) -> NavPath<usize>
+where
+    G: NavGraph + ?Sized,
+{
+    let length = path
+        .windows(2)
+        .map(
+            |pair| match (graph.node_position(pair[0]), graph.node_position(pair[1])) {
+                (Some(a), Some(b)) => (b - a).magnitude(),
+                _ => graph
+                    .neighbors(pair[0])
+                    .find(|(n, _)| *n == pair[1])
+                    .map_or(0.0, |(_, weight)| weight),
+            },
+        )
+        .sum();
+    NavPath::new(path.to_vec(), cost, length)
+}
@@ -1,54 +1,285 @@
-use crate::{Error, NavResult, Scalar};
+use crate::{
+    nav_graph::search_graph,
+    nav_path::{cells_distance, path_length},
+    nav_search::{astar_pruned, dijkstra, theta_star, NO_GOAL},
+    Error, NavCost, NavCostModel, NavFlowField, NavGraph, NavPath, NavResult, NavSearchContext,
+    NavVec3, Navigable, Scalar, SCALAR_SQRT_2,
+};
 use petgraph::{
-    algo::{astar, tarjan_scc},
-    graph::NodeIndex,
-    visit::EdgeRef,
-    Directed, Graph, Undirected,
+    algo::tarjan_scc, graph::NodeIndex, visit::EdgeRef, Directed, Direction, Graph, Undirected,
+};
+use serde::{
+    de::{
+        value::{MapAccessDeserializer, SeqAccessDeserializer},
+        Error as _, MapAccess, SeqAccess, Visitor,
+    },
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
 };
-use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
-#[cfg(not(feature = "scalar64"))]
-use std::f32::MAX as SCALAR_MAX;
-#[cfg(feature = "scalar64")]
-use std::f64::MAX as SCALAR_MAX;
 use typid::ID;
 
-#[cfg(feature = "parallel")]
-macro_rules! iter {
-    ($v:expr) => {
-        $v.par_iter()
-    };
-}
-#[cfg(not(feature = "parallel"))]
-macro_rules! iter {
-    ($v:expr) => {
-        $v.iter()
-    };
-}
-
-#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Explicit connection of `NavGrid` cells, going only from `from` to `to` unless made both
+/// ways.
+///
+/// # Example
+/// ```
+/// use navmesh::*;
+///
+/// let connection = NavGridConnection::new((0, 0), (1, 0))
+///     .with_cost(2.0)
+///     .with_both_ways(true);
+/// assert_eq!(connection.cost(), Some(2.0));
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub struct NavGridConnection {
     pub from: (usize, usize),
     pub to: (usize, usize),
+    #[serde(default, deserialize_with = "deserialize_connection_cost")]
+    cost: Option<Scalar>,
+    #[serde(default)]
+    both_ways: bool,
+}
+
+// cost is never NaN, it gets clamped when set or loaded.
+impl Eq for NavGridConnection {}
+
+impl NavGridConnection {
+    /// Create connection going only from `from` to `to`, costing cells distance.
+    pub fn new(from: (usize, usize), to: (usize, usize)) -> Self {
+        Self {
+            from,
+            to,
+            cost: None,
+            both_ways: false,
+        }
+    }
+
+    /// Use given cost of moving through connection in place of cells distance (which is 1).
+    pub fn with_cost(mut self, cost: Scalar) -> Self {
+        self.cost = Some(cost.max(0.0));
+        self
+    }
+
+    /// Connect cells also from `to` to `from`, with the same cost.
+    pub fn with_both_ways(mut self, both_ways: bool) -> Self {
+        self.both_ways = both_ways;
+        self
+    }
+
+    #[inline]
+    pub fn cost(&self) -> Option<Scalar> {
+        self.cost
+    }
+
+    #[inline]
+    pub fn both_ways(&self) -> bool {
+        self.both_ways
+    }
+
+    #[inline]
+    fn weight(&self) -> Scalar {
+        self.cost.unwrap_or(1.0).max(0.0)
+    }
+}
+
+// connection cost gets clamped the same way as in `with_cost`.
+fn deserialize_connection_cost<'de, D>(deserializer: D) -> Result<Option<Scalar>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<Scalar>::deserialize(deserializer)?.map(|cost| cost.max(0.0)))
 }
 
 /// Nav grid identifier.
 pub type NavGridID = ID<NavGrid>;
 
+/// Neighbourhood of cells connected by `NavGrid::with_connectivity`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NavGridConnectivity {
+    /// Horizontal and vertical neighbours.
+    #[default]
+    Four,
+    /// Horizontal, vertical and diagonal neighbours.
+    Eight,
+    /// Horizontal, vertical and diagonal neighbours, where diagonal move requires both
+    /// orthogonal cells next to it to be walkable.
+    EightNoCornerCutting,
+}
+
+impl NavGridConnectivity {
+    /// Shortest distance between cells when moving with this connectivity - manhattan distance
+    /// for `Four` and octile distance for others.
+    pub fn distance(self, from: (usize, usize), to: (usize, usize)) -> Scalar {
+        let dx = from.0.abs_diff(to.0) as Scalar;
+        let dy = from.1.abs_diff(to.1) as Scalar;
+        match self {
+            Self::Four => dx + dy,
+            Self::Eight | Self::EightNoCornerCutting => {
+                dx.max(dy) + (SCALAR_SQRT_2 - 1.0) * dx.min(dy)
+            }
+        }
+    }
+
+    // forward offsets - every one of them connects cells both ways.
+    fn offsets(self) -> &'static [(isize, isize)] {
+        match self {
+            Self::Four => &[(1, 0), (0, 1)],
+            Self::Eight | Self::EightNoCornerCutting => &[(1, 0), (0, 1), (1, 1), (-1, 1)],
+        }
+    }
+}
+
+/// Algorithm used by `NavGrid::find_path`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NavGridSearchMode {
+    /// Jump point search when all cells costs are equal and cost model keeps connections costs
+    /// proportional to their lengths, A* otherwise.
+    #[default]
+    Auto,
+    /// Always A*.
+    AStar,
+    /// Always jump point search, ignoring cells costs. Grids made of explicit connections still
+    /// use A*.
+    JumpPoint,
+}
+
+/// Placement of grid cells in world space.
+///
+/// Cells lie on XY plane at height of origin: cell `(0, 0)` starts at origin, columns go along
+/// X axis and rows along Y axis, both rotated around Z axis by `rotation` radians.
+///
+/// # Example
+/// ```
+/// use navmesh::*;
+///
+/// let transform = NavGridTransform {
+///     origin: (10.0, 0.0, 1.0).into(),
+///     cell_size: 2.0,
+///     rotation: 0.0,
+/// };
+/// assert_eq!(transform.cell_to_world((1, 2)), (13.0, 5.0, 1.0).into());
+/// assert_eq!(transform.world_to_cell((13.5, 4.0, 0.0).into()), (1, 2));
+/// assert_eq!(transform.world_to_cell((9.0, 0.0, 0.0).into()), (-1, 0));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NavGridTransform {
+    pub origin: NavVec3,
+    pub cell_size: Scalar,
+    pub rotation: Scalar,
+}
+
+impl Default for NavGridTransform {
+    fn default() -> Self {
+        Self {
+            origin: NavVec3::default(),
+            cell_size: 1.0,
+            rotation: 0.0,
+        }
+    }
+}
+
+impl NavGridTransform {
+    /// Check if transform maps world positions onto cells.
+    ///
+    /// # Returns
+    /// `Ok` or `Err` with `Error::InvalidCellSize` if cell size is not positive or not finite.
+    pub fn validate(&self) -> NavResult<()> {
+        if self.cell_size.is_finite() && self.cell_size > 0.0 {
+            Ok(())
+        } else {
+            Err(Error::InvalidCellSize(self.cell_size))
+        }
+    }
+
+    /// World position of given cell center.
+    pub fn cell_to_world(&self, (col, row): (isize, isize)) -> NavVec3 {
+        self.local_to_world(col as Scalar + 0.5, row as Scalar + 0.5)
+    }
+
+    /// Cell containing given world position projected onto grid plane.
+    pub fn world_to_cell(&self, point: NavVec3) -> (isize, isize) {
+        let (x, y) = self.world_to_local(point);
+        (x.floor() as isize, y.floor() as isize)
+    }
+
+    // grid plane position measured in cells.
+    pub(crate) fn local_to_world(&self, x: Scalar, y: Scalar) -> NavVec3 {
+        let (x, y) = (x * self.cell_size, y * self.cell_size);
+        let (sin, cos) = self.rotation.sin_cos();
+        self.origin + NavVec3::new(x * cos - y * sin, x * sin + y * cos, 0.0)
+    }
+
+    pub(crate) fn world_to_local(&self, point: NavVec3) -> (Scalar, Scalar) {
+        let offset = point - self.origin;
+        let (sin, cos) = self.rotation.sin_cos();
+        let x = offset.x * cos + offset.y * sin;
+        let y = offset.y * cos - offset.x * sin;
+        (x / self.cell_size, y / self.cell_size)
+    }
+}
+
+// serde impls are written by hand below to rebuild graph after loading.
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(remote = "Self")]
 pub struct NavGrid {
     id: NavGridID,
     cols: usize,
     rows: usize,
     cells: Vec<bool>,
     costs: Vec<Scalar>,
-    graph: Graph<(), (), Directed>,
-    nodes: Vec<Option<NodeIndex>>,
-    nodes_map: HashMap<NodeIndex, usize>,
+    #[serde(default)]
+    cost_model: NavCostModel,
+    // lowest cell cost, used to scale heuristic. recalculated after loading.
+    #[serde(skip)]
+    min_cost: Scalar,
+    // highest cell cost, costs are uniform when it equals lowest one. recalculated after
+    // loading.
+    #[serde(skip)]
+    max_cost: Scalar,
+    #[serde(default)]
+    search_mode: NavGridSearchMode,
+    // `None` for grids made of explicit connections.
+    #[serde(default)]
+    connectivity: Option<NavGridConnectivity>,
+    // explicit connections, kept to restore edges of cells that become walkable again.
+    #[serde(default)]
+    connections: Vec<NavGridConnection>,
+    #[serde(default)]
+    transform: Option<NavGridTransform>,
+    // graph node indices are the same as cells indices. it is rebuilt from cells after loading,
+    // while data containing graph comes from older versions (which had nodes only for walkable
+    // cells and did not store connectivity) and gets migrated.
+    #[serde(
+        skip_serializing,
+        default,
+        deserialize_with = "deserialize_legacy_graph"
+    )]
+    graph: Graph<(), Scalar, Directed>,
+}
+
+fn deserialize_legacy_graph<'de, D>(
+    deserializer: D,
+) -> Result<Graph<(), Scalar, Directed>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Graph::<(), (), Directed>::deserialize(deserializer)?.map(|_, _| (), |_, _| 1.0))
 }
 
 impl NavGrid {
     pub fn new(cols: usize, rows: usize, cells: Vec<bool>) -> NavResult<Self> {
+        Self::with_connectivity(cols, rows, cells, NavGridConnectivity::Four)
+    }
+
+    pub fn with_connectivity(
+        cols: usize,
+        rows: usize,
+        cells: Vec<bool>,
+        connectivity: NavGridConnectivity,
+    ) -> NavResult<Self> {
         if cols == 0 || rows == 0 {
             return Err(Error::EmptyCells(cols, rows));
         }
@@ -60,56 +291,23 @@ impl NavGrid {
             ));
         }
         let costs = vec![1.0; cells.len()];
-        let mut graph = Graph::<(), (), Directed>::with_capacity(
-            cells.len(),
-            (cols - 1) * rows + (rows - 1) * cols,
-        );
-        let nodes = (0..cells.len())
-            .zip(cells.iter())
-            .map(|(_, cell)| {
-                if *cell {
-                    Some(graph.add_node(()))
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>();
-        for ca in 0..(cols - 1) {
-            for r in 0..rows {
-                let cb = ca + 1;
-                let ia = r * cols + ca;
-                let ib = r * cols + cb;
-                if let (Some(na), Some(nb)) = (nodes[ia], nodes[ib]) {
-                    graph.add_edge(na, nb, ());
-                    graph.add_edge(nb, na, ());
-                }
-            }
-        }
-        for c in 0..cols {
-            for ra in 0..(rows - 1) {
-                let rb = ra + 1;
-                let ia = ra * cols + c;
-                let ib = rb * cols + c;
-                if let (Some(na), Some(nb)) = (nodes[ia], nodes[ib]) {
-                    graph.add_edge(na, nb, ());
-                    graph.add_edge(nb, na, ());
-                }
-            }
-        }
-        let nodes_map = iter!(nodes)
-            .enumerate()
-            .filter_map(|(i, n)| n.map(|n| (n, i)))
-            .collect();
-        Ok(Self {
+        let mut result = Self {
             id: NavGridID::new(),
             cols,
             rows,
             cells,
             costs,
-            graph,
-            nodes,
-            nodes_map,
-        })
+            cost_model: NavCostModel::default(),
+            min_cost: 1.0,
+            max_cost: 1.0,
+            search_mode: NavGridSearchMode::default(),
+            connectivity: Some(connectivity),
+            connections: vec![],
+            transform: None,
+            graph: Default::default(),
+        };
+        result.rebuild_graph();
+        Ok(result)
     }
 
     pub fn with_connections(
@@ -140,39 +338,28 @@ impl NavGrid {
             }
         }
         let costs = vec![1.0; count];
-        let mut graph =
-            Graph::<(), (), Directed>::with_capacity(count, (cols - 1) * rows + (rows - 1) * cols);
-        let nodes = (0..count)
-            .map(|index| {
-                let coord = (index % cols, index / cols);
-                if connections.iter().any(|c| c.from == coord || c.to == coord) {
-                    Some(graph.add_node(()))
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>();
-        for connection in connections {
-            let ia = connection.from.1 * cols + connection.from.0;
-            let ib = connection.to.1 * cols + connection.to.0;
-            if let (Some(na), Some(nb)) = (nodes[ia], nodes[ib]) {
-                graph.add_edge(na, nb, ());
-            }
+        let mut cells = vec![false; count];
+        for connection in &connections {
+            cells[connection.from.1 * cols + connection.from.0] = true;
+            cells[connection.to.1 * cols + connection.to.0] = true;
         }
-        let nodes_map = iter!(nodes)
-            .enumerate()
-            .filter_map(|(i, n)| n.map(|n| (n, i)))
-            .collect();
-        Ok(Self {
+        let mut result = Self {
             id: NavGridID::new(),
             cols,
             rows,
-            cells: nodes.iter().map(Option::is_some).collect(),
+            cells,
             costs,
-            graph,
-            nodes,
-            nodes_map,
-        })
+            cost_model: NavCostModel::default(),
+            min_cost: 1.0,
+            max_cost: 1.0,
+            search_mode: NavGridSearchMode::default(),
+            connectivity: None,
+            connections,
+            transform: None,
+            graph: Default::default(),
+        };
+        result.rebuild_graph();
+        Ok(result)
     }
 
     #[inline]
@@ -180,6 +367,16 @@ impl NavGrid {
         self.id
     }
 
+    #[inline]
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    #[inline]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
     #[inline]
     pub fn cells(&self) -> &[bool] {
         &self.cells
@@ -190,263 +387,1858 @@ impl NavGrid {
         &self.costs
     }
 
-    #[inline]
+    // might scan all cells when lowest or highest cost changes.
     pub fn set_cell_cost(&mut self, col: usize, row: usize, cost: Scalar) -> Option<Scalar> {
         let index = self.index(col, row)?;
         let c = self.costs.get_mut(index)?;
         let old = *c;
-        *c = cost.max(0.0);
+        let cost = cost.max(0.0);
+        *c = cost;
+        if cost < self.min_cost {
+            self.min_cost = cost;
+        } else if old <= self.min_cost {
+            self.min_cost = self.costs.iter().copied().fold(Scalar::MAX, Scalar::min);
+        }
+        if cost > self.max_cost {
+            self.max_cost = cost;
+        } else if old >= self.max_cost {
+            self.max_cost = self.costs.iter().copied().fold(0.0, Scalar::max);
+        }
         Some(old)
     }
 
-    pub fn neighbors(
-        &self,
-        col: usize,
-        row: usize,
-    ) -> Option<impl Iterator<Item = (usize, usize)> + '_> {
-        let index = self.index(col, row)?;
-        let node = self.nodes[index]?;
-        Some(self.graph.neighbors(node).filter_map(|node| {
-            self.nodes_map
-                .get(&node)
-                .and_then(|index| self.coord(*index))
-        }))
-    }
-
-    pub fn find_path(
-        &self,
-        from: (usize, usize),
-        to: (usize, usize),
-    ) -> Option<Vec<(usize, usize)>> {
-        self.find_path_custom(from, to, |_, _| true)
-    }
-
-    // filter params: first col-row, second col-row.
-    pub fn find_path_custom<F>(
-        &self,
-        from: (usize, usize),
-        to: (usize, usize),
-        mut filter: F,
-    ) -> Option<Vec<(usize, usize)>>
-    where
-        F: FnMut((usize, usize), (usize, usize)) -> bool,
-    {
-        let start_index = self.index(from.0, from.1)?;
-        let end_index = self.index(to.0, to.1)?;
-        let start_node = (*self.nodes.get(start_index)?)?;
-        let end_node = (*self.nodes.get(end_index)?)?;
-        let nodes = astar(
-            &self.graph,
-            start_node,
-            |n| n == end_node,
-            |e| {
-                let a = self.nodes_map[&e.source()];
-                let b = self.nodes_map[&e.target()];
-                if filter(self.coord(a).unwrap(), self.coord(b).unwrap()) {
-                    let a = self.costs[a];
-                    let b = self.costs[b];
-                    a * b
-                } else {
-                    SCALAR_MAX
-                }
-            },
-            |_| 0.0,
-        )?
-        .1;
-        Some(
-            nodes
-                .into_iter()
-                .filter_map(|n| self.coord(self.nodes_map[&n]))
-                .collect::<Vec<_>>(),
-        )
+    #[inline]
+    pub fn search_mode(&self) -> NavGridSearchMode {
+        self.search_mode
     }
 
-    pub fn find_islands(&self) -> Vec<Vec<(usize, usize)>> {
-        tarjan_scc(&self.graph)
-            .into_iter()
-            .map(|v| {
-                v.into_iter()
-                    .filter_map(|n| self.nodes_map.get(&n).and_then(|i| self.coord(*i)))
-                    .collect::<Vec<_>>()
-            })
-            .filter(|v| !v.is_empty())
-            .collect()
+    #[inline]
+    pub fn set_search_mode(&mut self, mode: NavGridSearchMode) {
+        self.search_mode = mode;
     }
 
-    pub fn index(&self, col: usize, row: usize) -> Option<usize> {
-        if col < self.cols && row < self.rows {
-            Some(row * self.cols + col)
+    pub fn set_cell_walkable(&mut self, col: usize, row: usize, walkable: bool) -> Option<bool> {
+        let index = self.index(col, row)?;
+        let old = self.cells[index];
+        if old == walkable {
+            return Some(old);
+        }
+        self.cells[index] = walkable;
+        if walkable {
+            self.connect_cell(index);
         } else {
-            None
+            self.disconnect_cell(index);
+        }
+        if self.connectivity == Some(NavGridConnectivity::EightNoCornerCutting) {
+            // diagonal moves around this cell corners depend on its walkability.
+            for (dc, dr) in [(-1, -1), (1, -1), (-1, 1), (1, 1)] {
+                let a = (col as isize + dc, row as isize);
+                let b = (col as isize, row as isize + dr);
+                if let (Some(ia), Some(ib)) = (self.index_signed(a), self.index_signed(b)) {
+                    self.disconnect_cells(ia, ib);
+                    self.connect_cells(ia, ib);
+                }
+            }
         }
+        Some(old)
     }
 
-    pub fn coord(&self, index: usize) -> Option<(usize, usize)> {
-        let col = index % self.cols;
-        let row = index / self.cols;
-        if col < self.cols && row < self.rows {
-            Some((col, row))
-        } else {
-            None
+    pub fn fill_rect(&mut self, col: usize, row: usize, cols: usize, rows: usize, walkable: bool) {
+        let to_col = (col + cols).min(self.cols);
+        let to_row = (row + rows).min(self.rows);
+        for r in row..to_row {
+            for c in col..to_col {
+                self.set_cell_walkable(c, r, walkable);
+            }
         }
     }
-}
-
-#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
-pub struct NavFreeGridConnection {
-    pub from: (isize, isize),
-    pub to: (isize, isize),
-}
-
-/// Nav free grid identifier.
-pub type NavFreeGridID = ID<NavFreeGrid>;
-
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
-pub struct NavFreeGrid {
-    id: NavFreeGridID,
-    cells: Vec<(isize, isize)>,
-    costs: Vec<Scalar>,
-    graph: Graph<(), (), Undirected>,
-    nodes: Vec<NodeIndex>,
-    nodes_map: HashMap<NodeIndex, usize>,
-}
 
-impl NavFreeGrid {
-    pub fn new(connections: Vec<NavFreeGridConnection>) -> Self {
-        let cells = connections
-            .iter()
-            .map(|c| c.from)
-            .chain(connections.iter().map(|c| c.to))
-            .collect::<HashSet<_>>()
-            .into_iter()
-            .collect::<Vec<_>>();
-        let costs = vec![1.0; cells.len()];
-        let mut graph = Graph::<(), (), Undirected>::with_capacity(cells.len(), connections.len());
-        let nodes = (0..cells.len())
-            .map(|_| graph.add_node(()))
-            .collect::<Vec<_>>();
-        for connection in connections {
-            let ia = cells.iter().position(|c| connection.from == *c);
-            let ib = cells.iter().position(|c| connection.to == *c);
-            if let (Some(ia), Some(ib)) = (ia, ib) {
-                graph.add_edge(nodes[ia], nodes[ib], ());
+    // fills cells which centers are within radius from center of given cell.
+    pub fn fill_circle(&mut self, col: usize, row: usize, radius: Scalar, walkable: bool) {
+        let extent = radius.max(0.0) as usize;
+        let radius_sqr = radius * radius;
+        for r in row.saturating_sub(extent)..=(row + extent).min(self.rows.saturating_sub(1)) {
+            for c in col.saturating_sub(extent)..=(col + extent).min(self.cols.saturating_sub(1)) {
+                let dc = c.abs_diff(col) as Scalar;
+                let dr = r.abs_diff(row) as Scalar;
+                if dc * dc + dr * dr <= radius_sqr {
+                    self.set_cell_walkable(c, r, walkable);
+                }
             }
         }
-        let nodes_map = iter!(nodes).enumerate().map(|(i, n)| (*n, i)).collect();
-        Self {
-            id: NavFreeGridID::new(),
-            cells,
-            costs,
-            graph,
-            nodes,
-            nodes_map,
-        }
     }
 
     #[inline]
-    pub fn id(&self) -> NavFreeGridID {
-        self.id
+    pub fn connectivity(&self) -> Option<NavGridConnectivity> {
+        self.connectivity
     }
 
     #[inline]
-    pub fn cells(&self) -> &[(isize, isize)] {
-        &self.cells
+    pub fn cost_model(&self) -> NavCostModel {
+        self.cost_model
     }
 
     #[inline]
-    pub fn cells_costs(&self) -> &[Scalar] {
-        &self.costs
+    pub fn set_cost_model(&mut self, model: NavCostModel) {
+        self.cost_model = model;
     }
 
     #[inline]
-    pub fn set_cell_cost(&mut self, col: isize, row: isize, cost: Scalar) -> Option<Scalar> {
-        let index = self.index(col, row)?;
-        let c = self.costs.get_mut(index)?;
-        let old = *c;
-        *c = cost.max(0.0);
-        Some(old)
+    pub fn transform(&self) -> Option<NavGridTransform> {
+        self.transform
+    }
+
+    /// Set world placement of grid.
+    ///
+    /// # Returns
+    /// `Ok` or `Err` with `Error::InvalidCellSize` if transform cell size is not positive.
+    pub fn set_transform(&mut self, transform: Option<NavGridTransform>) -> NavResult<()> {
+        if let Some(transform) = &transform {
+            transform.validate()?;
+        }
+        self.transform = transform;
+        Ok(())
+    }
+
+    /// World position of given cell center. Grid without transform uses default one.
+    pub fn cell_to_world(&self, (col, row): (usize, usize)) -> NavVec3 {
+        self.transform
+            .unwrap_or_default()
+            .cell_to_world((col as isize, row as isize))
+    }
+
+    /// Cell containing given world position or `None` if position is outside of grid.
+    pub fn world_to_cell(&self, point: NavVec3) -> Option<(usize, usize)> {
+        let (col, row) = self.transform.unwrap_or_default().world_to_cell(point);
+        if col < 0 || row < 0 {
+            return None;
+        }
+        self.index(col as usize, row as usize)?;
+        Some((col as usize, row as usize))
     }
 
     pub fn neighbors(
         &self,
-        col: isize,
-        row: isize,
-    ) -> Option<impl Iterator<Item = (isize, isize)> + '_> {
+        col: usize,
+        row: usize,
+    ) -> Option<impl Iterator<Item = (usize, usize)> + '_> {
         let index = self.index(col, row)?;
-        let node = self.nodes[index];
-        Some(self.graph.neighbors(node).filter_map(|node| {
-            self.nodes_map
-                .get(&node)
-                .and_then(|index| self.coord(*index))
-        }))
+        if !self.cells[index] {
+            return None;
+        }
+        Some(
+            self.graph
+                .neighbors(NodeIndex::new(index))
+                .filter_map(|node| self.coord(node.index())),
+        )
     }
 
     pub fn find_path(
         &self,
-        from: (isize, isize),
-        to: (isize, isize),
-    ) -> Option<Vec<(isize, isize)>> {
-        self.find_path_custom(from, to, |_, _| true)
+        from: (usize, usize),
+        to: (usize, usize),
+    ) -> Option<NavPath<(usize, usize)>> {
+        let jump_point = match self.search_mode {
+            NavGridSearchMode::Auto => {
+                self.min_cost > 0.0
+                    && self.min_cost == self.max_cost
+                    && (self.cost_model != NavCostModel::Additive || self.min_cost == 1.0)
+            }
+            NavGridSearchMode::AStar => false,
+            NavGridSearchMode::JumpPoint => true,
+        };
+        match self.connectivity {
+            Some(connectivity) if jump_point => {
+                let mut context = NavSearchContext::default();
+                self.find_path_jump_point(&mut context, connectivity, from, to)
+            }
+            _ => self.find_path_custom(from, to, |_, _, _| true),
+        }
     }
 
-    // filter params: first col-row, second col-row.
+    /// Find path between world positions, going through centers of cells containing them.
+    ///
+    /// Returned path length is measured in world units.
+    pub fn find_path_world(&self, from: NavVec3, to: NavVec3) -> Option<NavPath<NavVec3>> {
+        let from = self.world_to_cell(from)?;
+        let to = self.world_to_cell(to)?;
+        let path = self.find_path(from, to)?;
+        let points = path
+            .points
+            .into_iter()
+            .map(|cell| self.cell_to_world(cell))
+            .collect();
+        Some(NavPath::from_points(points, path.cost))
+    }
+
+    // filter params: first col-row, second col-row, connection cost.
+    // connections rejected by filter cannot be traversed.
     pub fn find_path_custom<F>(
         &self,
-        from: (isize, isize),
-        to: (isize, isize),
+        from: (usize, usize),
+        to: (usize, usize),
         mut filter: F,
-    ) -> Option<Vec<(isize, isize)>>
+    ) -> Option<NavPath<(usize, usize)>>
     where
-        F: FnMut((isize, isize), (isize, isize)) -> bool,
+        F: FnMut((usize, usize), (usize, usize), Scalar) -> bool,
     {
-        let start_index = self.index(from.0, from.1)?;
-        let end_index = self.index(to.0, to.1)?;
-        let start_node = *self.nodes.get(start_index)?;
-        let end_node = *self.nodes.get(end_index)?;
-        let nodes = astar(
-            &self.graph,
-            start_node,
-            |n| n == end_node,
-            |e| {
-                let a = self.nodes_map[&e.source()];
-                let b = self.nodes_map[&e.target()];
-                if filter(self.coord(a).unwrap(), self.coord(b).unwrap()) {
-                    let a = self.costs[a];
-                    let b = self.costs[b];
-                    a * b
+        let mut context = NavSearchContext::default();
+        let (_, cost) = self.find_path_inner(
+            &mut context,
+            from,
+            &[to],
+            |a, b, w| {
+                let cost = self.connection_cost(self.cost_model, a, b, w);
+                if filter(self.coord(a).unwrap(), self.coord(b).unwrap(), cost) {
+                    Some(cost)
                 } else {
-                    SCALAR_MAX
+                    None
                 }
             },
-            |_| 0.0,
-        )?
-        .1;
-        Some(
-            nodes
-                .into_iter()
-                .filter_map(|n| self.coord(self.nodes_map[&n]))
-                .collect::<Vec<_>>(),
-        )
+            |n, goal| self.heuristic(self.cost_model, n, goal),
+        )?;
+        Some(self.make_path(&context.path, cost))
     }
 
-    pub fn find_islands(&self) -> Vec<Vec<(isize, isize)>> {
-        tarjan_scc(&self.graph)
-            .into_iter()
-            .map(|v| {
-                v.into_iter()
-                    .filter_map(|n| self.nodes_map.get(&n).and_then(|i| self.coord(*i)))
-                    .collect::<Vec<_>>()
-            })
-            .filter(|v| !v.is_empty())
-            .collect()
-    }
+    // cost params: first col-row, second col-row.
+    pub fn find_path_with_cost<C>(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+        cost: &C,
+    ) -> Option<NavPath<(usize, usize)>>
+    where
+        C: NavCost<(usize, usize)>,
+    {
+        let mut context = NavSearchContext::default();
+        let cost = self.find_path_cost_inner(&mut context, from, to, cost)?;
+        Some(self.make_path(&context.path, cost))
+    }
+
+    // cost params: first col-row, second col-row.
+    pub fn find_path_with_context<'a, C>(
+        &'a self,
+        context: &'a mut NavSearchContext,
+        from: (usize, usize),
+        to: (usize, usize),
+        cost: &C,
+    ) -> Option<impl Iterator<Item = (usize, usize)> + 'a>
+    where
+        C: NavCost<(usize, usize)>,
+    {
+        self.find_path_cost_inner(context, from, to, cost)?;
+        Some(context.path.iter().filter_map(move |n| self.coord(*n)))
+    }
+
+    // returns index of reached target and path to it.
+    pub fn find_path_to_any(
+        &self,
+        from: (usize, usize),
+        targets: &[(usize, usize)],
+    ) -> Option<(usize, NavPath<(usize, usize)>)> {
+        let mut context = NavSearchContext::default();
+        let (winner, cost) = self.find_path_inner(
+            &mut context,
+            from,
+            targets,
+            |a, b, w| Some(self.connection_cost(self.cost_model, a, b, w)),
+            |n, goal| self.heuristic(self.cost_model, n, goal),
+        )?;
+        Some((winner, self.make_path(&context.path, cost)))
+    }
+
+    /// Find path to given cell or, when it cannot be reached, to reachable cell closest to it
+    /// (which makes path partial). Returns `None` only when either cell is out of grid or start
+    /// cell is not walkable.
+    pub fn find_path_partial(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+    ) -> Option<NavPath<(usize, usize)>> {
+        let start_index = self.index(from.0, from.1).filter(|i| self.cells[*i])?;
+        let end_index = self.index(to.0, to.1)?;
+        let mut context = NavSearchContext::default();
+        if let Some((_, cost)) = search_graph(
+            self,
+            &mut context,
+            start_index,
+            &[end_index],
+            |a, b, w| Some(self.connection_cost(self.cost_model, a, b, w)),
+            |n, goal| self.heuristic(self.cost_model, n, goal),
+        ) {
+            return Some(self.make_path(&context.path, cost));
+        }
+        // closest reached cell, cheaper one wins between equally distant ones.
+        let distance = |index: usize| {
+            let (col, row) = self.coord(index).unwrap();
+            cells_distance(col.abs_diff(to.0), row.abs_diff(to.1))
+        };
+        let (mut index, cost) = (0..self.cells.len())
+            .filter_map(|index| context.visited(index).map(|(cost, _)| (index, cost)))
+            .min_by(|a, b| {
+                distance(a.0)
+                    .partial_cmp(&distance(b.0))
+                    .unwrap_or(Ordering::Equal)
+                    .then(a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+            })?;
+        let mut path = vec![index];
+        while index != start_index {
+            index = context.visited(index)?.1;
+            path.push(index);
+        }
+        path.reverse();
+        let mut result = self.make_path(&path, cost);
+        result.partial = true;
+        Some(result)
+    }
+
+    // filter params: first col-row, second col-row.
+    pub fn flow_field<F>(&self, goal: (usize, usize), mut filter: F) -> Option<NavFlowField>
+    where
+        F: FnMut((usize, usize), (usize, usize)) -> bool,
+    {
+        let goal_index = self.index(goal.0, goal.1)?;
+        if !self.cells[goal_index] {
+            return None;
+        }
+        let mut context = NavSearchContext::default();
+        // search goes backwards from goal, so it follows incoming connections.
+        dijkstra(
+            &mut context,
+            self.cells.len(),
+            goal_index,
+            |n| {
+                self.graph
+                    .edges_directed(NodeIndex::new(n), Direction::Incoming)
+                    .map(|e| (e.source().index(), *e.weight()))
+            },
+            |a, b, w| {
+                if filter(self.coord(b).unwrap(), self.coord(a).unwrap()) {
+                    Some(self.connection_cost(self.cost_model, b, a, w))
+                } else {
+                    None
+                }
+            },
+        );
+        let (costs, next) = (0..self.cells.len())
+            .map(|index| match context.visited(index) {
+                Some((cost, parent)) if index != goal_index => (Some(cost), Some(parent)),
+                Some((cost, _)) => (Some(cost), None),
+                None => (None, None),
+            })
+            .unzip();
+        Some(NavFlowField::new(self.cols, self.rows, goal, costs, next))
+    }
+
+    // returns waypoints in continuous cells space, where cell (col, row) spans from (col, row)
+    // to (col + 1, row + 1). cells costs are ignored.
+    pub fn find_path_any_angle(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+    ) -> Option<NavPath<(Scalar, Scalar)>> {
+        let start_index = self.index(from.0, from.1)?;
+        let end_index = self.index(to.0, to.1)?;
+        if !self.cells[start_index] || !self.cells[end_index] {
+            return None;
+        }
+        let mut context = NavSearchContext::default();
+        let length = theta_star(
+            &mut context,
+            self.cells.len(),
+            start_index,
+            end_index,
+            |n| {
+                self.graph
+                    .neighbors(NodeIndex::new(n))
+                    .map(|node| node.index())
+            },
+            |a, b| self.line_of_sight(self.coord(a).unwrap(), self.coord(b).unwrap()),
+            |a, b| {
+                let (ca, ra) = self.coord(a).unwrap();
+                let (cb, rb) = self.coord(b).unwrap();
+                let dx = ca.abs_diff(cb) as Scalar;
+                let dy = ra.abs_diff(rb) as Scalar;
+                (dx * dx + dy * dy).sqrt()
+            },
+        )?;
+        let points = context
+            .path
+            .iter()
+            .map(|n| Self::cell_center(self.coord(*n).unwrap()))
+            .collect();
+        Some(NavPath::new(points, length, length))
+    }
+
+    // removes waypoints of cells path that can be skipped in straight line and returns them in
+    // continuous cells space.
+    pub fn string_pull(&self, path: &[(usize, usize)]) -> Vec<(Scalar, Scalar)> {
+        let mut result = Vec::with_capacity(path.len());
+        let (first, last) = match (path.first(), path.last()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => return result,
+        };
+        result.push(Self::cell_center(first));
+        let mut anchor = first;
+        for pair in path.windows(2).skip(1) {
+            if !self.line_of_sight(anchor, pair[1]) {
+                anchor = pair[0];
+                result.push(Self::cell_center(anchor));
+            }
+        }
+        if path.len() > 1 {
+            result.push(Self::cell_center(last));
+        }
+        result
+    }
+
+    // tells if straight line between cells centers goes only through walkable cells. line
+    // passing exactly through cells corner requires both cells next to that corner walkable.
+    pub fn line_of_sight(&self, from: (usize, usize), to: (usize, usize)) -> bool {
+        let (mut col, mut row) = (from.0 as isize, from.1 as isize);
+        if !self.walkable(col, row) || !self.walkable(to.0 as isize, to.1 as isize) {
+            return false;
+        }
+        let dx = to.0 as isize - col;
+        let dy = to.1 as isize - row;
+        let (step_x, step_y) = (dx.signum(), dy.signum());
+        let (count_x, count_y) = (dx.abs(), dy.abs());
+        let (mut x, mut y) = (0, 0);
+        while x < count_x || y < count_y {
+            // compare distances along the line to next vertical and horizontal cell borders.
+            let border_x = (2 * x + 1) * count_y;
+            let border_y = (2 * y + 1) * count_x;
+            if y >= count_y || (x < count_x && border_x < border_y) {
+                col += step_x;
+                x += 1;
+            } else if x >= count_x || border_y < border_x {
+                row += step_y;
+                y += 1;
+            } else {
+                if !self.walkable(col + step_x, row) || !self.walkable(col, row + step_y) {
+                    return false;
+                }
+                col += step_x;
+                row += step_y;
+                x += 1;
+                y += 1;
+            }
+            if !self.walkable(col, row) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn find_path_inner<E, H>(
+        &self,
+        context: &mut NavSearchContext,
+        from: (usize, usize),
+        targets: &[(usize, usize)],
+        edge_cost: E,
+        heuristic: H,
+    ) -> Option<(usize, Scalar)>
+    where
+        E: FnMut(usize, usize, Scalar) -> Option<Scalar>,
+        H: FnMut(usize, usize) -> Scalar,
+    {
+        let start_index = self.index(from.0, from.1)?;
+        if !self.cells[start_index] {
+            return None;
+        }
+        let mut goals = std::mem::take(&mut context.goals);
+        goals.clear();
+        goals.extend(targets.iter().map(|(col, row)| {
+            self.index(*col, *row)
+                .filter(|index| self.cells[*index])
+                .unwrap_or(NO_GOAL)
+        }));
+        let result = search_graph(self, context, start_index, &goals, edge_cost, heuristic);
+        context.goals = goals;
+        result
+    }
+
+    // leaves found path in context and returns its cost.
+    fn find_path_cost_inner<C>(
+        &self,
+        context: &mut NavSearchContext,
+        from: (usize, usize),
+        to: (usize, usize),
+        cost: &C,
+    ) -> Option<Scalar>
+    where
+        C: NavCost<(usize, usize)>,
+    {
+        let model = cost.cost_model().unwrap_or(self.cost_model);
+        let (_, result) = self.find_path_inner(
+            context,
+            from,
+            &[to],
+            |a, b, w| {
+                let (ca, cb) = (self.coord(a).unwrap(), self.coord(b).unwrap());
+                cost.edge_cost(ca, cb, self.connection_cost(model, a, b, w))
+            },
+            |n, goal| {
+                cost.heuristic(self.coord(n).unwrap(), self.coord(goal).unwrap())
+                    .unwrap_or_else(|| self.heuristic(model, n, goal))
+            },
+        )?;
+        Some(result)
+    }
+
+    fn make_path(&self, path: &[usize], cost: Scalar) -> NavPath<(usize, usize)> {
+        let points = path
+            .iter()
+            .filter_map(|n| self.coord(*n))
+            .collect::<Vec<_>>();
+        let length = path_length(&points, |a, b| {
+            cells_distance(a.0.abs_diff(b.0), a.1.abs_diff(b.1))
+        });
+        NavPath::new(points, cost, length)
+    }
+
+    #[inline]
+    fn connection_cost(&self, model: NavCostModel, a: usize, b: usize, weight: Scalar) -> Scalar {
+        model.combine(weight, self.costs[a], self.costs[b])
+    }
+
+    fn heuristic(&self, model: NavCostModel, a: usize, b: usize) -> Scalar {
+        match self.connectivity {
+            Some(connectivity) => {
+                let distance =
+                    connectivity.distance(self.coord(a).unwrap(), self.coord(b).unwrap());
+                distance * model.heuristic_scale(self.min_cost)
+            }
+            None => 0.0,
+        }
+    }
+
+    // successor cells with cost of moving into them under grid cost model.
+    pub(crate) fn successors(&self, index: usize) -> impl Iterator<Item = (usize, Scalar)> + '_ {
+        self.graph.edges(NodeIndex::new(index)).map(move |e| {
+            let to = e.target().index();
+            (
+                to,
+                self.connection_cost(self.cost_model, index, to, *e.weight()),
+            )
+        })
+    }
+
+    pub(crate) fn predecessors(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        self.graph
+            .edges_directed(NodeIndex::new(index), Direction::Incoming)
+            .map(|e| e.source().index())
+    }
+
+    // cells which outgoing connections or their costs might change along with given cell.
+    pub(crate) fn affected_cells(&self, index: usize, result: &mut Vec<usize>) {
+        result.push(index);
+        if self.connectivity.is_some() {
+            let (col, row) = self.coord(index).unwrap();
+            for dr in -1..=1 {
+                for dc in -1..=1 {
+                    if dc == 0 && dr == 0 {
+                        continue;
+                    }
+                    let other = (col as isize + dc, row as isize + dr);
+                    if let Some(other) = self.index_signed(other) {
+                        result.push(other);
+                    }
+                }
+            }
+        } else {
+            for connection in &self.connections {
+                let ia = connection.from.1 * self.cols + connection.from.0;
+                let ib = connection.to.1 * self.cols + connection.to.0;
+                if ib == index {
+                    result.push(ia);
+                } else if ia == index {
+                    result.push(ib);
+                }
+            }
+        }
+    }
+
+    // scale of cells distance making it never overestimate cost under current cell costs.
+    #[inline]
+    pub(crate) fn heuristic_scale(&self) -> Scalar {
+        self.cost_model.heuristic_scale(self.min_cost)
+    }
+
+    fn find_path_jump_point(
+        &self,
+        context: &mut NavSearchContext,
+        connectivity: NavGridConnectivity,
+        from: (usize, usize),
+        to: (usize, usize),
+    ) -> Option<NavPath<(usize, usize)>> {
+        let start_index = self.index(from.0, from.1)?;
+        let end_index = self.index(to.0, to.1)?;
+        if !self.cells[start_index] || !self.cells[end_index] {
+            return None;
+        }
+        let goal = (to.0 as isize, to.1 as isize);
+        // costs are uniform or ignored, so plain distances keep paths optimal.
+        astar_pruned(
+            context,
+            self.cells.len(),
+            start_index,
+            |n| n == end_index,
+            |n, parent| {
+                let (col, row) = self.coord(n).unwrap();
+                let mut successors = [(0, 0.0); 8];
+                let mut count = 0;
+                for (dc, dr) in self.jump_directions(connectivity, n, parent) {
+                    let position = (col as isize + dc, row as isize + dr);
+                    if let Some((c, r)) = self.jump(connectivity, position, (dc, dr), goal) {
+                        let (c, r) = (c as usize, r as usize);
+                        successors[count] =
+                            (r * self.cols + c, connectivity.distance((col, row), (c, r)));
+                        count += 1;
+                    }
+                }
+                successors.into_iter().take(count)
+            },
+            |_, _, w| Some(w),
+            |n| connectivity.distance(self.coord(n).unwrap(), to),
+        )?;
+        let mut result = Vec::with_capacity(context.path.len());
+        result.push(start_index);
+        for pair in context.path.windows(2) {
+            let (mut col, mut row) = self.coord(pair[0]).unwrap();
+            let (to_col, to_row) = self.coord(pair[1]).unwrap();
+            while (col, row) != (to_col, to_row) {
+                col = (col as isize + (to_col as isize - col as isize).signum()) as usize;
+                row = (row as isize + (to_row as isize - row as isize).signum()) as usize;
+                result.push(row * self.cols + col);
+            }
+        }
+        let cost = result
+            .windows(2)
+            .map(|pair| {
+                let (a, b) = (self.coord(pair[0]).unwrap(), self.coord(pair[1]).unwrap());
+                let weight = if a.0 != b.0 && a.1 != b.1 {
+                    SCALAR_SQRT_2
+                } else {
+                    1.0
+                };
+                self.connection_cost(self.cost_model, pair[0], pair[1], weight)
+            })
+            .sum();
+        Some(self.make_path(&result, cost))
+    }
+
+    // directions worth exploring from jump point, pruned by direction of arrival.
+    fn jump_directions(
+        &self,
+        connectivity: NavGridConnectivity,
+        index: usize,
+        parent: usize,
+    ) -> impl Iterator<Item = (isize, isize)> + '_ {
+        let (col, row) = self.coord(index).unwrap();
+        let (x, y) = (col as isize, row as isize);
+        let mut directions = [(0, 0); 8];
+        let mut count = 0;
+        let mut push = |direction: (isize, isize)| {
+            directions[count] = direction;
+            count += 1;
+        };
+        if index == parent {
+            for node in self.graph.neighbors(NodeIndex::new(index)) {
+                let (c, r) = self.coord(node.index()).unwrap();
+                push((c as isize - x, r as isize - y));
+            }
+        } else {
+            let (pc, pr) = self.coord(parent).unwrap();
+            let dx = (x - pc as isize).signum();
+            let dy = (y - pr as isize).signum();
+            let w = |c: isize, r: isize| self.walkable(x + c, y + r);
+            match connectivity {
+                NavGridConnectivity::Four => {
+                    if dx != 0 {
+                        push((0, -1));
+                        push((0, 1));
+                        push((dx, 0));
+                    } else {
+                        push((-1, 0));
+                        push((1, 0));
+                        push((0, dy));
+                    }
+                }
+                NavGridConnectivity::Eight => {
+                    if dx != 0 && dy != 0 {
+                        push((0, dy));
+                        push((dx, 0));
+                        push((dx, dy));
+                        if !w(-dx, 0) {
+                            push((-dx, dy));
+                        }
+                        if !w(0, -dy) {
+                            push((dx, -dy));
+                        }
+                    } else if dx != 0 {
+                        push((dx, 0));
+                        if !w(0, 1) {
+                            push((dx, 1));
+                        }
+                        if !w(0, -1) {
+                            push((dx, -1));
+                        }
+                    } else {
+                        push((0, dy));
+                        if !w(1, 0) {
+                            push((1, dy));
+                        }
+                        if !w(-1, 0) {
+                            push((-1, dy));
+                        }
+                    }
+                }
+                NavGridConnectivity::EightNoCornerCutting => {
+                    if dx != 0 && dy != 0 {
+                        push((0, dy));
+                        push((dx, 0));
+                        if w(0, dy) && w(dx, 0) {
+                            push((dx, dy));
+                        }
+                    } else if dx != 0 {
+                        if w(dx, 0) {
+                            push((dx, 0));
+                            if w(0, 1) {
+                                push((dx, 1));
+                            }
+                            if w(0, -1) {
+                                push((dx, -1));
+                            }
+                        }
+                        push((0, 1));
+                        push((0, -1));
+                    } else {
+                        if w(0, dy) {
+                            push((0, dy));
+                            if w(1, 0) {
+                                push((1, dy));
+                            }
+                            if w(-1, 0) {
+                                push((-1, dy));
+                            }
+                        }
+                        push((1, 0));
+                        push((-1, 0));
+                    }
+                }
+            }
+        }
+        directions.into_iter().take(count)
+    }
+
+    // walks from position in given direction until it finds jump point, goal or obstacle.
+    fn jump(
+        &self,
+        connectivity: NavGridConnectivity,
+        position: (isize, isize),
+        direction: (isize, isize),
+        goal: (isize, isize),
+    ) -> Option<(isize, isize)> {
+        let (mut x, mut y) = position;
+        let (dx, dy) = direction;
+        let w = |x: isize, y: isize| self.walkable(x, y);
+        loop {
+            if !w(x, y) {
+                return None;
+            }
+            if (x, y) == goal {
+                return Some((x, y));
+            }
+            match connectivity {
+                NavGridConnectivity::Four => {
+                    if dx != 0 {
+                        if (w(x, y - 1) && !w(x - dx, y - 1)) || (w(x, y + 1) && !w(x - dx, y + 1))
+                        {
+                            return Some((x, y));
+                        }
+                    } else {
+                        if (w(x - 1, y) && !w(x - 1, y - dy)) || (w(x + 1, y) && !w(x + 1, y - dy))
+                        {
+                            return Some((x, y));
+                        }
+                        // moving vertically has to look for horizontal jump points.
+                        if self.jump(connectivity, (x + 1, y), (1, 0), goal).is_some()
+                            || self.jump(connectivity, (x - 1, y), (-1, 0), goal).is_some()
+                        {
+                            return Some((x, y));
+                        }
+                    }
+                }
+                NavGridConnectivity::Eight => {
+                    if dx != 0 && dy != 0 {
+                        if (w(x - dx, y + dy) && !w(x - dx, y))
+                            || (w(x + dx, y - dy) && !w(x, y - dy))
+                        {
+                            return Some((x, y));
+                        }
+                    } else if dx != 0 {
+                        if (w(x + dx, y + 1) && !w(x, y + 1)) || (w(x + dx, y - 1) && !w(x, y - 1))
+                        {
+                            return Some((x, y));
+                        }
+                    } else if (w(x + 1, y + dy) && !w(x + 1, y))
+                        || (w(x - 1, y + dy) && !w(x - 1, y))
+                    {
+                        return Some((x, y));
+                    }
+                }
+                NavGridConnectivity::EightNoCornerCutting => {
+                    let forced = if dy == 0 {
+                        (w(x, y - 1) && !w(x - dx, y - 1)) || (w(x, y + 1) && !w(x - dx, y + 1))
+                    } else if dx == 0 {
+                        (w(x - 1, y) && !w(x - 1, y - dy)) || (w(x + 1, y) && !w(x + 1, y - dy))
+                    } else {
+                        false
+                    };
+                    if forced {
+                        return Some((x, y));
+                    }
+                }
+            }
+            if dx != 0 && dy != 0 && connectivity != NavGridConnectivity::Four {
+                // moving diagonally has to look for straight jump points.
+                if self
+                    .jump(connectivity, (x + dx, y), (dx, 0), goal)
+                    .is_some()
+                    || self
+                        .jump(connectivity, (x, y + dy), (0, dy), goal)
+                        .is_some()
+                {
+                    return Some((x, y));
+                }
+                if connectivity == NavGridConnectivity::EightNoCornerCutting
+                    && !(w(x + dx, y) && w(x, y + dy))
+                {
+                    return None;
+                }
+            }
+            x += dx;
+            y += dy;
+        }
+    }
+
+    #[inline]
+    fn cell_center((col, row): (usize, usize)) -> (Scalar, Scalar) {
+        (col as Scalar + 0.5, row as Scalar + 0.5)
+    }
+
+    #[inline]
+    fn walkable(&self, col: isize, row: isize) -> bool {
+        col >= 0
+            && row >= 0
+            && (col as usize) < self.cols
+            && (row as usize) < self.rows
+            && self.cells[row as usize * self.cols + col as usize]
+    }
+
+    // connects all walkable cells from scratch.
+    // older versions added graph nodes only for walkable cells, in cells order. their edges
+    // become four-connectivity when they match it, or explicit connections otherwise.
+    fn migrate_legacy_graph(
+        &mut self,
+        legacy: &Graph<(), Scalar, Directed>,
+    ) -> Result<(), &'static str> {
+        let walkable = (0..self.cells.len())
+            .filter(|index| self.cells[*index])
+            .collect::<Vec<_>>();
+        if legacy.node_count() != walkable.len() {
+            return Err("NavGrid legacy graph nodes count does not match walkable cells count");
+        }
+        let edges = legacy
+            .edge_references()
+            .map(|edge| {
+                (
+                    walkable[edge.source().index()],
+                    walkable[edge.target().index()],
+                )
+            })
+            .collect::<Vec<_>>();
+        let four = (0..self.cells.len())
+            .filter(|index| self.cells[*index])
+            .flat_map(|index| {
+                let (col, row) = (index % self.cols, index / self.cols);
+                [(col + 1, row), (col, row + 1)]
+                    .into_iter()
+                    .filter(|(c, r)| *c < self.cols && *r < self.rows)
+                    .map(|(c, r)| r * self.cols + c)
+                    .filter(|other| self.cells[*other])
+            })
+            .count()
+            * 2;
+        let is_four = edges.iter().collect::<HashSet<_>>().len() == four
+            && edges.iter().all(|(a, b)| {
+                let (ca, ra) = (a % self.cols, a / self.cols);
+                let (cb, rb) = (b % self.cols, b / self.cols);
+                ca.abs_diff(cb) + ra.abs_diff(rb) == 1
+            });
+        if is_four {
+            self.connectivity = Some(NavGridConnectivity::Four);
+            self.connections.clear();
+        } else {
+            self.connectivity = None;
+            self.connections = edges
+                .into_iter()
+                .map(|(a, b)| {
+                    NavGridConnection::new(
+                        (a % self.cols, a / self.cols),
+                        (b % self.cols, b / self.cols),
+                    )
+                })
+                .collect();
+        }
+        Ok(())
+    }
+
+    fn rebuild_graph(&mut self) {
+        let count = self.cells.len();
+        let mut graph = Graph::with_capacity(count, count * 4);
+        for _ in 0..count {
+            graph.add_node(());
+        }
+        self.graph = graph;
+        match self.connectivity {
+            Some(connectivity) => {
+                for (dc, dr) in connectivity.offsets() {
+                    for col in 0..self.cols {
+                        for row in 0..self.rows {
+                            let other = (col as isize + dc, row as isize + dr);
+                            if let Some(other) = self.index_signed(other) {
+                                self.connect_cells(row * self.cols + col, other);
+                            }
+                        }
+                    }
+                }
+            }
+            None => {
+                for i in 0..self.connections.len() {
+                    self.add_connection_edges(i);
+                }
+            }
+        }
+    }
+
+    fn connect_cell(&mut self, index: usize) {
+        match self.connectivity {
+            Some(connectivity) => {
+                let (col, row) = self.coord(index).unwrap();
+                for (dc, dr) in connectivity.offsets() {
+                    for (dc, dr) in [(*dc, *dr), (-dc, -dr)] {
+                        let other = (col as isize + dc, row as isize + dr);
+                        if let Some(other) = self.index_signed(other) {
+                            self.connect_cells(index, other);
+                        }
+                    }
+                }
+            }
+            None => {
+                for i in 0..self.connections.len() {
+                    let connection = self.connections[i];
+                    let ia = connection.from.1 * self.cols + connection.from.0;
+                    let ib = connection.to.1 * self.cols + connection.to.0;
+                    if ia == index || ib == index {
+                        self.add_connection_edges(i);
+                    }
+                }
+            }
+        }
+    }
+
+    // adds edges of explicit connection if both its cells are walkable.
+    fn add_connection_edges(&mut self, connection: usize) {
+        let connection = self.connections[connection];
+        let ia = connection.from.1 * self.cols + connection.from.0;
+        let ib = connection.to.1 * self.cols + connection.to.0;
+        if self.cells.get(ia) == Some(&true) && self.cells.get(ib) == Some(&true) {
+            let (na, nb) = (NodeIndex::new(ia), NodeIndex::new(ib));
+            self.graph.add_edge(na, nb, connection.weight());
+            if connection.both_ways {
+                self.graph.add_edge(nb, na, connection.weight());
+            }
+        }
+    }
+
+    fn disconnect_cell(&mut self, index: usize) {
+        let node = NodeIndex::new(index);
+        for direction in [Direction::Outgoing, Direction::Incoming] {
+            while let Some(edge) = self.graph.first_edge(node, direction) {
+                self.graph.remove_edge(edge);
+            }
+        }
+    }
+
+    // connects neighbour cells both ways if connectivity allows it.
+    fn connect_cells(&mut self, a: usize, b: usize) {
+        let connectivity = match self.connectivity {
+            Some(connectivity) => connectivity,
+            None => return,
+        };
+        let (ca, ra) = self.coord(a).unwrap();
+        let (cb, rb) = self.coord(b).unwrap();
+        if self.can_connect(connectivity, (ca, ra), (cb, rb)) {
+            let weight = if ca != cb && ra != rb {
+                SCALAR_SQRT_2
+            } else {
+                1.0
+            };
+            let (na, nb) = (NodeIndex::new(a), NodeIndex::new(b));
+            self.graph.add_edge(na, nb, weight);
+            self.graph.add_edge(nb, na, weight);
+        }
+    }
+
+    fn disconnect_cells(&mut self, a: usize, b: usize) {
+        let (na, nb) = (NodeIndex::new(a), NodeIndex::new(b));
+        while let Some(edge) = self.graph.find_edge(na, nb) {
+            self.graph.remove_edge(edge);
+        }
+        while let Some(edge) = self.graph.find_edge(nb, na) {
+            self.graph.remove_edge(edge);
+        }
+    }
+
+    #[inline]
+    fn index_signed(&self, (col, row): (isize, isize)) -> Option<usize> {
+        if col < 0 || row < 0 {
+            return None;
+        }
+        self.index(col as usize, row as usize)
+    }
+
+    fn can_connect(
+        &self,
+        connectivity: NavGridConnectivity,
+        a: (usize, usize),
+        b: (usize, usize),
+    ) -> bool {
+        let walkable = |(col, row): (usize, usize)| self.cells[row * self.cols + col];
+        if !walkable(a) || !walkable(b) {
+            return false;
+        }
+        if a.0 == b.0 || a.1 == b.1 {
+            return true;
+        }
+        match connectivity {
+            NavGridConnectivity::Four => false,
+            NavGridConnectivity::Eight => true,
+            NavGridConnectivity::EightNoCornerCutting => {
+                walkable((a.0, b.1)) && walkable((b.0, a.1))
+            }
+        }
+    }
+
+    pub fn find_islands(&self) -> Vec<Vec<(usize, usize)>> {
+        tarjan_scc(&self.graph)
+            .into_iter()
+            .map(|v| {
+                v.into_iter()
+                    .filter(|n| self.cells[n.index()])
+                    .filter_map(|n| self.coord(n.index()))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|v| !v.is_empty())
+            .collect()
+    }
+
+    pub fn index(&self, col: usize, row: usize) -> Option<usize> {
+        if col < self.cols && row < self.rows {
+            Some(row * self.cols + col)
+        } else {
+            None
+        }
+    }
+
+    pub fn coord(&self, index: usize) -> Option<(usize, usize)> {
+        let col = index % self.cols;
+        let row = index / self.cols;
+        if col < self.cols && row < self.rows {
+            Some((col, row))
+        } else {
+            None
+        }
+    }
+}
+
+impl Serialize for NavGrid {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Self::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for NavGrid {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut result = Self::deserialize(deserializer)?;
+        if result.cells.len() != result.cols * result.rows
+            || result.costs.len() != result.cells.len()
+        {
+            return Err(D::Error::custom(
+                "NavGrid cells count does not match cols and rows",
+            ));
+        }
+        result.min_cost = result.costs.iter().copied().fold(Scalar::MAX, Scalar::min);
+        result.max_cost = result.costs.iter().copied().fold(0.0, Scalar::max);
+        let legacy = std::mem::take(&mut result.graph);
+        if legacy.node_count() > 0 {
+            result
+                .migrate_legacy_graph(&legacy)
+                .map_err(D::Error::custom)?;
+        }
+        result.rebuild_graph();
+        Ok(result)
+    }
+}
+
+impl NavGraph for NavGrid {
+    fn node_count(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn neighbors(&self, node: usize) -> impl Iterator<Item = (usize, Scalar)> + '_ {
+        self.graph
+            .edges(NodeIndex::new(node))
+            .map(|e| (e.target().index(), *e.weight()))
+    }
+
+    fn edge_cost(&self, from: usize, to: usize, weight: Scalar) -> Option<Scalar> {
+        Some(self.connection_cost(self.cost_model, from, to, weight))
+    }
+
+    fn heuristic(&self, node: usize, goal: usize) -> Scalar {
+        self.heuristic(self.cost_model, node, goal)
+    }
+
+    fn node_position(&self, node: usize) -> Option<NavVec3> {
+        self.coord(node).map(|cell| self.cell_to_world(cell))
+    }
+}
+
+impl Navigable for NavGrid {
+    type Node = (usize, usize);
+    type Point = (usize, usize);
+
+    fn find_path(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+    ) -> Option<NavPath<(usize, usize)>> {
+        self.find_path(from, to)
+    }
+
+    fn find_path_custom<F>(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+        filter: F,
+    ) -> Option<NavPath<(usize, usize)>>
+    where
+        F: FnMut((usize, usize), (usize, usize), Scalar) -> bool,
+    {
+        self.find_path_custom(from, to, filter)
+    }
+
+    fn find_islands(&self) -> Vec<Vec<(usize, usize)>> {
+        self.find_islands()
+    }
+
+    fn neighbors(&self, (col, row): (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.neighbors(col, row).into_iter().flatten()
+    }
+
+    fn node_cost(&self, (col, row): (usize, usize)) -> Option<Scalar> {
+        self.index(col, row).map(|index| self.costs[index])
+    }
+
+    fn set_node_cost(&mut self, (col, row): (usize, usize), cost: Scalar) -> Option<Scalar> {
+        self.set_cell_cost(col, row, cost)
+    }
+
+    fn cost_model(&self) -> NavCostModel {
+        self.cost_model()
+    }
+
+    fn set_cost_model(&mut self, model: NavCostModel) {
+        self.set_cost_model(model);
+    }
+}
+
+/// Connection of `NavFreeGrid` cells. Unlike `NavGridConnection` it goes both ways unless
+/// made one way, as free grid connections always did.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NavFreeGridConnection {
+    pub from: (isize, isize),
+    pub to: (isize, isize),
+    #[serde(default, deserialize_with = "deserialize_connection_cost")]
+    cost: Option<Scalar>,
+    #[serde(default = "NavFreeGridConnection::default_both_ways")]
+    both_ways: bool,
+}
+
+impl Default for NavFreeGridConnection {
+    fn default() -> Self {
+        Self::new(Default::default(), Default::default())
+    }
+}
+
+// cost is never NaN, it gets clamped when set or loaded.
+impl Eq for NavFreeGridConnection {}
+
+impl NavFreeGridConnection {
+    /// Create connection going both ways between `from` and `to`, costing cells distance.
+    pub fn new(from: (isize, isize), to: (isize, isize)) -> Self {
+        Self {
+            from,
+            to,
+            cost: None,
+            both_ways: true,
+        }
+    }
+
+    /// Use given cost of moving through connection in place of cells distance (which is 1).
+    pub fn with_cost(mut self, cost: Scalar) -> Self {
+        self.cost = Some(cost.max(0.0));
+        self
+    }
+
+    /// Connect cells also from `to` to `from`, with the same cost, or only from `from` to `to`.
+    pub fn with_both_ways(mut self, both_ways: bool) -> Self {
+        self.both_ways = both_ways;
+        self
+    }
+
+    #[inline]
+    pub fn cost(&self) -> Option<Scalar> {
+        self.cost
+    }
+
+    #[inline]
+    pub fn both_ways(&self) -> bool {
+        self.both_ways
+    }
+
+    #[inline]
+    fn weight(&self) -> Scalar {
+        self.cost.unwrap_or(1.0).max(0.0)
+    }
+
+    fn default_both_ways() -> bool {
+        true
+    }
+}
+
+/// Nav free grid identifier.
+pub type NavFreeGridID = ID<NavFreeGrid>;
+
+// serde impls are written by hand below to rebuild cells map after loading.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(remote = "Self")]
+pub struct NavFreeGrid {
+    id: NavFreeGridID,
+    cells: Vec<(isize, isize)>,
+    // {cell coord: cell index}
+    #[serde(skip)]
+    cells_map: HashMap<(isize, isize), usize>,
+    costs: Vec<Scalar>,
+    #[serde(default)]
+    cost_model: NavCostModel,
+    #[serde(default)]
+    transform: Option<NavGridTransform>,
+    // graph node indices are the same as cells indices. it is stored as list of edges and
+    // rebuilt from them after loading.
+    #[serde(
+        serialize_with = "serialize_free_graph",
+        deserialize_with = "deserialize_free_graph"
+    )]
+    graph: Graph<(), Scalar, Directed>,
+}
+
+impl NavFreeGrid {
+    pub fn new(connections: Vec<NavFreeGridConnection>) -> Self {
+        let mut result = Self {
+            id: NavFreeGridID::new(),
+            cells: Vec::with_capacity(connections.len()),
+            cells_map: HashMap::with_capacity(connections.len()),
+            costs: Vec::with_capacity(connections.len()),
+            cost_model: NavCostModel::default(),
+            transform: None,
+            graph: Graph::with_capacity(connections.len(), connections.len()),
+        };
+        for connection in connections {
+            result.add_connection(connection);
+        }
+        result
+    }
+
+    /// Create grid of given cells, connecting ones that are neighbours under given connectivity.
+    pub fn from_cells<I>(cells: I, connectivity: NavGridConnectivity) -> Self
+    where
+        I: IntoIterator<Item = (isize, isize)>,
+    {
+        let mut result = Self::new(vec![]);
+        for (col, row) in cells {
+            result.add_cell(col, row);
+        }
+        for index in 0..result.cells.len() {
+            let (ca, ra) = result.cells[index];
+            for (dc, dr) in connectivity.offsets() {
+                let (cb, rb) = (ca + dc, ra + dr);
+                let other = match result.index(cb, rb) {
+                    Some(other) => other,
+                    None => continue,
+                };
+                if connectivity == NavGridConnectivity::EightNoCornerCutting
+                    && ca != cb
+                    && ra != rb
+                    && (result.index(cb, ra).is_none() || result.index(ca, rb).is_none())
+                {
+                    continue;
+                }
+                let weight = if ca != cb && ra != rb {
+                    SCALAR_SQRT_2
+                } else {
+                    1.0
+                };
+                let (na, nb) = (NodeIndex::new(index), NodeIndex::new(other));
+                result.graph.add_edge(na, nb, weight);
+                result.graph.add_edge(nb, na, weight);
+            }
+        }
+        result
+    }
+
+    /// Create grid with four-connectivity from text map, mostly for test fixtures.
+    ///
+    /// Every line is a row of cells starting at row 0, lines are trimmed and empty ones skipped.
+    /// `#` marks blocked cell, any other character walkable one.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let grid = NavFreeGrid::from_ascii_map(
+    ///     "
+    ///     ...
+    ///     #.#
+    ///     ...
+    ///     ",
+    /// );
+    /// assert_eq!(grid.cells().len(), 7);
+    /// assert_eq!(grid.find_path((0, 0), (0, 2)).unwrap().points.len(), 5);
+    /// ```
+    pub fn from_ascii_map(map: &str) -> Self {
+        let cells = map
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .enumerate()
+            .flat_map(|(row, line)| {
+                line.chars()
+                    .enumerate()
+                    .filter(|(_, c)| *c != '#')
+                    .map(move |(col, _)| (col as isize, row as isize))
+            });
+        Self::from_cells(cells, NavGridConnectivity::Four)
+    }
+
+    #[inline]
+    pub fn id(&self) -> NavFreeGridID {
+        self.id
+    }
+
+    #[inline]
+    pub fn cells(&self) -> &[(isize, isize)] {
+        &self.cells
+    }
+
+    #[inline]
+    pub fn cells_costs(&self) -> &[Scalar] {
+        &self.costs
+    }
+
+    #[inline]
+    pub fn set_cell_cost(&mut self, col: isize, row: isize, cost: Scalar) -> Option<Scalar> {
+        let index = self.index(col, row)?;
+        let c = self.costs.get_mut(index)?;
+        let old = *c;
+        *c = cost.max(0.0);
+        Some(old)
+    }
+
+    /// Add cell without connections. Returns index of cell, either new or already existing one.
+    pub fn add_cell(&mut self, col: isize, row: isize) -> usize {
+        if let Some(index) = self.cells_map.get(&(col, row)) {
+            return *index;
+        }
+        let index = self.graph.add_node(()).index();
+        self.cells.push((col, row));
+        self.cells_map.insert((col, row), index);
+        self.costs.push(1.0);
+        index
+    }
+
+    /// Remove cell with all its connections. Returns `false` if there was no such cell.
+    ///
+    /// Last cell takes index of removed one.
+    pub fn remove_cell(&mut self, col: isize, row: isize) -> bool {
+        let index = match self.cells_map.remove(&(col, row)) {
+            Some(index) => index,
+            None => return false,
+        };
+        // graph moves its last node into removed node slot, so cells have to follow.
+        self.graph.remove_node(NodeIndex::new(index));
+        self.cells.swap_remove(index);
+        self.costs.swap_remove(index);
+        if let Some(moved) = self.cells.get(index) {
+            self.cells_map.insert(*moved, index);
+        }
+        true
+    }
+
+    /// Connect two cells, adding missing ones. Cost of already existing connection gets
+    /// replaced. Returns `false` if cells were already connected.
+    pub fn add_connection(&mut self, connection: NavFreeGridConnection) -> bool {
+        let a = NodeIndex::new(self.add_cell(connection.from.0, connection.from.1));
+        let b = NodeIndex::new(self.add_cell(connection.to.0, connection.to.1));
+        let result = self.graph.find_edge(a, b).is_none();
+        self.graph.update_edge(a, b, connection.weight());
+        if connection.both_ways {
+            self.graph.update_edge(b, a, connection.weight());
+        }
+        result
+    }
+
+    /// Disconnect two cells (also from `to` to `from` if connection goes both ways), keeping
+    /// cells in grid. Returns `false` if cells were not connected.
+    pub fn remove_connection(&mut self, connection: NavFreeGridConnection) -> bool {
+        let (a, b) = match (
+            self.index(connection.from.0, connection.from.1),
+            self.index(connection.to.0, connection.to.1),
+        ) {
+            (Some(a), Some(b)) => (NodeIndex::new(a), NodeIndex::new(b)),
+            _ => return false,
+        };
+        let mut result = false;
+        if let Some(edge) = self.graph.find_edge(a, b) {
+            self.graph.remove_edge(edge);
+            result = true;
+        }
+        if connection.both_ways {
+            if let Some(edge) = self.graph.find_edge(b, a) {
+                self.graph.remove_edge(edge);
+                result = true;
+            }
+        }
+        result
+    }
+
+    #[inline]
+    pub fn cost_model(&self) -> NavCostModel {
+        self.cost_model
+    }
+
+    #[inline]
+    pub fn set_cost_model(&mut self, model: NavCostModel) {
+        self.cost_model = model;
+    }
+
+    #[inline]
+    pub fn transform(&self) -> Option<NavGridTransform> {
+        self.transform
+    }
+
+    /// Set world placement of grid.
+    ///
+    /// # Returns
+    /// `Ok` or `Err` with `Error::InvalidCellSize` if transform cell size is not positive.
+    pub fn set_transform(&mut self, transform: Option<NavGridTransform>) -> NavResult<()> {
+        if let Some(transform) = &transform {
+            transform.validate()?;
+        }
+        self.transform = transform;
+        Ok(())
+    }
+
+    /// World position of given cell center. Grid without transform uses default one.
+    pub fn cell_to_world(&self, cell: (isize, isize)) -> NavVec3 {
+        self.transform.unwrap_or_default().cell_to_world(cell)
+    }
+
+    /// Cell containing given world position or `None` if there is no such cell in grid.
+    pub fn world_to_cell(&self, point: NavVec3) -> Option<(isize, isize)> {
+        let (col, row) = self.transform.unwrap_or_default().world_to_cell(point);
+        self.index(col, row)?;
+        Some((col, row))
+    }
+
+    pub fn neighbors(
+        &self,
+        col: isize,
+        row: isize,
+    ) -> Option<impl Iterator<Item = (isize, isize)> + '_> {
+        let index = self.index(col, row)?;
+        Some(
+            self.graph
+                .neighbors(NodeIndex::new(index))
+                .filter_map(|node| self.coord(node.index())),
+        )
+    }
+
+    pub fn find_path(
+        &self,
+        from: (isize, isize),
+        to: (isize, isize),
+    ) -> Option<NavPath<(isize, isize)>> {
+        self.find_path_custom(from, to, |_, _, _| true)
+    }
+
+    pub fn find_path_world(&self, from: NavVec3, to: NavVec3) -> Option<NavPath<NavVec3>> {
+        let from = self.world_to_cell(from)?;
+        let to = self.world_to_cell(to)?;
+        let path = self.find_path(from, to)?;
+        let points = path
+            .points
+            .into_iter()
+            .map(|cell| self.cell_to_world(cell))
+            .collect();
+        Some(NavPath::from_points(points, path.cost))
+    }
+
+    // filter params: first col-row, second col-row, connection cost.
+    // connections rejected by filter cannot be traversed.
+    pub fn find_path_custom<F>(
+        &self,
+        from: (isize, isize),
+        to: (isize, isize),
+        mut filter: F,
+    ) -> Option<NavPath<(isize, isize)>>
+    where
+        F: FnMut((isize, isize), (isize, isize), Scalar) -> bool,
+    {
+        let mut context = NavSearchContext::default();
+        let cost = self.find_path_inner(
+            &mut context,
+            from,
+            to,
+            |a, b, w| {
+                let cost = self.connection_cost(self.cost_model, a, b, w);
+                if filter(self.coord(a).unwrap(), self.coord(b).unwrap(), cost) {
+                    Some(cost)
+                } else {
+                    None
+                }
+            },
+            |_, _| 0.0,
+        )?;
+        Some(self.make_path(&context.path, cost))
+    }
+
+    // cost params: first col-row, second col-row.
+    pub fn find_path_with_cost<C>(
+        &self,
+        from: (isize, isize),
+        to: (isize, isize),
+        cost: &C,
+    ) -> Option<NavPath<(isize, isize)>>
+    where
+        C: NavCost<(isize, isize)>,
+    {
+        let mut context = NavSearchContext::default();
+        let cost = self.find_path_cost_inner(&mut context, from, to, cost)?;
+        Some(self.make_path(&context.path, cost))
+    }
+
+    // cost params: first col-row, second col-row.
+    pub fn find_path_with_context<'a, C>(
+        &'a self,
+        context: &'a mut NavSearchContext,
+        from: (isize, isize),
+        to: (isize, isize),
+        cost: &C,
+    ) -> Option<impl Iterator<Item = (isize, isize)> + 'a>
+    where
+        C: NavCost<(isize, isize)>,
+    {
+        self.find_path_cost_inner(context, from, to, cost)?;
+        Some(context.path.iter().filter_map(move |n| self.coord(*n)))
+    }
+
+    fn find_path_inner<E, H>(
+        &self,
+        context: &mut NavSearchContext,
+        from: (isize, isize),
+        to: (isize, isize),
+        edge_cost: E,
+        heuristic: H,
+    ) -> Option<Scalar>
+    where
+        E: FnMut(usize, usize, Scalar) -> Option<Scalar>,
+        H: FnMut(usize, usize) -> Scalar,
+    {
+        let start_index = self.index(from.0, from.1)?;
+        let end_index = self.index(to.0, to.1)?;
+        let (_, cost) = search_graph(
+            self,
+            context,
+            start_index,
+            &[end_index],
+            edge_cost,
+            heuristic,
+        )?;
+        Some(cost)
+    }
+
+    // leaves found path in context and returns its cost.
+    fn find_path_cost_inner<C>(
+        &self,
+        context: &mut NavSearchContext,
+        from: (isize, isize),
+        to: (isize, isize),
+        cost: &C,
+    ) -> Option<Scalar>
+    where
+        C: NavCost<(isize, isize)>,
+    {
+        let model = cost.cost_model().unwrap_or(self.cost_model);
+        self.find_path_inner(
+            context,
+            from,
+            to,
+            |a, b, w| {
+                let (ca, cb) = (self.coord(a).unwrap(), self.coord(b).unwrap());
+                cost.edge_cost(ca, cb, self.connection_cost(model, a, b, w))
+            },
+            |n, goal| {
+                cost.heuristic(self.coord(n).unwrap(), self.coord(goal).unwrap())
+                    .unwrap_or(0.0)
+            },
+        )
+    }
+
+    fn make_path(&self, path: &[usize], cost: Scalar) -> NavPath<(isize, isize)> {
+        let points = path
+            .iter()
+            .filter_map(|n| self.coord(*n))
+            .collect::<Vec<_>>();
+        let length = path_length(&points, |a, b| {
+            cells_distance(a.0.abs_diff(b.0), a.1.abs_diff(b.1))
+        });
+        NavPath::new(points, cost, length)
+    }
+
+    #[inline]
+    fn connection_cost(&self, model: NavCostModel, a: usize, b: usize, weight: Scalar) -> Scalar {
+        model.combine(weight, self.costs[a], self.costs[b])
+    }
+
+    pub fn find_islands(&self) -> Vec<Vec<(isize, isize)>> {
+        tarjan_scc(&self.graph)
+            .into_iter()
+            .map(|v| {
+                v.into_iter()
+                    .filter_map(|n| self.coord(n.index()))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|v| !v.is_empty())
+            .collect()
+    }
 
     pub fn index(&self, col: isize, row: isize) -> Option<usize> {
-        let coord = (col, row);
-        self.cells.iter().position(|c| coord == *c)
+        self.cells_map.get(&(col, row)).copied()
     }
 
     pub fn coord(&self, index: usize) -> Option<(isize, isize)> {
         self.cells.get(index).copied()
     }
 }
+
+impl Serialize for NavFreeGrid {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Self::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for NavFreeGrid {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut result = Self::deserialize(deserializer)?;
+        while result.graph.node_count() < result.cells.len() {
+            result.graph.add_node(());
+        }
+        if result.costs.len() != result.cells.len()
+            || result.graph.node_count() != result.cells.len()
+        {
+            return Err(D::Error::custom(
+                "NavFreeGrid cells count does not match costs and graph",
+            ));
+        }
+        result.cells_map = result
+            .cells
+            .iter()
+            .enumerate()
+            .map(|(index, cell)| (*cell, index))
+            .collect();
+        Ok(result)
+    }
+}
+
+impl NavGraph for NavFreeGrid {
+    fn node_count(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn neighbors(&self, node: usize) -> impl Iterator<Item = (usize, Scalar)> + '_ {
+        self.graph
+            .edges(NodeIndex::new(node))
+            .map(|e| (e.target().index(), *e.weight()))
+    }
+
+    fn edge_cost(&self, from: usize, to: usize, weight: Scalar) -> Option<Scalar> {
+        Some(self.connection_cost(self.cost_model, from, to, weight))
+    }
+
+    fn node_position(&self, node: usize) -> Option<NavVec3> {
+        self.coord(node).map(|cell| self.cell_to_world(cell))
+    }
+}
+
+impl Navigable for NavFreeGrid {
+    type Node = (isize, isize);
+    type Point = (isize, isize);
+
+    fn find_path(
+        &self,
+        from: (isize, isize),
+        to: (isize, isize),
+    ) -> Option<NavPath<(isize, isize)>> {
+        self.find_path(from, to)
+    }
+
+    fn find_path_custom<F>(
+        &self,
+        from: (isize, isize),
+        to: (isize, isize),
+        filter: F,
+    ) -> Option<NavPath<(isize, isize)>>
+    where
+        F: FnMut((isize, isize), (isize, isize), Scalar) -> bool,
+    {
+        self.find_path_custom(from, to, filter)
+    }
+
+    fn find_islands(&self) -> Vec<Vec<(isize, isize)>> {
+        self.find_islands()
+    }
+
+    fn neighbors(&self, (col, row): (isize, isize)) -> impl Iterator<Item = (isize, isize)> + '_ {
+        self.neighbors(col, row).into_iter().flatten()
+    }
+
+    fn node_cost(&self, (col, row): (isize, isize)) -> Option<Scalar> {
+        self.index(col, row).map(|index| self.costs[index])
+    }
+
+    fn set_node_cost(&mut self, (col, row): (isize, isize), cost: Scalar) -> Option<Scalar> {
+        self.set_cell_cost(col, row, cost)
+    }
+
+    fn cost_model(&self) -> NavCostModel {
+        self.cost_model()
+    }
+
+    fn set_cost_model(&mut self, model: NavCostModel) {
+        self.set_cost_model(model);
+    }
+}
+
+// free grid graph is stored as list of (from, to, weight) edges.
+fn serialize_free_graph<S>(
+    graph: &Graph<(), Scalar, Directed>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.collect_seq(
+        graph
+            .edge_references()
+            .map(|edge| (edge.source().index(), edge.target().index(), *edge.weight())),
+    )
+}
+
+// graph gets nodes up to the last one used by edges, the rest is added after loading. human
+// readable formats can still load undirected graphs saved by older versions, where every edge
+// connects cells both ways with default weight.
+fn deserialize_free_graph<'de, D>(deserializer: D) -> Result<Graph<(), Scalar, Directed>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct EdgesVisitor;
+
+    impl<'de> Visitor<'de> for EdgesVisitor {
+        type Value = Vec<(usize, usize, Scalar)>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("list of graph edges")
+        }
+
+        fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            Vec::deserialize(SeqAccessDeserializer::new(seq))
+        }
+
+        fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            let graph = Graph::<(), (), Undirected>::deserialize(MapAccessDeserializer::new(map))?;
+            Ok(graph
+                .edge_references()
+                .flat_map(|edge| {
+                    let (a, b) = (edge.source().index(), edge.target().index());
+                    [(a, b, 1.0), (b, a, 1.0)]
+                })
+                .collect())
+        }
+    }
+
+    let edges = if deserializer.is_human_readable() {
+        deserializer.deserialize_any(EdgesVisitor)?
+    } else {
+        deserializer.deserialize_seq(EdgesVisitor)?
+    };
+    let count = edges
+        .iter()
+        .map(|(a, b, _)| a.max(b) + 1)
+        .max()
+        .unwrap_or(0);
+    if count > u32::MAX as usize {
+        return Err(D::Error::custom(
+            "NavFreeGrid graph edge index out of range",
+        ));
+    }
+    let mut graph = Graph::with_capacity(count, edges.len());
+    for _ in 0..count {
+        graph.add_node(());
+    }
+    for (a, b, weight) in edges {
+        graph.add_edge(NodeIndex::new(a), NodeIndex::new(b), weight.max(0.0));
+    }
+    Ok(graph)
+}
@@ -0,0 +1,542 @@
+use crate::{
+    nav_graph::search_graph, nav_path::path_length, nav_search::flood_islands, NavCost,
+    NavCostModel, NavGraph, NavGridTransform, NavPath, NavResult, NavSearchContext, NavVec3,
+    Navigable, Scalar,
+};
+use serde::{Deserialize, Serialize};
+use typid::ID;
+
+/// Axial offsets of six hex neighbours.
+const DIRECTIONS: [(isize, isize); 6] = [(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)];
+
+/// Nav hex grid identifier.
+pub type NavHexGridID = ID<NavHexGrid>;
+
+/// Placement of hex corners, which decides how axial coordinates map onto world space.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NavHexOrientation {
+    /// Corner at the top, rows of hexes go along X axis.
+    #[default]
+    Pointy,
+    /// Flat edge at the top, columns of hexes go along Y axis.
+    Flat,
+}
+
+/// Hexagonal grid with cells identified by axial coordinates `(q, r)`.
+///
+/// Cube coordinates of cell are `(q, r, -q - r)`. Every cell is connected with its six
+/// neighbours and cells can be made non-walkable or get costs at any time.
+///
+/// # Example
+/// ```
+/// use navmesh::*;
+///
+/// let mut grid = NavHexGrid::hexagon(2, NavHexOrientation::Pointy);
+/// assert_eq!(grid.cells().len(), 19);
+/// grid.set_cell_walkable((0, 0), false);
+/// let path = grid.find_path((-1, 0), (1, 0)).unwrap().points;
+/// assert_eq!(path.len(), 4);
+/// assert_eq!(grid.ring((0, 0), 1).len(), 6);
+/// assert!(!grid.line_of_sight((-1, 0), (1, 0)));
+/// ```
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct NavHexGrid {
+    id: NavHexGridID,
+    orientation: NavHexOrientation,
+    // sorted, so cell index is found with binary search.
+    cells: Vec<(isize, isize)>,
+    walkable: Vec<bool>,
+    costs: Vec<Scalar>,
+    #[serde(default)]
+    cost_model: NavCostModel,
+    // lowest cell cost, used to scale heuristic.
+    min_cost: Scalar,
+    #[serde(default)]
+    transform: Option<NavGridTransform>,
+}
+
+impl NavHexGrid {
+    /// Create grid of given walkable cells (axial coordinates). Duplicates are ignored.
+    pub fn new(mut cells: Vec<(isize, isize)>, orientation: NavHexOrientation) -> Self {
+        cells.sort_unstable();
+        cells.dedup();
+        let count = cells.len();
+        Self {
+            id: NavHexGridID::new(),
+            orientation,
+            cells,
+            walkable: vec![true; count],
+            costs: vec![1.0; count],
+            cost_model: NavCostModel::default(),
+            min_cost: 1.0,
+            transform: None,
+        }
+    }
+
+    /// Create hexagon shaped grid of cells within `radius` from `(0, 0)`.
+    pub fn hexagon(radius: usize, orientation: NavHexOrientation) -> Self {
+        Self::new(Self::range_cells((0, 0), radius).collect(), orientation)
+    }
+
+    #[inline]
+    pub fn id(&self) -> NavHexGridID {
+        self.id
+    }
+
+    #[inline]
+    pub fn orientation(&self) -> NavHexOrientation {
+        self.orientation
+    }
+
+    #[inline]
+    pub fn cells(&self) -> &[(isize, isize)] {
+        &self.cells
+    }
+
+    #[inline]
+    pub fn cells_walkable(&self) -> &[bool] {
+        &self.walkable
+    }
+
+    #[inline]
+    pub fn cells_costs(&self) -> &[Scalar] {
+        &self.costs
+    }
+
+    pub fn is_cell_walkable(&self, cell: (isize, isize)) -> Option<bool> {
+        Some(self.walkable[self.index(cell)?])
+    }
+
+    pub fn set_cell_walkable(&mut self, cell: (isize, isize), walkable: bool) -> Option<bool> {
+        let index = self.index(cell)?;
+        let old = self.walkable[index];
+        self.walkable[index] = walkable;
+        Some(old)
+    }
+
+    pub fn set_cell_cost(&mut self, cell: (isize, isize), cost: Scalar) -> Option<Scalar> {
+        let index = self.index(cell)?;
+        let old = self.costs[index];
+        let cost = cost.max(0.0);
+        self.costs[index] = cost;
+        if cost < self.min_cost {
+            self.min_cost = cost;
+        } else if old <= self.min_cost {
+            self.min_cost = self.costs.iter().copied().fold(Scalar::MAX, Scalar::min);
+        }
+        Some(old)
+    }
+
+    #[inline]
+    pub fn cost_model(&self) -> NavCostModel {
+        self.cost_model
+    }
+
+    #[inline]
+    pub fn set_cost_model(&mut self, model: NavCostModel) {
+        self.cost_model = model;
+    }
+
+    #[inline]
+    pub fn transform(&self) -> Option<NavGridTransform> {
+        self.transform
+    }
+
+    /// Set world placement of grid, where `cell_size` is distance from hex center to its corner.
+    ///
+    /// # Returns
+    /// `Ok` or `Err` with `Error::InvalidCellSize` if transform cell size is not positive.
+    pub fn set_transform(&mut self, transform: Option<NavGridTransform>) -> NavResult<()> {
+        if let Some(transform) = &transform {
+            transform.validate()?;
+        }
+        self.transform = transform;
+        Ok(())
+    }
+
+    /// World position of given cell center. Grid without transform uses default one.
+    pub fn cell_to_world(&self, (q, r): (isize, isize)) -> NavVec3 {
+        let (q, r) = (q as Scalar, r as Scalar);
+        let sqrt_3 = (3.0 as Scalar).sqrt();
+        let (x, y) = match self.orientation {
+            NavHexOrientation::Pointy => (sqrt_3 * q + sqrt_3 * 0.5 * r, 1.5 * r),
+            NavHexOrientation::Flat => (1.5 * q, sqrt_3 * 0.5 * q + sqrt_3 * r),
+        };
+        self.transform.unwrap_or_default().local_to_world(x, y)
+    }
+
+    /// Cell containing given world position or `None` if there is no such cell in grid.
+    pub fn world_to_cell(&self, point: NavVec3) -> Option<(isize, isize)> {
+        let (x, y) = self.transform.unwrap_or_default().world_to_local(point);
+        let sqrt_3 = (3.0 as Scalar).sqrt();
+        let (q, r) = match self.orientation {
+            NavHexOrientation::Pointy => (sqrt_3 / 3.0 * x - y / 3.0, 2.0 / 3.0 * y),
+            NavHexOrientation::Flat => (2.0 / 3.0 * x, -x / 3.0 + sqrt_3 / 3.0 * y),
+        };
+        let cell = Self::round(q, r, -q - r);
+        self.index(cell)?;
+        Some(cell)
+    }
+
+    #[inline]
+    pub fn axial_to_cube((q, r): (isize, isize)) -> (isize, isize, isize) {
+        (q, r, -q - r)
+    }
+
+    #[inline]
+    pub fn cube_to_axial((x, y, _): (isize, isize, isize)) -> (isize, isize) {
+        (x, y)
+    }
+
+    /// Number of steps between two cells.
+    pub fn distance(from: (isize, isize), to: (isize, isize)) -> usize {
+        let dq = from.0 - to.0;
+        let dr = from.1 - to.1;
+        (dq.unsigned_abs() + dr.unsigned_abs() + (dq + dr).unsigned_abs()) / 2
+    }
+
+    /// Cells on straight line between two cells, including both, regardless of grid contents.
+    pub fn line(from: (isize, isize), to: (isize, isize)) -> Vec<(isize, isize)> {
+        let steps = Self::distance(from, to);
+        if steps == 0 {
+            return vec![from];
+        }
+        // nudge keeps line going along hexes edges from rounding ambiguously.
+        let (aq, ar) = (from.0 as Scalar + 1e-3, from.1 as Scalar + 2e-3);
+        let (bq, br) = (to.0 as Scalar + 1e-3, to.1 as Scalar + 2e-3);
+        (0..=steps)
+            .map(|step| {
+                let t = step as Scalar / steps as Scalar;
+                let q = aq + (bq - aq) * t;
+                let r = ar + (br - ar) * t;
+                Self::round(q, r, -q - r)
+            })
+            .collect()
+    }
+
+    /// Cells of grid within `radius` steps from center, including center.
+    pub fn range(&self, center: (isize, isize), radius: usize) -> Vec<(isize, isize)> {
+        Self::range_cells(center, radius)
+            .filter(|cell| self.index(*cell).is_some())
+            .collect()
+    }
+
+    /// Cells of grid exactly `radius` steps away from center.
+    pub fn ring(&self, center: (isize, isize), radius: usize) -> Vec<(isize, isize)> {
+        if radius == 0 {
+            return self.range(center, 0);
+        }
+        let radius = radius as isize;
+        let (dq, dr) = DIRECTIONS[4];
+        let mut cell = (center.0 + dq * radius, center.1 + dr * radius);
+        let mut result = Vec::with_capacity(6 * radius as usize);
+        for (dq, dr) in DIRECTIONS {
+            for _ in 0..radius {
+                if self.index(cell).is_some() {
+                    result.push(cell);
+                }
+                cell = (cell.0 + dq, cell.1 + dr);
+            }
+        }
+        result
+    }
+
+    /// Tells if every cell on line between two cells is walkable.
+    pub fn line_of_sight(&self, from: (isize, isize), to: (isize, isize)) -> bool {
+        Self::line(from, to)
+            .into_iter()
+            .all(|cell| self.is_cell_walkable(cell).unwrap_or(false))
+    }
+
+    pub fn neighbors(
+        &self,
+        cell: (isize, isize),
+    ) -> Option<impl Iterator<Item = (isize, isize)> + '_> {
+        let index = self.index(cell)?;
+        if !self.walkable[index] {
+            return None;
+        }
+        Some(
+            self.neighbor_indices(index)
+                .map(move |index| self.cells[index]),
+        )
+    }
+
+    pub fn find_path(
+        &self,
+        from: (isize, isize),
+        to: (isize, isize),
+    ) -> Option<NavPath<(isize, isize)>> {
+        self.find_path_custom(from, to, |_, _, _| true)
+    }
+
+    pub fn find_path_world(&self, from: NavVec3, to: NavVec3) -> Option<NavPath<NavVec3>> {
+        let from = self.world_to_cell(from)?;
+        let to = self.world_to_cell(to)?;
+        let path = self.find_path(from, to)?;
+        let points = path
+            .points
+            .into_iter()
+            .map(|cell| self.cell_to_world(cell))
+            .collect();
+        Some(NavPath::from_points(points, path.cost))
+    }
+
+    // filter params: first cell, second cell, connection cost.
+    // connections rejected by filter cannot be traversed.
+    pub fn find_path_custom<F>(
+        &self,
+        from: (isize, isize),
+        to: (isize, isize),
+        mut filter: F,
+    ) -> Option<NavPath<(isize, isize)>>
+    where
+        F: FnMut((isize, isize), (isize, isize), Scalar) -> bool,
+    {
+        let mut context = NavSearchContext::default();
+        let cost = self.find_path_inner(
+            &mut context,
+            from,
+            to,
+            |a, b, w| {
+                let cost = self.connection_cost(self.cost_model, a, b, w);
+                if filter(self.cells[a], self.cells[b], cost) {
+                    Some(cost)
+                } else {
+                    None
+                }
+            },
+            |n, goal| self.heuristic(self.cost_model, n, goal),
+        )?;
+        Some(self.make_path(&context.path, cost))
+    }
+
+    // cost params: first cell, second cell.
+    pub fn find_path_with_cost<C>(
+        &self,
+        from: (isize, isize),
+        to: (isize, isize),
+        cost: &C,
+    ) -> Option<NavPath<(isize, isize)>>
+    where
+        C: NavCost<(isize, isize)>,
+    {
+        let mut context = NavSearchContext::default();
+        let cost = self.find_path_cost_inner(&mut context, from, to, cost)?;
+        Some(self.make_path(&context.path, cost))
+    }
+
+    // cost params: first cell, second cell.
+    pub fn find_path_with_context<'a, C>(
+        &'a self,
+        context: &'a mut NavSearchContext,
+        from: (isize, isize),
+        to: (isize, isize),
+        cost: &C,
+    ) -> Option<impl Iterator<Item = (isize, isize)> + 'a>
+    where
+        C: NavCost<(isize, isize)>,
+    {
+        self.find_path_cost_inner(context, from, to, cost)?;
+        Some(context.path.iter().map(move |n| self.cells[*n]))
+    }
+
+    // leaves found path in context and returns its cost.
+    fn find_path_cost_inner<C>(
+        &self,
+        context: &mut NavSearchContext,
+        from: (isize, isize),
+        to: (isize, isize),
+        cost: &C,
+    ) -> Option<Scalar>
+    where
+        C: NavCost<(isize, isize)>,
+    {
+        let model = cost.cost_model().unwrap_or(self.cost_model);
+        self.find_path_inner(
+            context,
+            from,
+            to,
+            |a, b, w| {
+                let base = self.connection_cost(model, a, b, w);
+                cost.edge_cost(self.cells[a], self.cells[b], base)
+            },
+            |n, goal| {
+                cost.heuristic(self.cells[n], self.cells[goal])
+                    .unwrap_or_else(|| self.heuristic(model, n, goal))
+            },
+        )
+    }
+
+    fn make_path(&self, path: &[usize], cost: Scalar) -> NavPath<(isize, isize)> {
+        let points = path.iter().map(|n| self.cells[*n]).collect::<Vec<_>>();
+        let length = path_length(&points, |a, b| Self::distance(*a, *b) as Scalar);
+        NavPath::new(points, cost, length)
+    }
+
+    pub fn find_islands(&self) -> Vec<Vec<(isize, isize)>> {
+        flood_islands(
+            self.cells.len(),
+            |n| self.walkable[n],
+            |n| self.neighbor_indices(n),
+        )
+        .into_iter()
+        .map(|island| island.into_iter().map(|n| self.cells[n]).collect())
+        .collect()
+    }
+
+    pub fn index(&self, cell: (isize, isize)) -> Option<usize> {
+        self.cells.binary_search(&cell).ok()
+    }
+
+    #[inline]
+    pub fn coord(&self, index: usize) -> Option<(isize, isize)> {
+        self.cells.get(index).copied()
+    }
+
+    fn find_path_inner<E, H>(
+        &self,
+        context: &mut NavSearchContext,
+        from: (isize, isize),
+        to: (isize, isize),
+        edge_cost: E,
+        heuristic: H,
+    ) -> Option<Scalar>
+    where
+        E: FnMut(usize, usize, Scalar) -> Option<Scalar>,
+        H: FnMut(usize, usize) -> Scalar,
+    {
+        let start_index = self.index(from)?;
+        let end_index = self.index(to)?;
+        if !self.walkable[start_index] || !self.walkable[end_index] {
+            return None;
+        }
+        let (_, cost) = search_graph(
+            self,
+            context,
+            start_index,
+            &[end_index],
+            edge_cost,
+            heuristic,
+        )?;
+        Some(cost)
+    }
+
+    // walkable neighbours of cell.
+    fn neighbor_indices(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        let (q, r) = self.cells[index];
+        DIRECTIONS.iter().filter_map(move |(dq, dr)| {
+            let index = self.index((q + dq, r + dr))?;
+            if self.walkable[index] {
+                Some(index)
+            } else {
+                None
+            }
+        })
+    }
+
+    #[inline]
+    fn connection_cost(&self, model: NavCostModel, a: usize, b: usize, weight: Scalar) -> Scalar {
+        model.combine(weight, self.costs[a], self.costs[b])
+    }
+
+    fn heuristic(&self, model: NavCostModel, a: usize, b: usize) -> Scalar {
+        Self::distance(self.cells[a], self.cells[b]) as Scalar
+            * model.heuristic_scale(self.min_cost)
+    }
+
+    fn range_cells((q, r): (isize, isize), radius: usize) -> impl Iterator<Item = (isize, isize)> {
+        let radius = radius as isize;
+        (-radius..=radius).flat_map(move |dq| {
+            let from = (-radius).max(-dq - radius);
+            let to = radius.min(-dq + radius);
+            (from..=to).map(move |dr| (q + dq, r + dr))
+        })
+    }
+
+    fn round(q: Scalar, r: Scalar, s: Scalar) -> (isize, isize) {
+        let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+        let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+        if dq > dr && dq > ds {
+            rq = -rr - rs;
+        } else if dr > ds {
+            rr = -rq - rs;
+        }
+        (rq as isize, rr as isize)
+    }
+}
+
+impl Navigable for NavHexGrid {
+    type Node = (isize, isize);
+    type Point = (isize, isize);
+
+    fn find_path(
+        &self,
+        from: (isize, isize),
+        to: (isize, isize),
+    ) -> Option<NavPath<(isize, isize)>> {
+        self.find_path(from, to)
+    }
+
+    fn find_path_custom<F>(
+        &self,
+        from: (isize, isize),
+        to: (isize, isize),
+        filter: F,
+    ) -> Option<NavPath<(isize, isize)>>
+    where
+        F: FnMut((isize, isize), (isize, isize), Scalar) -> bool,
+    {
+        self.find_path_custom(from, to, filter)
+    }
+
+    fn find_islands(&self) -> Vec<Vec<(isize, isize)>> {
+        self.find_islands()
+    }
+
+    fn neighbors(&self, cell: (isize, isize)) -> impl Iterator<Item = (isize, isize)> + '_ {
+        self.neighbors(cell).into_iter().flatten()
+    }
+
+    fn node_cost(&self, cell: (isize, isize)) -> Option<Scalar> {
+        self.index(cell).map(|index| self.costs[index])
+    }
+
+    fn set_node_cost(&mut self, cell: (isize, isize), cost: Scalar) -> Option<Scalar> {
+        self.set_cell_cost(cell, cost)
+    }
+
+    fn cost_model(&self) -> NavCostModel {
+        self.cost_model()
+    }
+
+    fn set_cost_model(&mut self, model: NavCostModel) {
+        self.set_cost_model(model);
+    }
+}
+
+impl NavGraph for NavHexGrid {
+    fn node_count(&self) -> usize {
+        self.cells.len()
+    }
+
+    // cells that are not walkable cannot be left.
+    fn neighbors(&self, node: usize) -> impl Iterator<Item = (usize, Scalar)> + '_ {
+        (self.walkable.get(node) == Some(&true))
+            .then(|| self.neighbor_indices(node).map(|n| (n, 1.0)))
+            .into_iter()
+            .flatten()
+    }
+
+    fn edge_cost(&self, from: usize, to: usize, weight: Scalar) -> Option<Scalar> {
+        Some(self.connection_cost(self.cost_model, from, to, weight))
+    }
+
+    fn heuristic(&self, node: usize, goal: usize) -> Scalar {
+        self.heuristic(self.cost_model, node, goal)
+    }
+
+    fn node_position(&self, node: usize) -> Option<NavVec3> {
+        self.coord(node).map(|cell| self.cell_to_world(cell))
+    }
+}
@@ -1,34 +1,20 @@
-use crate::Scalar;
+use crate::{
+    nav_graph::search_graph, nav_navigable::NavFilterCost, nav_path::path_length, NavCost,
+    NavCostModel, NavGraph, NavPath, NavSearchContext, NavVec3, Navigable, Scalar,
+};
 use petgraph::{
-    algo::{astar, tarjan_scc},
+    algo::tarjan_scc,
     graph::NodeIndex,
-    visit::EdgeRef,
+    visit::{EdgeFiltered, EdgeRef},
     Directed, Graph,
 };
-use serde::{de::DeserializeOwned, Deserialize, Serialize};
-#[cfg(not(feature = "scalar64"))]
-use std::f32::MAX as SCALAR_MAX;
-#[cfg(feature = "scalar64")]
-use std::f64::MAX as SCALAR_MAX;
-use std::{
-    collections::{HashMap, HashSet},
-    hash::Hash,
+use serde::{
+    de::{DeserializeOwned, Error as _},
+    Deserialize, Deserializer, Serialize, Serializer,
 };
+use std::{collections::HashMap, hash::Hash};
 use typid::ID;
 
-#[cfg(feature = "parallel")]
-macro_rules! iter {
-    ($v:expr) => {
-        $v.par_iter()
-    };
-}
-#[cfg(not(feature = "parallel"))]
-macro_rules! iter {
-    ($v:expr) => {
-        $v.iter()
-    };
-}
-
 /// Nav islands identifier.
 pub type NavIslandsID = ID<NavIslands<(), ()>>;
 
@@ -61,7 +47,36 @@ where
     pub distance: Scalar,
 }
 
+// connection data stored on graph edge.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+struct NavIslandsEdge {
+    distance: Scalar,
+    cost: Scalar,
+    enabled: bool,
+    // connection was added both ways, so edits apply to the opposite edge too.
+    #[serde(default)]
+    both_ways: bool,
+}
+
+impl NavIslandsEdge {
+    fn new(distance: Scalar) -> Self {
+        Self {
+            distance,
+            cost: 1.0,
+            enabled: true,
+            both_ways: false,
+        }
+    }
+
+    #[inline]
+    fn weight(&self) -> Scalar {
+        self.distance * self.cost
+    }
+}
+
+// serde impls are written by hand below to rebuild portals map after loading.
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(remote = "Self")]
 pub struct NavIslands<Island, Portal>
 where
     Island: std::fmt::Debug + Clone + Eq + Hash + Send + Sync,
@@ -69,13 +84,18 @@ where
 {
     id: NavIslandsID,
     costs: Vec<Scalar>,
+    #[serde(default)]
+    cost_model: NavCostModel,
     #[serde(bound(
         deserialize = "Island: Serialize + DeserializeOwned, Portal: Serialize + DeserializeOwned"
     ))]
     portals: Vec<NavIslandPortal<Island, Portal>>,
-    graph: Graph<(), Scalar, Directed>,
-    nodes: Vec<NodeIndex>,
-    nodes_map: HashMap<NodeIndex, usize>,
+    // {portal: portal index}
+    #[serde(skip, default = "HashMap::new")]
+    portals_map: HashMap<NavIslandPortal<Island, Portal>, usize>,
+    // graph node indices are the same as portals indices.
+    #[serde(deserialize_with = "deserialize_graph")]
+    graph: Graph<(), NavIslandsEdge, Directed>,
 }
 
 impl<Island, Portal> NavIslands<Island, Portal>
@@ -84,38 +104,19 @@ where
     Portal: std::fmt::Debug + Clone + Eq + Hash + Send + Sync,
 {
     pub fn new(connections: Vec<NavIslandsConnection<Island, Portal>>, both_ways: bool) -> Self {
-        let portals = connections
-            .iter()
-            .map(|c| c.from.clone())
-            .chain(connections.iter().map(|c| c.to.clone()))
-            .collect::<HashSet<_>>()
-            .into_iter()
-            .collect::<Vec<_>>();
-        let costs = vec![1.0; portals.len()];
-        let mut graph =
-            Graph::<(), Scalar, Directed>::with_capacity(portals.len(), connections.len());
-        let nodes = (0..portals.len())
-            .map(|_| graph.add_node(()))
-            .collect::<Vec<_>>();
-        for connection in connections {
-            let ia = portals.iter().position(|c| &connection.from == c);
-            let ib = portals.iter().position(|c| &connection.to == c);
-            if let (Some(ia), Some(ib)) = (ia, ib) {
-                graph.add_edge(nodes[ia], nodes[ib], connection.distance);
-                if both_ways {
-                    graph.add_edge(nodes[ib], nodes[ia], connection.distance);
-                }
-            }
-        }
-        let nodes_map = iter!(nodes).enumerate().map(|(i, n)| (*n, i)).collect();
-        Self {
+        let count = connections.len() * 2;
+        let mut result = Self {
             id: NavIslandsID::new(),
-            costs,
-            portals,
-            graph,
-            nodes,
-            nodes_map,
+            costs: Vec::with_capacity(count),
+            cost_model: NavCostModel::default(),
+            portals: Vec::with_capacity(count),
+            portals_map: HashMap::with_capacity(count),
+            graph: Graph::with_capacity(count, connections.len()),
+        };
+        for connection in connections {
+            result.add_connection(connection, both_ways);
         }
+        result
     }
 
     #[inline]
@@ -133,6 +134,239 @@ where
         &self.costs
     }
 
+    /// Add portal without connections, returning its index (or index of already existing one).
+    pub fn add_portal(&mut self, portal: NavIslandPortal<Island, Portal>) -> usize {
+        if let Some(index) = self.portals_map.get(&portal) {
+            return *index;
+        }
+        let index = self.graph.add_node(()).index();
+        self.portals_map.insert(portal.clone(), index);
+        self.portals.push(portal);
+        self.costs.push(1.0);
+        index
+    }
+
+    /// Remove portal with all its connections. Returns `false` if there was no such portal.
+    ///
+    /// Last portal takes index of removed one.
+    pub fn remove_portal(&mut self, portal: &NavIslandPortal<Island, Portal>) -> bool {
+        let index = match self.portals_map.remove(portal) {
+            Some(index) => index,
+            None => return false,
+        };
+        // graph moves its last node into removed node slot, so portals have to follow.
+        self.graph.remove_node(NodeIndex::new(index));
+        self.portals.swap_remove(index);
+        self.costs.swap_remove(index);
+        if let Some(moved) = self.portals.get(index) {
+            self.portals_map.insert(moved.clone(), index);
+        }
+        true
+    }
+
+    /// Connect two portals, adding missing ones. Distance of already existing connection gets
+    /// replaced (keeping its cost and enabled state). Connection added `both_ways` remembers
+    /// that, so later edits and removal apply to both its directions. Returns `false` if portals
+    /// were already connected.
+    pub fn add_connection(
+        &mut self,
+        connection: NavIslandsConnection<Island, Portal>,
+        both_ways: bool,
+    ) -> bool {
+        let a = NodeIndex::new(self.add_portal(connection.from));
+        let b = NodeIndex::new(self.add_portal(connection.to));
+        let result = self.graph.find_edge(a, b).is_none();
+        self.connect(a, b, connection.distance);
+        if both_ways {
+            self.connect(b, a, connection.distance);
+        }
+        for (a, b) in [(a, b), (b, a)] {
+            if let Some(edge) = self.graph.find_edge(a, b) {
+                self.graph[edge].both_ways = both_ways;
+            }
+        }
+        result
+    }
+
+    fn connect(&mut self, a: NodeIndex, b: NodeIndex, distance: Scalar) {
+        match self.graph.find_edge(a, b) {
+            Some(edge) => self.graph[edge].distance = distance,
+            None => {
+                self.graph.add_edge(a, b, NavIslandsEdge::new(distance));
+            }
+        }
+    }
+
+    /// Disconnect two portals (also `to` from `from` if connection was added both ways), keeping
+    /// portals in graph. Returns `false` if portals were not connected.
+    pub fn remove_connection(
+        &mut self,
+        from: &NavIslandPortal<Island, Portal>,
+        to: &NavIslandPortal<Island, Portal>,
+    ) -> bool {
+        let (a, b) = match (self.index(from), self.index(to)) {
+            (Some(a), Some(b)) => (NodeIndex::new(a), NodeIndex::new(b)),
+            _ => return false,
+        };
+        let edge = match self.graph.find_edge(a, b) {
+            Some(edge) => edge,
+            None => return false,
+        };
+        let both_ways = self.graph[edge].both_ways;
+        self.graph.remove_edge(edge);
+        if both_ways {
+            if let Some(edge) = self.graph.find_edge(b, a) {
+                self.graph.remove_edge(edge);
+            }
+        }
+        true
+    }
+
+    /// Tells if connection going from `from` to `to` was added both ways.
+    pub fn is_connection_both_ways(
+        &self,
+        from: &NavIslandPortal<Island, Portal>,
+        to: &NavIslandPortal<Island, Portal>,
+    ) -> Option<bool> {
+        self.connection(from, to).map(|edge| edge.both_ways)
+    }
+
+    /// Mark connection going from `from` to `to` and the opposite one as added both ways or
+    /// not. Clearing it lets each direction be edited (e.g. locked from one side only) and
+    /// removed on its own, setting it ties them together again. Opposite connection is never
+    /// added nor removed here.
+    ///
+    /// # Returns
+    /// `Some` with old state or `None` if there is no connection from `from` to `to`.
+    pub fn set_connection_both_ways(
+        &mut self,
+        from: &NavIslandPortal<Island, Portal>,
+        to: &NavIslandPortal<Island, Portal>,
+        both_ways: bool,
+    ) -> Option<bool> {
+        let a = NodeIndex::new(self.index(from)?);
+        let b = NodeIndex::new(self.index(to)?);
+        let edge = self.graph.find_edge(a, b)?;
+        let result = std::mem::replace(&mut self.graph[edge].both_ways, both_ways);
+        if let Some(edge) = self.graph.find_edge(b, a) {
+            self.graph[edge].both_ways = both_ways;
+        }
+        Some(result)
+    }
+
+    /// Distance of connection going from `from` to `to`.
+    pub fn connection_distance(
+        &self,
+        from: &NavIslandPortal<Island, Portal>,
+        to: &NavIslandPortal<Island, Portal>,
+    ) -> Option<Scalar> {
+        self.connection(from, to).map(|edge| edge.distance)
+    }
+
+    /// Cost factor of connection going from `from` to `to` (1 by default).
+    pub fn connection_cost(
+        &self,
+        from: &NavIslandPortal<Island, Portal>,
+        to: &NavIslandPortal<Island, Portal>,
+    ) -> Option<Scalar> {
+        self.connection(from, to).map(|edge| edge.cost)
+    }
+
+    /// Tells if connection going from `from` to `to` can be traversed.
+    pub fn is_connection_enabled(
+        &self,
+        from: &NavIslandPortal<Island, Portal>,
+        to: &NavIslandPortal<Island, Portal>,
+    ) -> Option<bool> {
+        self.connection(from, to).map(|edge| edge.enabled)
+    }
+
+    /// Set distance of connection going from `from` to `to` (and of the opposite one too, if
+    /// connection was added both ways).
+    ///
+    /// # Returns
+    /// `Some` with old distance or `None` if there is no connection from `from` to `to`.
+    pub fn set_connection_distance(
+        &mut self,
+        from: &NavIslandPortal<Island, Portal>,
+        to: &NavIslandPortal<Island, Portal>,
+        distance: Scalar,
+    ) -> Option<Scalar> {
+        let distance = distance.max(0.0);
+        self.edit_connection(from, to, |edge| {
+            std::mem::replace(&mut edge.distance, distance)
+        })
+    }
+
+    /// Set cost factor of connection going from `from` to `to` (and of the opposite one too, if
+    /// connection was added both ways). Connection weight used by searches is distance times
+    /// cost.
+    ///
+    /// # Returns
+    /// `Some` with old cost or `None` if there is no connection from `from` to `to`.
+    pub fn set_connection_cost(
+        &mut self,
+        from: &NavIslandPortal<Island, Portal>,
+        to: &NavIslandPortal<Island, Portal>,
+        cost: Scalar,
+    ) -> Option<Scalar> {
+        let cost = cost.max(0.0);
+        self.edit_connection(from, to, |edge| std::mem::replace(&mut edge.cost, cost))
+    }
+
+    /// Enable or disable connection going from `from` to `to` (and the opposite one too, if
+    /// connection was added both ways). Disabled connections stay in graph but are never
+    /// traversed, so locked door can be opened again without reconstructing islands. To lock
+    /// only one side of connection added both ways, clear it with `set_connection_both_ways`
+    /// first.
+    ///
+    /// # Returns
+    /// `Some` with old enabled state or `None` if there is no connection from `from` to `to`.
+    pub fn set_connection_enabled(
+        &mut self,
+        from: &NavIslandPortal<Island, Portal>,
+        to: &NavIslandPortal<Island, Portal>,
+        enabled: bool,
+    ) -> Option<bool> {
+        self.edit_connection(from, to, |edge| {
+            std::mem::replace(&mut edge.enabled, enabled)
+        })
+    }
+
+    fn connection(
+        &self,
+        from: &NavIslandPortal<Island, Portal>,
+        to: &NavIslandPortal<Island, Portal>,
+    ) -> Option<&NavIslandsEdge> {
+        let a = NodeIndex::new(self.index(from)?);
+        let b = NodeIndex::new(self.index(to)?);
+        self.graph.find_edge(a, b).map(|edge| &self.graph[edge])
+    }
+
+    // applies change to connection and (when added both ways) to opposite one, returning result
+    // of changing the first one.
+    fn edit_connection<F, R>(
+        &mut self,
+        from: &NavIslandPortal<Island, Portal>,
+        to: &NavIslandPortal<Island, Portal>,
+        mut f: F,
+    ) -> Option<R>
+    where
+        F: FnMut(&mut NavIslandsEdge) -> R,
+    {
+        let a = NodeIndex::new(self.index(from)?);
+        let b = NodeIndex::new(self.index(to)?);
+        let edge = self.graph.find_edge(a, b)?;
+        let both_ways = self.graph[edge].both_ways;
+        let result = f(&mut self.graph[edge]);
+        if both_ways {
+            if let Some(edge) = self.graph.find_edge(b, a) {
+                f(&mut self.graph[edge]);
+            }
+        }
+        Some(result)
+    }
+
     #[inline]
     pub fn set_portal_cost(
         &mut self,
@@ -146,74 +380,165 @@ where
         Some(old)
     }
 
+    #[inline]
+    pub fn cost_model(&self) -> NavCostModel {
+        self.cost_model
+    }
+
+    #[inline]
+    pub fn set_cost_model(&mut self, model: NavCostModel) {
+        self.cost_model = model;
+    }
+
     pub fn neighbors(
         &self,
         portal: &NavIslandPortal<Island, Portal>,
     ) -> Option<impl Iterator<Item = &NavIslandPortal<Island, Portal>> + '_> {
         let index = self.index(portal)?;
-        let node = self.nodes[index];
-        Some(self.graph.neighbors(node).filter_map(|node| {
-            self.nodes_map
-                .get(&node)
-                .and_then(|index| self.portal(*index))
-        }))
+        Some(self.edges(index).filter_map(|(node, _)| self.portal(node)))
     }
 
     pub fn find_path(
         &self,
         from: &NavIslandPortal<Island, Portal>,
         to: &NavIslandPortal<Island, Portal>,
-    ) -> Option<(Scalar, Vec<&NavIslandPortal<Island, Portal>>)> {
+    ) -> Option<NavPath<&NavIslandPortal<Island, Portal>>> {
         self.find_path_custom(from, to, |_, _| true)
     }
 
     // filter params: first island-portal, second island-portal.
+    // connections rejected by filter cannot be traversed.
     pub fn find_path_custom<F>(
         &self,
         from: &NavIslandPortal<Island, Portal>,
         to: &NavIslandPortal<Island, Portal>,
         mut filter: F,
-    ) -> Option<(Scalar, Vec<&NavIslandPortal<Island, Portal>>)>
+    ) -> Option<NavPath<&NavIslandPortal<Island, Portal>>>
     where
         F: FnMut(&NavIslandPortal<Island, Portal>, &NavIslandPortal<Island, Portal>) -> bool,
     {
-        let start_index = self.index(from)?;
-        let end_index = self.index(to)?;
-        let start_node = *self.nodes.get(start_index)?;
-        let end_node = *self.nodes.get(end_index)?;
-        let (distance, nodes) = astar(
-            &self.graph,
-            start_node,
-            |n| n == end_node,
-            |e| {
-                let a = self.nodes_map[&e.source()];
-                let b = self.nodes_map[&e.target()];
-                let w = *e.weight();
+        let mut context = NavSearchContext::default();
+        let cost = self.find_path_inner(
+            &mut context,
+            from,
+            to,
+            |a, b, w| {
                 if filter(self.portal(a).unwrap(), self.portal(b).unwrap()) {
-                    let a = self.costs[a];
-                    let b = self.costs[b];
-                    w * a * b
+                    Some(self.combined_cost(self.cost_model, a, b, w))
                 } else {
-                    SCALAR_MAX
+                    None
                 }
             },
-            |_| 0.0,
+            |_, _| 0.0,
+        )?;
+        Some(self.make_path(&context.path, cost))
+    }
+
+    // cost params: first island-portal, second island-portal.
+    pub fn find_path_with_cost<C>(
+        &self,
+        from: &NavIslandPortal<Island, Portal>,
+        to: &NavIslandPortal<Island, Portal>,
+        cost: &C,
+    ) -> Option<NavPath<&NavIslandPortal<Island, Portal>>>
+    where
+        C: for<'p> NavCost<&'p NavIslandPortal<Island, Portal>>,
+    {
+        let mut context = NavSearchContext::default();
+        let (cost, _) = self.find_path_with_context(&mut context, from, to, cost)?;
+        Some(self.make_path(&context.path, cost))
+    }
+
+    // cost params: first island-portal, second island-portal.
+    pub fn find_path_with_context<'a, 'b, C>(
+        &'a self,
+        context: &'b mut NavSearchContext,
+        from: &NavIslandPortal<Island, Portal>,
+        to: &NavIslandPortal<Island, Portal>,
+        cost: &C,
+    ) -> Option<(
+        Scalar,
+        impl Iterator<Item = &'a NavIslandPortal<Island, Portal>> + 'b,
+    )>
+    where
+        'a: 'b,
+        C: for<'p> NavCost<&'p NavIslandPortal<Island, Portal>>,
+    {
+        let model = cost.cost_model().unwrap_or(self.cost_model);
+        let distance = self.find_path_inner(
+            context,
+            from,
+            to,
+            |a, b, w| {
+                let (pa, pb) = (self.portal(a).unwrap(), self.portal(b).unwrap());
+                cost.edge_cost(pa, pb, self.combined_cost(model, a, b, w))
+            },
+            |n, goal| {
+                cost.heuristic(self.portal(n).unwrap(), self.portal(goal).unwrap())
+                    .unwrap_or(0.0)
+            },
         )?;
         Some((
             distance,
-            nodes
-                .into_iter()
-                .filter_map(|n| self.portal(self.nodes_map[&n]))
-                .collect::<Vec<_>>(),
+            context.path.iter().filter_map(move |n| self.portal(*n)),
         ))
     }
 
+    fn find_path_inner<E, H>(
+        &self,
+        context: &mut NavSearchContext,
+        from: &NavIslandPortal<Island, Portal>,
+        to: &NavIslandPortal<Island, Portal>,
+        edge_cost: E,
+        heuristic: H,
+    ) -> Option<Scalar>
+    where
+        E: FnMut(usize, usize, Scalar) -> Option<Scalar>,
+        H: FnMut(usize, usize) -> Scalar,
+    {
+        let start_index = self.index(from)?;
+        let end_index = self.index(to)?;
+        let (_, cost) = search_graph(
+            self,
+            context,
+            start_index,
+            &[end_index],
+            edge_cost,
+            heuristic,
+        )?;
+        Some(cost)
+    }
+
+    // enabled connections leaving given portal, with their weights.
+    fn edges(&self, index: usize) -> impl Iterator<Item = (usize, Scalar)> + '_ {
+        self.graph
+            .edges(NodeIndex::new(index))
+            .filter(|e| e.weight().enabled)
+            .map(|e| (e.target().index(), e.weight().weight()))
+    }
+
+    // path length is sum of connections distances.
+    fn make_path(&self, path: &[usize], cost: Scalar) -> NavPath<&NavIslandPortal<Island, Portal>> {
+        let length = path_length(path, |a, b| {
+            self.graph
+                .find_edge(NodeIndex::new(*a), NodeIndex::new(*b))
+                .map_or(0.0, |edge| self.graph[edge].distance)
+        });
+        let portals = path.iter().filter_map(|n| self.portal(*n)).collect();
+        NavPath::new(portals, cost, length)
+    }
+
+    #[inline]
+    fn combined_cost(&self, model: NavCostModel, a: usize, b: usize, weight: Scalar) -> Scalar {
+        model.combine(weight, self.costs[a], self.costs[b])
+    }
+
     pub fn find_islands(&self) -> Vec<Vec<&NavIslandPortal<Island, Portal>>> {
-        tarjan_scc(&self.graph)
+        tarjan_scc(&EdgeFiltered::from_fn(&self.graph, |e| e.weight().enabled))
             .into_iter()
             .map(|v| {
                 v.into_iter()
-                    .filter_map(|n| self.nodes_map.get(&n).and_then(|i| self.portal(*i)))
+                    .filter_map(|n| self.portal(n.index()))
                     .collect::<Vec<_>>()
             })
             .filter(|v| !v.is_empty())
@@ -221,10 +546,172 @@ where
     }
 
     pub fn index(&self, portal: &NavIslandPortal<Island, Portal>) -> Option<usize> {
-        self.portals.iter().position(|p| portal == p)
+        self.portals_map.get(portal).copied()
     }
 
     pub fn portal(&self, index: usize) -> Option<&NavIslandPortal<Island, Portal>> {
         self.portals.get(index)
     }
 }
+
+impl<Island, Portal> Serialize for NavIslands<Island, Portal>
+where
+    Island: std::fmt::Debug + Clone + Eq + Hash + Send + Sync + Serialize,
+    Portal: std::fmt::Debug + Clone + Eq + Hash + Send + Sync + Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Self::serialize(self, serializer)
+    }
+}
+
+impl<'de, Island, Portal> Deserialize<'de> for NavIslands<Island, Portal>
+where
+    Island: std::fmt::Debug + Clone + Eq + Hash + Send + Sync + Serialize + DeserializeOwned,
+    Portal: std::fmt::Debug + Clone + Eq + Hash + Send + Sync + Serialize + DeserializeOwned,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut result = Self::deserialize(deserializer)?;
+        if result.costs.len() != result.portals.len()
+            || result.graph.node_count() != result.portals.len()
+        {
+            return Err(D::Error::custom(
+                "NavIslands portals count does not match costs and graph",
+            ));
+        }
+        result.portals_map = result
+            .portals
+            .iter()
+            .enumerate()
+            .map(|(index, portal)| (portal.clone(), index))
+            .collect();
+        Ok(result)
+    }
+}
+
+impl<Island, Portal> NavGraph for NavIslands<Island, Portal>
+where
+    Island: std::fmt::Debug + Clone + Eq + Hash + Send + Sync,
+    Portal: std::fmt::Debug + Clone + Eq + Hash + Send + Sync,
+{
+    fn node_count(&self) -> usize {
+        self.portals.len()
+    }
+
+    fn neighbors(&self, node: usize) -> impl Iterator<Item = (usize, Scalar)> + '_ {
+        self.edges(node)
+    }
+
+    fn edge_cost(&self, from: usize, to: usize, weight: Scalar) -> Option<Scalar> {
+        Some(self.combined_cost(self.cost_model, from, to, weight))
+    }
+
+    fn node_position(&self, _node: usize) -> Option<NavVec3> {
+        None
+    }
+}
+
+impl<Island, Portal> Navigable for NavIslands<Island, Portal>
+where
+    Island: std::fmt::Debug + Clone + Eq + Hash + Send + Sync,
+    Portal: std::fmt::Debug + Clone + Eq + Hash + Send + Sync,
+{
+    type Node = NavIslandPortal<Island, Portal>;
+    type Point = NavIslandPortal<Island, Portal>;
+
+    fn find_path_custom<F>(
+        &self,
+        from: NavIslandPortal<Island, Portal>,
+        to: NavIslandPortal<Island, Portal>,
+        mut filter: F,
+    ) -> Option<NavPath<NavIslandPortal<Island, Portal>>>
+    where
+        F: FnMut(NavIslandPortal<Island, Portal>, NavIslandPortal<Island, Portal>, Scalar) -> bool,
+    {
+        let cost = NavFilterCost::new(
+            |a: &NavIslandPortal<Island, Portal>, b: &NavIslandPortal<Island, Portal>, cost| {
+                filter(a.clone(), b.clone(), cost)
+            },
+        );
+        self.find_path_with_cost(&from, &to, &cost)
+            .map(|path| path.map(Clone::clone))
+    }
+
+    fn find_islands(&self) -> Vec<Vec<NavIslandPortal<Island, Portal>>> {
+        self.find_islands()
+            .into_iter()
+            .map(|island| island.into_iter().cloned().collect())
+            .collect()
+    }
+
+    fn neighbors(
+        &self,
+        portal: NavIslandPortal<Island, Portal>,
+    ) -> impl Iterator<Item = NavIslandPortal<Island, Portal>> + '_ {
+        self.neighbors(&portal).into_iter().flatten().cloned()
+    }
+
+    fn node_cost(&self, portal: NavIslandPortal<Island, Portal>) -> Option<Scalar> {
+        self.index(&portal).map(|index| self.costs[index])
+    }
+
+    fn set_node_cost(
+        &mut self,
+        portal: NavIslandPortal<Island, Portal>,
+        cost: Scalar,
+    ) -> Option<Scalar> {
+        self.set_portal_cost(&portal, cost)
+    }
+
+    fn cost_model(&self) -> NavCostModel {
+        self.cost_model()
+    }
+
+    fn set_cost_model(&mut self, model: NavCostModel) {
+        self.set_cost_model(model);
+    }
+}
+
+// legacy graph edges were bare distances.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NavIslandsEdgeFormat {
+    Edge(NavIslandsEdge),
+    Distance(Scalar),
+}
+
+// human readable formats can still load graphs stored before connections got cost, enabled state
+// and direction. Legacy connection is treated as added both ways when the opposite edge has the
+// same distance.
+fn deserialize_graph<'de, D>(
+    deserializer: D,
+) -> Result<Graph<(), NavIslandsEdge, Directed>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    if !deserializer.is_human_readable() {
+        return Graph::deserialize(deserializer);
+    }
+    let graph = Graph::<(), NavIslandsEdgeFormat, Directed>::deserialize(deserializer)?;
+    let distance = |a, b| match graph.find_edge(a, b).map(|edge| &graph[edge]) {
+        Some(NavIslandsEdgeFormat::Distance(distance)) => Some(*distance),
+        _ => None,
+    };
+    Ok(graph.map(
+        |_, node| *node,
+        |index, edge| match edge {
+            NavIslandsEdgeFormat::Edge(edge) => *edge,
+            NavIslandsEdgeFormat::Distance(value) => {
+                let (a, b) = graph.edge_endpoints(index).unwrap();
+                let mut edge = NavIslandsEdge::new(*value);
+                edge.both_ways = distance(b, a) == Some(*value);
+                edge
+            }
+        },
+    ))
+}
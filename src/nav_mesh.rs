@@ -1,19 +1,17 @@
-use crate::{Error, NavConnection, NavResult, NavVec3, Scalar, ZERO_TRESHOLD};
-use petgraph::{
-    algo::{astar, tarjan_scc},
-    graph::NodeIndex,
-    visit::EdgeRef,
-    Graph, Undirected,
+use crate::{
+    nav_graph::search_graph,
+    nav_navigable::NavFilterCost,
+    nav_path::path_length,
+    nav_search::{dijkstra, NO_GOAL},
+    Error, NavConnection, NavCost, NavCostModel, NavDistanceField, NavGraph, NavPath, NavResult,
+    NavSearchContext, NavVec3, Navigable, Scalar, ZERO_TRESHOLD,
 };
+use petgraph::{algo::tarjan_scc, graph::NodeIndex, visit::EdgeRef, Graph, Undirected};
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use spade::{rtree::RTree, BoundingRect, SpatialObject};
 use std::collections::HashMap;
-#[cfg(not(feature = "scalar64"))]
-use std::f32::MAX as SCALAR_MAX;
-#[cfg(feature = "scalar64")]
-use std::f64::MAX as SCALAR_MAX;
 use typid::ID;
 
 #[cfg(feature = "parallel")]
@@ -226,8 +224,27 @@ pub enum NavPathMode {
     MidPoints,
 }
 
+#[derive(Debug, Copy, Clone)]
+pub(crate) enum NavMeshPathNode {
+    Point(NavVec3),
+    // (a, b, normal)
+    LevelChange(NavVec3, NavVec3, NavVec3),
+}
+
+// connection of triangles stored on graph edge.
+#[derive(Debug, Default, Copy, Clone)]
+struct NavMeshEdge {
+    // squared distance between triangles centers.
+    weight: Scalar,
+    // distances from centers of edge source and target triangles to shared edge midpoint.
+    source_length: Scalar,
+    target_length: Scalar,
+}
+
 /// Nav mesh object used to find shortest path between two points.
+// serde impls are written by hand below to rebuild graph after loading.
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(remote = "Self")]
 pub struct NavMesh {
     id: NavMeshID,
     vertices: Vec<NavVec3>,
@@ -235,14 +252,16 @@ pub struct NavMesh {
     areas: Vec<NavArea>,
     // {triangle connection: (distance sqr, vertex connection)}
     connections: HashMap<NavConnection, (Scalar, NavConnection)>,
-    graph: Graph<(), Scalar, Undirected>,
-    nodes: Vec<NodeIndex>,
-    nodes_map: HashMap<NodeIndex, usize>,
+    // rebuilt from connections after loading.
+    #[serde(skip)]
+    graph: Graph<(), NavMeshEdge, Undirected>,
     rtree: RTree<NavSpatialObject>,
     spatials: Vec<NavSpatialObject>,
     // {triangle index: [(from, to)]}
     hard_edges: HashMap<usize, Vec<(NavVec3, NavVec3)>>,
     origin: NavVec3,
+    #[serde(default)]
+    cost_model: NavCostModel,
 }
 
 impl NavMesh {
@@ -371,16 +390,7 @@ impl NavMesh {
         })
         .collect::<HashMap<_, _>>();
 
-        let mut graph = Graph::<(), Scalar, Undirected>::new_undirected();
-        let nodes = (0..triangles.len())
-            .map(|_| graph.add_node(()))
-            .collect::<Vec<_>>();
-        graph.extend_with_edges(
-            iter!(connections)
-                .map(|(conn, (w, _))| (nodes[conn.0 as usize], nodes[conn.1 as usize], w))
-                .collect::<Vec<_>>(),
-        );
-        let nodes_map = iter!(nodes).enumerate().map(|(i, n)| (*n, i)).collect();
+        let graph = Self::build_graph(&vertices, &areas, &connections);
 
         let spatials = iter!(triangles)
             .enumerate()
@@ -439,15 +449,48 @@ impl NavMesh {
             areas,
             connections,
             graph,
-            nodes,
-            nodes_map,
             rtree,
             spatials,
             hard_edges,
             origin,
+            cost_model: NavCostModel::default(),
         })
     }
 
+    // graph node indices are the same as triangle indices.
+    fn build_graph(
+        vertices: &[NavVec3],
+        areas: &[NavArea],
+        connections: &HashMap<NavConnection, (Scalar, NavConnection)>,
+    ) -> Graph<(), NavMeshEdge, Undirected> {
+        let count = areas.len();
+        let mut graph =
+            Graph::<(), NavMeshEdge, Undirected>::with_capacity(count, connections.len());
+        for _ in 0..count {
+            graph.add_node(());
+        }
+        graph.extend_with_edges(
+            connections
+                .iter()
+                .filter(|(conn, (_, edge))| {
+                    (conn.0 as usize) < count
+                        && (conn.1 as usize) < count
+                        && (edge.0 as usize) < vertices.len()
+                        && (edge.1 as usize) < vertices.len()
+                })
+                .map(|(conn, (weight, edge))| {
+                    let midpoint = (vertices[edge.0 as usize] + vertices[edge.1 as usize]) * 0.5;
+                    let edge = NavMeshEdge {
+                        weight: *weight,
+                        source_length: (midpoint - areas[conn.0 as usize].center).magnitude(),
+                        target_length: (areas[conn.1 as usize].center - midpoint).magnitude(),
+                    };
+                    (conn.0, conn.1, edge)
+                }),
+        );
+        graph
+    }
+
     pub fn thicken(&self, value: Scalar) -> NavResult<Self> {
         let shifted = iter!(self.vertices)
             .enumerate()
@@ -528,6 +571,21 @@ impl NavMesh {
         old
     }
 
+    /// Cost model used to combine connections weights with areas costs.
+    #[inline]
+    pub fn cost_model(&self) -> NavCostModel {
+        self.cost_model
+    }
+
+    /// Set cost model used to combine connections weights with areas costs.
+    ///
+    /// # Arguments
+    /// * `model` - cost model used by queries that do not override it.
+    #[inline]
+    pub fn set_cost_model(&mut self, model: NavCostModel) {
+        self.cost_model = model;
+    }
+
     /// Find closest point on nav mesh.
     ///
     /// # Arguments
@@ -550,7 +608,7 @@ impl NavMesh {
     /// * `mode` - path finding quality.
     ///
     /// # Returns
-    /// `Some` with path points on nav mesh if found or `None` otherwise.
+    /// `Some` with path on nav mesh if found or `None` otherwise.
     ///
     /// # Example
     /// ```
@@ -579,7 +637,8 @@ impl NavMesh {
     ///         NavQuery::Accuracy,
     ///         NavPathMode::MidPoints,
     ///     )
-    ///     .unwrap();
+    ///     .unwrap()
+    ///     .points;
     /// assert_eq!(
     ///     path.into_iter()
     ///         .map(|v| (
@@ -597,7 +656,7 @@ impl NavMesh {
         to: NavVec3,
         query: NavQuery,
         mode: NavPathMode,
-    ) -> Option<Vec<NavVec3>> {
+    ) -> Option<NavPath<NavVec3>> {
         self.find_path_custom(from, to, query, mode, |_, _, _| true)
     }
 
@@ -608,11 +667,12 @@ impl NavMesh {
     /// * `to` - query point to.
     /// * `query` - query quality.
     /// * `mode` - path finding quality.
-    /// * `filter` - closure that gives you a connection distance squared, first triangle index
-    ///   and second triangle index.
+    /// * `filter` - closure that gives you a connection distance squared (between triangles
+    ///   centers, while path costs are measured through shared edge midpoint), first triangle
+    ///   index and second triangle index. Connections it rejects cannot be traversed.
     ///
     /// # Returns
-    /// `Some` with path points on nav mesh if found or `None` otherwise.
+    /// `Some` with path on nav mesh if found or `None` otherwise.
     ///
     /// # Example
     /// ```
@@ -642,7 +702,8 @@ impl NavMesh {
     ///         NavPathMode::MidPoints,
     ///         |_dist_sqr, _first_idx, _second_idx| true,
     ///     )
-    ///     .unwrap();
+    ///     .unwrap()
+    ///     .points;
     /// assert_eq!(
     ///     path.into_iter()
     ///         .map(|v| (
@@ -661,38 +722,272 @@ impl NavMesh {
         query: NavQuery,
         mode: NavPathMode,
         filter: F,
-    ) -> Option<Vec<NavVec3>>
+    ) -> Option<NavPath<NavVec3>>
     where
         F: FnMut(Scalar, usize, usize) -> bool,
     {
-        if from.same_as(to) {
-            return None;
-        }
+        let mut context = NavSearchContext::default();
+        self.find_path_inner(
+            &mut context,
+            from,
+            &[to],
+            query,
+            mode,
+            self.filter_cost(filter),
+            |_, _| 0.0,
+        )
+        .map(|(_, cost, points)| NavPath::from_points(points.to_vec(), cost))
+    }
+
+    /// Find shortest path on nav mesh between two points, providing custom cost function.
+    ///
+    /// # Arguments
+    /// * `from` - query point from.
+    /// * `to` - query point to.
+    /// * `query` - query quality.
+    /// * `mode` - path finding quality.
+    /// * `cost` - cost provider that gets triangles indices.
+    ///
+    /// # Returns
+    /// `Some` with path on nav mesh if found or `None` otherwise.
+    pub fn find_path_with_cost<C>(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        query: NavQuery,
+        mode: NavPathMode,
+        cost: &C,
+    ) -> Option<NavPath<NavVec3>>
+    where
+        C: NavCost<usize>,
+    {
+        let mut context = NavSearchContext::default();
+        self.find_path_cost_inner(&mut context, from, to, query, mode, cost)
+            .map(|(cost, points)| NavPath::from_points(points.to_vec(), cost))
+    }
+
+    /// Find shortest path on nav mesh between two points, reusing search context buffers.
+    ///
+    /// # Arguments
+    /// * `context` - reusable search context.
+    /// * `from` - query point from.
+    /// * `to` - query point to.
+    /// * `query` - query quality.
+    /// * `mode` - path finding quality.
+    /// * `cost` - cost provider that gets triangles indices.
+    ///
+    /// # Returns
+    /// `Some` with path points stored in search context if found or `None` otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (2.0, 0.0, 1.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    ///     (1.0, 1.0, 0.0).into(), // 4
+    ///     (2.0, 1.0, 1.0).into(), // 5
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 4).into(), // 0
+    ///     (4, 3, 0).into(), // 1
+    ///     (1, 2, 5).into(), // 2
+    ///     (5, 4, 1).into(), // 3
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let mut context = NavSearchContext::default();
+    /// let path = mesh
+    ///     .find_path_with_context(
+    ///         &mut context,
+    ///         (0.0, 1.0, 0.0).into(),
+    ///         (1.5, 0.25, 0.5).into(),
+    ///         NavQuery::Accuracy,
+    ///         NavPathMode::MidPoints,
+    ///         &NavDefaultCost,
+    ///     )
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     path.iter()
+    ///         .map(|v| (
+    ///             (v.x * 10.0) as i32,
+    ///             (v.y * 10.0) as i32,
+    ///             (v.z * 10.0) as i32,
+    ///         ))
+    ///         .collect::<Vec<_>>(),
+    ///     vec![(0, 10, 0), (10, 5, 0), (15, 2, 5),]
+    /// );
+    /// ```
+    pub fn find_path_with_context<'a, C>(
+        &self,
+        context: &'a mut NavSearchContext,
+        from: NavVec3,
+        to: NavVec3,
+        query: NavQuery,
+        mode: NavPathMode,
+        cost: &C,
+    ) -> Option<&'a [NavVec3]>
+    where
+        C: NavCost<usize>,
+    {
+        self.find_path_cost_inner(context, from, to, query, mode, cost)
+            .map(|(_, points)| points)
+    }
+
+    /// Find shortest path on nav mesh from point to the closest of many target points.
+    ///
+    /// Single search is performed that stops at first reached target. When more targets lie on
+    /// the same triangle, the first one in the list wins.
+    ///
+    /// # Arguments
+    /// * `from` - query point from.
+    /// * `targets` - list of query points to.
+    /// * `query` - query quality.
+    /// * `mode` - path finding quality.
+    ///
+    /// # Returns
+    /// `Some` with index of reached target and path if found or `None` otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (2.0, 0.0, 1.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    ///     (1.0, 1.0, 0.0).into(), // 4
+    ///     (2.0, 1.0, 1.0).into(), // 5
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 4).into(), // 0
+    ///     (4, 3, 0).into(), // 1
+    ///     (1, 2, 5).into(), // 2
+    ///     (5, 4, 1).into(), // 3
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let (target, path) = mesh
+    ///     .find_path_to_any(
+    ///         (0.0, 1.0, 0.0).into(),
+    ///         &[(1.5, 0.25, 0.5).into(), (0.75, 0.25, 0.0).into()],
+    ///         NavQuery::Accuracy,
+    ///         NavPathMode::MidPoints,
+    ///     )
+    ///     .unwrap();
+    /// assert_eq!(target, 1);
+    /// assert_eq!(path.points.last(), Some(&(0.75, 0.25, 0.0).into()));
+    /// ```
+    pub fn find_path_to_any(
+        &self,
+        from: NavVec3,
+        targets: &[NavVec3],
+        query: NavQuery,
+        mode: NavPathMode,
+    ) -> Option<(usize, NavPath<NavVec3>)> {
+        let mut context = NavSearchContext::default();
+        self.find_path_inner(
+            &mut context,
+            from,
+            targets,
+            query,
+            mode,
+            |a, b, _| self.connection_cost(self.cost_model, a, b),
+            |_, _| 0.0,
+        )
+        .map(|(winner, cost, points)| (winner, NavPath::from_points(points.to_vec(), cost)))
+    }
+
+    fn find_path_cost_inner<'a, C>(
+        &self,
+        context: &'a mut NavSearchContext,
+        from: NavVec3,
+        to: NavVec3,
+        query: NavQuery,
+        mode: NavPathMode,
+        cost: &C,
+    ) -> Option<(Scalar, &'a [NavVec3])>
+    where
+        C: NavCost<usize>,
+    {
+        let model = cost.cost_model().unwrap_or(self.cost_model);
+        self.find_path_inner(
+            context,
+            from,
+            &[to],
+            query,
+            mode,
+            |a, b, _| cost.edge_cost(a, b, self.connection_cost(model, a, b)?),
+            |n, goal| cost.heuristic(n, goal).unwrap_or(0.0),
+        )
+        .map(|(_, cost, points)| (cost, points))
+    }
+
+    // returns index of reached target, cost of triangles path and path points.
+    #[allow(clippy::too_many_arguments)]
+    fn find_path_inner<'a, E, H>(
+        &self,
+        context: &'a mut NavSearchContext,
+        from: NavVec3,
+        targets: &[NavVec3],
+        query: NavQuery,
+        mode: NavPathMode,
+        edge_cost: E,
+        heuristic: H,
+    ) -> Option<(usize, Scalar, &'a [NavVec3])>
+    where
+        E: FnMut(usize, usize, Scalar) -> Option<Scalar>,
+        H: FnMut(usize, usize) -> Scalar,
+    {
         let start = self.find_closest_triangle(from, query)?;
-        let end = self.find_closest_triangle(to, query)?;
+        let mut goals = std::mem::take(&mut context.goals);
+        goals.clear();
+        goals.extend(targets.iter().map(|to| {
+            if from.same_as(*to) {
+                NO_GOAL
+            } else {
+                self.find_closest_triangle(*to, query).unwrap_or(NO_GOAL)
+            }
+        }));
+        let result = self.find_path_triangles_inner(context, start, &goals, edge_cost, heuristic);
+        let end = result.map(|(winner, cost)| (winner, goals[winner], cost));
+        context.goals = goals;
+        let (winner, end, cost) = end?;
         let from = self.spatials[start].closest_point(from);
-        let to = self.spatials[end].closest_point(to);
-        let (triangles, _) = self.find_path_triangles_custom(start, end, filter)?;
+        let to = self.spatials[end].closest_point(targets[winner]);
+        let NavSearchContext {
+            path: triangles,
+            points,
+            mesh_nodes: nodes,
+            ..
+        } = context;
+        points.clear();
         if triangles.is_empty() {
             return None;
         } else if triangles.len() == 1 {
-            return Some(vec![from, to]);
+            points.push(from);
+            points.push(to);
+            return Some((winner, cost, points));
         }
         match mode {
-            NavPathMode::Accuracy => Some(self.find_path_accuracy(from, to, &triangles)),
-            NavPathMode::MidPoints => Some(self.find_path_midpoints(from, to, &triangles)),
+            NavPathMode::Accuracy => self.find_path_accuracy(from, to, triangles, nodes, points),
+            NavPathMode::MidPoints => self.find_path_midpoints(from, to, triangles, points),
         }
+        Some((winner, cost, points))
     }
 
-    fn find_path_accuracy(&self, from: NavVec3, to: NavVec3, triangles: &[usize]) -> Vec<NavVec3> {
-        #[derive(Debug)]
-        enum Node {
-            Point(NavVec3),
-            // (a, b, normal)
-            LevelChange(NavVec3, NavVec3, NavVec3),
-        }
-
-        // TODO: reduce allocations.
+    fn find_path_accuracy(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        triangles: &[usize],
+        nodes: &mut Vec<NavMeshPathNode>,
+        points: &mut Vec<NavVec3>,
+    ) {
         if triangles.len() == 2 {
             let NavConnection(a, b) =
                 self.connections[&NavConnection(triangles[0] as u32, triangles[1] as u32)].1;
@@ -700,22 +995,23 @@ impl NavMesh {
             let b = self.vertices[b as usize];
             let n = self.spatials[triangles[0]].normal();
             let m = self.spatials[triangles[1]].normal();
+            points.push(from);
             if !NavVec3::is_line_between_points(from, to, a, b, n) {
                 let da = (from - a).sqr_magnitude();
                 let db = (from - b).sqr_magnitude();
-                let point = if da < db { a } else { b };
-                return vec![from, point, to];
+                points.push(if da < db { a } else { b });
             } else if n.dot(m) < 1.0 - ZERO_TRESHOLD {
                 let n = (b - a).normalize().cross(n);
                 if let Some(point) = NavVec3::raycast_line(from, to, a, b, n) {
-                    return vec![from, point, to];
+                    points.push(point);
                 }
             }
-            return vec![from, to];
+            points.push(to);
+            return;
         }
         let mut start = from;
         let mut last_normal = self.spatials[triangles[0]].normal();
-        let mut nodes = Vec::with_capacity(triangles.len() - 1);
+        nodes.clear();
         for triplets in triangles.windows(3) {
             let NavConnection(a, b) =
                 self.connections[&NavConnection(triplets[0] as u32, triplets[1] as u32)].1;
@@ -734,11 +1030,11 @@ impl NavMesh {
                 let da = (start - a).sqr_magnitude();
                 let db = (start - b).sqr_magnitude();
                 start = if da < db { a } else { b };
-                nodes.push(Node::Point(start));
+                nodes.push(NavMeshPathNode::Point(start));
             } else if old_last_normal.dot(normal) < 1.0 - ZERO_TRESHOLD {
                 let normal = self.spatials[triplets[0]].normal();
                 let normal = (b - a).normalize().cross(normal);
-                nodes.push(Node::LevelChange(a, b, normal));
+                nodes.push(NavMeshPathNode::LevelChange(a, b, normal));
             }
         }
         {
@@ -755,28 +1051,28 @@ impl NavMesh {
                 let da = (start - a).sqr_magnitude();
                 let db = (start - b).sqr_magnitude();
                 let point = if da < db { a } else { b };
-                nodes.push(Node::Point(point));
+                nodes.push(NavMeshPathNode::Point(point));
             } else if n.dot(m) < 1.0 - ZERO_TRESHOLD {
                 let n = (b - a).normalize().cross(n);
-                nodes.push(Node::LevelChange(a, b, n));
+                nodes.push(NavMeshPathNode::LevelChange(a, b, n));
             }
         }
 
-        let mut points = Vec::with_capacity(nodes.len() + 2);
+        points.reserve(nodes.len() + 2);
         points.push(from);
         let mut point = from;
         for i in 0..nodes.len() {
             match nodes[i] {
-                Node::Point(p) => {
+                NavMeshPathNode::Point(p) => {
                     point = p;
                     points.push(p);
                 }
-                Node::LevelChange(a, b, n) => {
+                NavMeshPathNode::LevelChange(a, b, n) => {
                     let next = nodes
                         .iter()
                         .skip(i + 1)
                         .find_map(|n| match n {
-                            Node::Point(p) => Some(*p),
+                            NavMeshPathNode::Point(p) => Some(*p),
                             _ => None,
                         })
                         .unwrap_or(to);
@@ -788,10 +1084,15 @@ impl NavMesh {
         }
         points.push(to);
         points.dedup();
-        points
     }
 
-    fn find_path_midpoints(&self, from: NavVec3, to: NavVec3, triangles: &[usize]) -> Vec<NavVec3> {
+    fn find_path_midpoints(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        triangles: &[usize],
+        points: &mut Vec<NavVec3>,
+    ) {
         if triangles.len() == 2 {
             let NavConnection(a, b) =
                 self.connections[&NavConnection(triangles[0] as u32, triangles[1] as u32)].1;
@@ -799,16 +1100,17 @@ impl NavMesh {
             let b = self.vertices[b as usize];
             let n = self.spatials[triangles[0]].normal();
             let m = self.spatials[triangles[1]].normal();
+            points.push(from);
             if n.dot(m) < 1.0 - ZERO_TRESHOLD || !NavVec3::is_line_between_points(from, to, a, b, n)
             {
-                return vec![from, (a + b) * 0.5, to];
-            } else {
-                return vec![from, to];
+                points.push((a + b) * 0.5);
             }
+            points.push(to);
+            return;
         }
         let mut start = from;
         let mut last_normal = self.spatials[triangles[0]].normal();
-        let mut points = Vec::with_capacity(triangles.len() + 1);
+        points.reserve(triangles.len() + 1);
         points.push(from);
         for triplets in triangles.windows(3) {
             let NavConnection(a, b) =
@@ -852,7 +1154,6 @@ impl NavMesh {
         }
         points.push(to);
         points.dedup();
-        points
     }
 
     /// Find shortest path on nav mesh between two points.
@@ -864,7 +1165,7 @@ impl NavMesh {
     /// * `mode` - path finding quality.
     ///
     /// # Returns
-    /// `Some` with path points on nav mesh and path length if found or `None` otherwise.
+    /// `Some` with path of triangles indices if found or `None` otherwise.
     ///
     /// # Example
     /// ```
@@ -886,11 +1187,11 @@ impl NavMesh {
     /// ];
     ///
     /// let mesh = NavMesh::new(vertices, triangles).unwrap();
-    /// let path = mesh.find_path_triangles(1, 2).unwrap().0;
-    /// assert_eq!(path, vec![1, 0, 3, 2]);
+    /// let path = mesh.find_path_triangles(1, 2).unwrap();
+    /// assert_eq!(path.points, vec![1, 0, 3, 2]);
     /// ```
     #[inline]
-    pub fn find_path_triangles(&self, from: usize, to: usize) -> Option<(Vec<usize>, Scalar)> {
+    pub fn find_path_triangles(&self, from: usize, to: usize) -> Option<NavPath<usize>> {
         self.find_path_triangles_custom(from, to, |_, _, _| true)
     }
 
@@ -901,11 +1202,12 @@ impl NavMesh {
     /// * `to` - query point to.
     /// * `query` - query quality.
     /// * `mode` - path finding quality.
-    /// * `filter` - closure that gives you a connection distance squared, first triangle index
-    ///   and second triangle index.
+    /// * `filter` - closure that gives you a connection distance squared (between triangles
+    ///   centers, while path costs are measured through shared edge midpoint), first triangle
+    ///   index and second triangle index. Connections it rejects cannot be traversed.
     ///
     /// # Returns
-    /// `Some` with path points on nav mesh and path length if found or `None` otherwise.
+    /// `Some` with path of triangles indices if found or `None` otherwise.
     ///
     /// # Example
     /// ```
@@ -931,49 +1233,240 @@ impl NavMesh {
     ///     1,
     ///     2,
     ///     |_dist_sqr, _first_idx, _second_idx| true
-    /// ).unwrap().0;
-    /// assert_eq!(path, vec![1, 0, 3, 2]);
+    /// ).unwrap();
+    /// assert_eq!(path.points, vec![1, 0, 3, 2]);
     /// ```
     #[inline]
     pub fn find_path_triangles_custom<F>(
         &self,
         from: usize,
         to: usize,
-        mut filter: F,
-    ) -> Option<(Vec<usize>, Scalar)>
+        filter: F,
+    ) -> Option<NavPath<usize>>
     where
         F: FnMut(Scalar, usize, usize) -> bool,
     {
-        let to = self.nodes[to];
-        astar(
-            &self.graph,
-            self.nodes[from],
-            |n| n == to,
-            |e| {
-                let a = self.nodes_map[&e.source()];
-                let b = self.nodes_map[&e.target()];
-                let w = *e.weight();
-                if filter(w, a, b) {
-                    let a = self.areas[a].cost;
-                    let b = self.areas[b].cost;
-                    w * a * b
-                } else {
-                    SCALAR_MAX
-                }
+        let mut context = NavSearchContext::default();
+        let (_, cost) = self.find_path_triangles_inner(
+            &mut context,
+            from,
+            &[to],
+            self.filter_cost(filter),
+            |_, _| 0.0,
+        )?;
+        Some(self.make_triangles_path(context.path, cost))
+    }
+
+    /// Find shortest path on nav mesh between two triangles, providing custom cost function.
+    ///
+    /// # Arguments
+    /// * `from` - triangle index from.
+    /// * `to` - triangle index to.
+    /// * `cost` - cost provider that gets triangles indices.
+    ///
+    /// # Returns
+    /// `Some` with path of triangles indices if found or `None` otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (2.0, 0.0, 1.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    ///     (1.0, 1.0, 0.0).into(), // 4
+    ///     (2.0, 1.0, 1.0).into(), // 5
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 4).into(), // 0
+    ///     (4, 3, 0).into(), // 1
+    ///     (1, 2, 5).into(), // 2
+    ///     (5, 4, 1).into(), // 3
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let path = mesh
+    ///     .find_path_triangles_with_cost(1, 2, &|_, _, base: Scalar| Some(base * 2.0))
+    ///     .unwrap();
+    /// assert_eq!(path.points, vec![1, 0, 3, 2]);
+    /// let base_path = mesh.find_path_triangles(1, 2).unwrap();
+    /// assert_eq!(path.cost, base_path.cost * 2.0);
+    /// assert_eq!(path.length, base_path.length);
+    /// ```
+    pub fn find_path_triangles_with_cost<C>(
+        &self,
+        from: usize,
+        to: usize,
+        cost: &C,
+    ) -> Option<NavPath<usize>>
+    where
+        C: NavCost<usize>,
+    {
+        let mut context = NavSearchContext::default();
+        self.find_path_triangles_with_context(&mut context, from, to, cost)
+            .map(|(triangles, cost)| self.make_triangles_path(triangles.to_vec(), cost))
+    }
+
+    /// Find shortest path on nav mesh between two triangles, reusing search context buffers.
+    ///
+    /// # Arguments
+    /// * `context` - reusable search context.
+    /// * `from` - triangle index from.
+    /// * `to` - triangle index to.
+    /// * `cost` - cost provider that gets triangles indices.
+    ///
+    /// # Returns
+    /// `Some` with triangles indices stored in search context and path length if found or
+    /// `None` otherwise.
+    pub fn find_path_triangles_with_context<'a, C>(
+        &self,
+        context: &'a mut NavSearchContext,
+        from: usize,
+        to: usize,
+        cost: &C,
+    ) -> Option<(&'a [usize], Scalar)>
+    where
+        C: NavCost<usize>,
+    {
+        let model = cost.cost_model().unwrap_or(self.cost_model);
+        let (_, cost) = self.find_path_triangles_inner(
+            context,
+            from,
+            &[to],
+            |a, b, _| cost.edge_cost(a, b, self.connection_cost(model, a, b)?),
+            |n, goal| cost.heuristic(n, goal).unwrap_or(0.0),
+        )?;
+        Some((&context.path, cost))
+    }
+
+    // triangles path length is measured between triangles centers.
+    fn make_triangles_path(&self, triangles: Vec<usize>, cost: Scalar) -> NavPath<usize> {
+        let length = path_length(&triangles, |a, b| {
+            (self.areas[*b].center - self.areas[*a].center).magnitude()
+        });
+        NavPath::new(triangles, cost, length)
+    }
+
+    fn find_path_triangles_inner<E, H>(
+        &self,
+        context: &mut NavSearchContext,
+        from: usize,
+        goals: &[usize],
+        edge_cost: E,
+        heuristic: H,
+    ) -> Option<(usize, Scalar)>
+    where
+        E: FnMut(usize, usize, Scalar) -> Option<Scalar>,
+        H: FnMut(usize, usize) -> Scalar,
+    {
+        search_graph(self, context, from, goals, edge_cost, heuristic)
+    }
+
+    // weight is squared distance between centers, so costs are measured along path going
+    // through shared edge midpoint instead, to keep them in distance units. `None` for triangles
+    // not sharing an edge.
+    #[inline]
+    fn connection_cost(&self, model: NavCostModel, a: usize, b: usize) -> Option<Scalar> {
+        let index = self.graph.find_edge(NodeIndex::new(a), NodeIndex::new(b))?;
+        let edge = &self.graph[index];
+        let (from, to) = if self.graph.edge_endpoints(index)?.0.index() == a {
+            (edge.source_length, edge.target_length)
+        } else {
+            (edge.target_length, edge.source_length)
+        };
+        Some(model.combine_split(from, to, self.areas[a].cost, self.areas[b].cost))
+    }
+
+    fn filter_cost<'a, F>(
+        &'a self,
+        mut filter: F,
+    ) -> impl FnMut(usize, usize, Scalar) -> Option<Scalar> + 'a
+    where
+        F: FnMut(Scalar, usize, usize) -> bool + 'a,
+    {
+        move |a, b, w| {
+            if filter(w, a, b) {
+                self.connection_cost(self.cost_model, a, b)
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Calculate distance field leading every triangle towards goal triangle.
+    ///
+    /// # Arguments
+    /// * `goal` - goal triangle index.
+    ///
+    /// # Returns
+    /// `Some` with distance field or `None` if goal triangle does not exist.
+    pub fn distance_field(&self, goal: usize) -> Option<NavDistanceField> {
+        if goal >= self.triangles.len() {
+            return None;
+        }
+        let mut context = NavSearchContext::default();
+        // search goes backwards from goal, so edge costs are taken in opposite direction.
+        dijkstra(
+            &mut context,
+            self.graph.node_count(),
+            goal,
+            |n| {
+                self.graph
+                    .edges(NodeIndex::new(n))
+                    .map(|e| (e.target().index(), e.weight().weight))
             },
-            |_| 0.0,
+            |a, b, _| self.connection_cost(self.cost_model, b, a),
+        );
+        let mut costs = Vec::with_capacity(self.triangles.len());
+        let mut next = Vec::with_capacity(self.triangles.len());
+        let mut directions = Vec::with_capacity(self.triangles.len());
+        for index in 0..self.triangles.len() {
+            match context.visited(index) {
+                Some((cost, parent)) if index != goal => {
+                    costs.push(Some(cost));
+                    next.push(Some(parent));
+                    directions
+                        .push((self.areas[parent].center - self.areas[index].center).normalize());
+                }
+                Some((cost, _)) => {
+                    costs.push(Some(cost));
+                    next.push(None);
+                    directions.push(NavVec3::default());
+                }
+                None => {
+                    costs.push(None);
+                    next.push(None);
+                    directions.push(NavVec3::default());
+                }
+            }
+        }
+        Some(NavDistanceField::new(goal, costs, next, directions))
+    }
+
+    /// Iterate over triangles sharing an edge with given triangle.
+    ///
+    /// # Arguments
+    /// * `index` - triangle index.
+    ///
+    /// # Returns
+    /// `Some` with neighbor triangles indices or `None` if triangle does not exist.
+    pub fn neighbors(&self, index: usize) -> Option<impl Iterator<Item = usize> + '_> {
+        if index >= self.triangles.len() {
+            return None;
+        }
+        Some(
+            self.graph
+                .neighbors(NodeIndex::new(index))
+                .map(|node| node.index()),
         )
-        .map(|(c, v)| (iter!(v).map(|v| self.nodes_map[v]).collect(), c))
     }
 
     pub fn find_triangle_islands(&self) -> Vec<Vec<usize>> {
         tarjan_scc(&self.graph)
             .into_iter()
-            .map(|v| {
-                v.into_iter()
-                    .filter_map(|n| self.nodes_map.get(&n).copied())
-                    .collect::<Vec<_>>()
-            })
+            .map(|v| v.into_iter().map(|n| n.index()).collect::<Vec<_>>())
             .filter(|v| !v.is_empty())
             .collect()
     }
@@ -1124,3 +1617,89 @@ impl NavMesh {
         }
     }
 }
+
+impl Serialize for NavMesh {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Self::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for NavMesh {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut result = Self::deserialize(deserializer)?;
+        result.graph = Self::build_graph(&result.vertices, &result.areas, &result.connections);
+        Ok(result)
+    }
+}
+
+impl NavGraph for NavMesh {
+    fn node_count(&self) -> usize {
+        self.triangles.len()
+    }
+
+    fn neighbors(&self, node: usize) -> impl Iterator<Item = (usize, Scalar)> + '_ {
+        self.graph
+            .edges(NodeIndex::new(node))
+            .map(|e| (e.target().index(), e.weight().weight))
+    }
+
+    fn edge_cost(&self, from: usize, to: usize, _weight: Scalar) -> Option<Scalar> {
+        self.connection_cost(self.cost_model, from, to)
+    }
+
+    fn node_position(&self, node: usize) -> Option<NavVec3> {
+        self.areas.get(node).map(|area| area.center)
+    }
+}
+
+impl Navigable for NavMesh {
+    type Node = usize;
+    type Point = NavVec3;
+
+    fn find_path_custom<F>(&self, from: NavVec3, to: NavVec3, filter: F) -> Option<NavPath<NavVec3>>
+    where
+        F: FnMut(usize, usize, Scalar) -> bool,
+    {
+        self.find_path_with_cost(
+            from,
+            to,
+            NavQuery::Accuracy,
+            NavPathMode::Accuracy,
+            &NavFilterCost::new(filter),
+        )
+    }
+
+    fn find_islands(&self) -> Vec<Vec<usize>> {
+        self.find_triangle_islands()
+    }
+
+    fn neighbors(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        self.neighbors(index).into_iter().flatten()
+    }
+
+    fn node_cost(&self, index: usize) -> Option<Scalar> {
+        self.areas.get(index).map(|area| area.cost)
+    }
+
+    fn set_node_cost(&mut self, index: usize, cost: Scalar) -> Option<Scalar> {
+        if index < self.areas.len() {
+            Some(self.set_area_cost(index, cost))
+        } else {
+            None
+        }
+    }
+
+    fn cost_model(&self) -> NavCostModel {
+        self.cost_model()
+    }
+
+    fn set_cost_model(&mut self, model: NavCostModel) {
+        self.set_cost_model(model);
+    }
+}
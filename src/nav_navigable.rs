@@ -0,0 +1,93 @@
+use crate::{NavCost, NavCostModel, NavPath, Scalar};
+use std::cell::RefCell;
+
+/// Common interface of navigation structures.
+///
+/// Lets behaviours and algorithms be written once against any of `NavMesh`, `NavNet`,
+/// `NavGrid`, `NavFreeGrid`, `NavChunkedGrid`, `NavHexGrid`, `NavVoxelGrid`, `NavOctree` and
+/// `NavIslands`. `Node` is the node type of navigation graph (triangle index for `NavMesh`,
+/// vertex index for `NavNet`, cell coordinate for grids, free leaf index for `NavOctree` and
+/// island portal for `NavIslands`) and `Point` is the type paths are found between and made of.
+/// `NavOctree` leaves have no costs - all of them cost 1 and cannot be changed.
+///
+/// Inherent methods of the same name take precedence, so when structure is used directly,
+/// trait methods that differ from them have to be called as `Navigable::find_path(&mesh, ..)`.
+///
+/// # Example
+/// ```
+/// use navmesh::*;
+///
+/// fn reachable<N: Navigable>(nav: &N, from: N::Point, to: N::Point) -> bool {
+///     nav.find_path(from, to).is_some()
+/// }
+///
+/// let grid = NavGrid::new(3, 1, vec![true, false, true]).unwrap();
+/// assert!(!reachable(&grid, (0, 0), (2, 0)));
+///
+/// let net = NavNet::new(
+///     vec![(0.0, 0.0, 0.0).into(), (1.0, 0.0, 0.0).into()],
+///     vec![NavConnection(0, 1)],
+/// )
+/// .unwrap();
+/// assert!(reachable(&net, (0.0, 0.0, 0.0).into(), (1.0, 0.0, 0.0).into()));
+/// ```
+pub trait Navigable {
+    type Node: Clone;
+    type Point: Clone;
+
+    /// Find shortest path between two points.
+    ///
+    /// `NavMesh` searches with `NavQuery::Accuracy` and `NavPathMode::Accuracy`.
+    fn find_path(&self, from: Self::Point, to: Self::Point) -> Option<NavPath<Self::Point>> {
+        self.find_path_custom(from, to, |_, _, _| true)
+    }
+
+    /// Find shortest path between two points, skipping connections rejected by filter.
+    ///
+    /// Filter params: first node, second node, connection cost.
+    fn find_path_custom<F>(
+        &self,
+        from: Self::Point,
+        to: Self::Point,
+        filter: F,
+    ) -> Option<NavPath<Self::Point>>
+    where
+        F: FnMut(Self::Node, Self::Node, Scalar) -> bool;
+
+    /// Groups of nodes connected with each other.
+    fn find_islands(&self) -> Vec<Vec<Self::Node>>;
+
+    /// Nodes that can be entered directly from given node (none if node does not exist).
+    fn neighbors(&self, node: Self::Node) -> impl Iterator<Item = Self::Node> + '_;
+
+    fn node_cost(&self, node: Self::Node) -> Option<Scalar>;
+
+    /// Set cost of node, returning old one or `None` if node does not exist.
+    fn set_node_cost(&mut self, node: Self::Node, cost: Scalar) -> Option<Scalar>;
+
+    fn cost_model(&self) -> NavCostModel;
+
+    fn set_cost_model(&mut self, model: NavCostModel);
+}
+
+// lets filters be passed to structures which custom filters do not get connection costs.
+pub(crate) struct NavFilterCost<F>(RefCell<F>);
+
+impl<F> NavFilterCost<F> {
+    pub fn new(filter: F) -> Self {
+        Self(RefCell::new(filter))
+    }
+}
+
+impl<T, F> NavCost<T> for NavFilterCost<F>
+where
+    F: FnMut(T, T, Scalar) -> bool,
+{
+    fn edge_cost(&self, from: T, to: T, base: Scalar) -> Option<Scalar> {
+        if (self.0.borrow_mut())(from, to, base) {
+            Some(base)
+        } else {
+            None
+        }
+    }
+}
@@ -1,19 +1,13 @@
-use crate::{Error, NavConnection, NavResult, NavVec3, Scalar};
-use petgraph::{
-    algo::{astar, tarjan_scc},
-    graph::NodeIndex,
-    visit::EdgeRef,
-    Graph, Undirected,
+use crate::{
+    nav_graph::search_graph, nav_navigable::NavFilterCost, nav_search::dijkstra, Error,
+    NavConnection, NavCost, NavCostModel, NavGraph, NavPath, NavResult, NavSearchContext, NavVec3,
+    Navigable, Scalar,
 };
+use petgraph::{algo::tarjan_scc, graph::NodeIndex, visit::EdgeRef, Graph, Undirected};
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use spade::{rtree::RTree, BoundingRect, SpatialObject};
-use std::collections::HashMap;
-#[cfg(not(feature = "scalar64"))]
-use std::f32::MAX as SCALAR_MAX;
-#[cfg(feature = "scalar64")]
-use std::f64::MAX as SCALAR_MAX;
 use typid::ID;
 
 #[cfg(feature = "parallel")]
@@ -78,16 +72,20 @@ impl SpatialObject for NavSpatialConnection {
 /// Nav net identifier.
 pub type NavNetID = ID<NavNet>;
 
+// serde impls are written by hand below to rebuild graph after loading.
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(remote = "Self")]
 pub struct NavNet {
     id: NavNetID,
     vertices: Vec<NavVec3>,
     connections: Vec<NavConnection>,
     distances: Vec<Scalar>,
     costs: Vec<Scalar>,
+    #[serde(default)]
+    cost_model: NavCostModel,
+    // rebuilt from connections after loading.
+    #[serde(skip)]
     graph: Graph<(), Scalar, Undirected>,
-    nodes: Vec<NodeIndex>,
-    nodes_map: HashMap<NodeIndex, usize>,
     rtree: RTree<NavSpatialConnection>,
     spatials: Vec<NavSpatialConnection>,
     origin: NavVec3,
@@ -118,17 +116,7 @@ impl NavNet {
 
         let costs = vec![1.0; vertices.len()];
 
-        let mut graph = Graph::<(), Scalar, Undirected>::new_undirected();
-        let nodes = (0..vertices.len())
-            .map(|_| graph.add_node(()))
-            .collect::<Vec<_>>();
-        graph.extend_with_edges(
-            iter!(connections)
-                .enumerate()
-                .map(|(i, conn)| (nodes[conn.0 as usize], nodes[conn.1 as usize], distances[i]))
-                .collect::<Vec<_>>(),
-        );
-        let nodes_map = iter!(nodes).enumerate().map(|(i, n)| (*n, i)).collect();
+        let graph = Self::build_graph(vertices.len(), &connections, &distances);
 
         let spatials = iter!(connections)
             .enumerate()
@@ -153,15 +141,34 @@ impl NavNet {
             connections,
             distances,
             costs,
+            cost_model: NavCostModel::default(),
             graph,
-            nodes,
-            nodes_map,
             rtree,
             spatials,
             origin,
         })
     }
 
+    // graph node indices are the same as vertices indices.
+    fn build_graph(
+        count: usize,
+        connections: &[NavConnection],
+        distances: &[Scalar],
+    ) -> Graph<(), Scalar, Undirected> {
+        let mut graph = Graph::<(), Scalar, Undirected>::with_capacity(count, connections.len());
+        for _ in 0..count {
+            graph.add_node(());
+        }
+        graph.extend_with_edges(
+            connections
+                .iter()
+                .zip(distances.iter())
+                .filter(|(conn, _)| (conn.0 as usize) < count && (conn.1 as usize) < count)
+                .map(|(conn, distance)| (conn.0, conn.1, *distance)),
+        );
+        graph
+    }
+
     pub fn scale(&self, value: NavVec3, origin: Option<NavVec3>) -> NavResult<Self> {
         let origin = origin.unwrap_or(self.origin);
         let vertices = iter!(self.vertices)
@@ -208,6 +215,16 @@ impl NavNet {
         Some(old)
     }
 
+    #[inline]
+    pub fn cost_model(&self) -> NavCostModel {
+        self.cost_model
+    }
+
+    #[inline]
+    pub fn set_cost_model(&mut self, model: NavCostModel) {
+        self.cost_model = model;
+    }
+
     pub fn closest_point(&self, point: NavVec3) -> Option<NavVec3> {
         let index = self.find_closest_connection(point)?;
         Some(self.spatials[index].closest_point(point))
@@ -217,110 +234,437 @@ impl NavNet {
         self.rtree.nearest_neighbor(&point).map(|c| c.index)
     }
 
-    pub fn find_path(&self, from: NavVec3, to: NavVec3) -> Option<Vec<NavVec3>> {
+    pub fn find_path(&self, from: NavVec3, to: NavVec3) -> Option<NavPath<NavVec3>> {
         self.find_path_custom(from, to, |_, _, _| true)
     }
 
-    // filter params: connection distance sqr, first vertex index, second vertex index.
+    // filter params: distance sqr of traversed part of connection, first vertex index, second
+    // vertex index. connections rejected by filter cannot be traversed.
     pub fn find_path_custom<F>(
         &self,
         from: NavVec3,
         to: NavVec3,
         mut filter: F,
-    ) -> Option<Vec<NavVec3>>
+    ) -> Option<NavPath<NavVec3>>
     where
         F: FnMut(Scalar, usize, usize) -> bool,
     {
-        let start_index = self.find_closest_connection(from)?;
-        let end_index = self.find_closest_connection(to)?;
-        let start_connection = self.connections[start_index];
-        let end_connection = self.connections[end_index];
-        let start_point = self.spatials[start_index].closest_point(from);
-        let end_point = self.spatials[end_index].closest_point(to);
-        if start_index == end_index {
-            return Some(vec![start_point, end_point]);
-        } else if start_point.same_as(end_point) {
-            return Some(vec![start_point]);
-        }
-        let start_vertice = {
-            let a = self.vertices[start_connection.0 as usize];
-            let b = self.vertices[start_connection.1 as usize];
-            if (a - start_point).sqr_magnitude() < (b - start_point).sqr_magnitude() {
-                start_connection.0 as usize
-            } else {
-                start_connection.1 as usize
-            }
-        };
-        let end_vertice = {
-            let a = self.vertices[end_connection.0 as usize];
-            let b = self.vertices[end_connection.1 as usize];
-            if (a - end_point).sqr_magnitude() < (b - end_point).sqr_magnitude() {
-                end_connection.0 as usize
-            } else {
-                end_connection.1 as usize
-            }
-        };
-        let start_node = *self.nodes.get(start_vertice)?;
-        let end_node = *self.nodes.get(end_vertice)?;
-        let nodes = astar(
-            &self.graph,
-            start_node,
-            |n| n == end_node,
-            |e| {
-                let a = self.nodes_map[&e.source()];
-                let b = self.nodes_map[&e.target()];
-                let w = *e.weight();
+        let mut context = NavSearchContext::default();
+        self.find_path_inner(
+            &mut context,
+            from,
+            to,
+            |a, b, w| {
                 if filter(w, a, b) {
-                    let a = self.costs[a];
-                    let b = self.costs[b];
-                    w * a * b
+                    Some(self.connection_cost(self.cost_model, a, b, w))
                 } else {
-                    SCALAR_MAX
+                    None
                 }
             },
-            |_| 0.0,
-        )?
-        .1;
-        let mut points = nodes
-            .into_iter()
-            .map(|n| self.vertices[self.nodes_map[&n]])
-            .collect::<Vec<_>>();
-        if points.len() > 2 {
-            {
-                let mut iter = points.iter();
-                let a = *iter.next()?;
-                let b = *iter.next()?;
-                let t = start_point.project(a, b);
-                if (0.0..=1.0).contains(&t) {
-                    points[0] = start_point;
-                } else {
-                    points.insert(0, start_point);
-                }
+            |_, _| 0.0,
+        )
+        .map(|(cost, points)| NavPath::from_points(points.to_vec(), cost))
+    }
+
+    // cost params: vertices indices.
+    pub fn find_path_with_cost<C>(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        cost: &C,
+    ) -> Option<NavPath<NavVec3>>
+    where
+        C: NavCost<usize>,
+    {
+        let mut context = NavSearchContext::default();
+        self.find_path_cost_inner(&mut context, from, to, cost)
+            .map(|(cost, points)| NavPath::from_points(points.to_vec(), cost))
+    }
+
+    // cost params: vertices indices.
+    pub fn find_path_with_context<'a, C>(
+        &self,
+        context: &'a mut NavSearchContext,
+        from: NavVec3,
+        to: NavVec3,
+        cost: &C,
+    ) -> Option<&'a [NavVec3]>
+    where
+        C: NavCost<usize>,
+    {
+        self.find_path_cost_inner(context, from, to, cost)
+            .map(|(_, points)| points)
+    }
+
+    // returns index of reached target and path to it. targets are compared by cost of whole
+    // path to them, including parts of connections they lie on.
+    pub fn find_path_to_any(
+        &self,
+        from: NavVec3,
+        targets: &[NavVec3],
+    ) -> Option<(usize, NavPath<NavVec3>)> {
+        let mut context = NavSearchContext::default();
+        let mut edge_cost = |a, b, w| Some(self.connection_cost(self.cost_model, a, b, w));
+        let start_index = self.find_closest_connection(from)?;
+        let start = (start_index, self.spatials[start_index].closest_point(from));
+        let start_vertice = self.closest_vertice(start_index, start.1);
+        dijkstra(
+            &mut context,
+            self.vertices.len(),
+            start_vertice,
+            |n| NavGraph::neighbors(self, n),
+            &mut edge_cost,
+        );
+        // (target index, end point, end connection vertice or `None` when going straight to
+        // target)
+        let mut best = None;
+        let mut best_cost = Scalar::INFINITY;
+        for (target, to) in targets.iter().enumerate() {
+            let end_index = match self.find_closest_connection(*to) {
+                Some(end_index) => end_index,
+                None => continue,
+            };
+            let end = (end_index, self.spatials[end_index].closest_point(*to));
+            let mut candidates = [None; 3];
+            if start.1.same_as(end.1) {
+                candidates[0] = Some((None, 0.0));
+            } else if start_index == end_index {
+                candidates[0] = self
+                    .direct_cost(&mut edge_cost, start_index, start.1, end.1)
+                    .map(|cost| (None, cost));
             }
-            {
-                let mut iter = points.iter().rev();
-                let a = *iter.next()?;
-                let b = *iter.next()?;
-                let t = end_point.project(a, b);
-                if (0.0..=1.0).contains(&t) {
-                    *points.last_mut()? = end_point;
-                } else {
-                    points.push(end_point);
+            let connection = self.connections[end_index];
+            for (slot, vertice) in [connection.0, connection.1].into_iter().enumerate() {
+                let vertice = vertice as usize;
+                candidates[slot + 1] = context.trace_path(vertice).and_then(|cost| {
+                    self.join_path_ends(&mut context, start, end, cost, &mut edge_cost)
+                        .map(|cost| (Some(vertice), cost))
+                });
+            }
+            for (vertice, cost) in candidates.into_iter().flatten() {
+                if cost < best_cost {
+                    best = Some((target, end, vertice));
+                    best_cost = cost;
                 }
             }
         }
-        Some(points)
+        let (target, end, vertice) = best?;
+        let points = match vertice {
+            Some(vertice) => {
+                let cost = context.trace_path(vertice)?;
+                self.join_path_ends(&mut context, start, end, cost, &mut edge_cost)?;
+                context.points
+            }
+            None if start.1.same_as(end.1) => vec![start.1],
+            None => vec![start.1, end.1],
+        };
+        Some((target, NavPath::from_points(points, best_cost)))
+    }
+
+    fn find_path_cost_inner<'a, C>(
+        &self,
+        context: &'a mut NavSearchContext,
+        from: NavVec3,
+        to: NavVec3,
+        cost: &C,
+    ) -> Option<(Scalar, &'a [NavVec3])>
+    where
+        C: NavCost<usize>,
+    {
+        let model = cost.cost_model().unwrap_or(self.cost_model);
+        self.find_path_inner(
+            context,
+            from,
+            to,
+            |a, b, w| cost.edge_cost(a, b, self.connection_cost(model, a, b, w)),
+            |n, goal| cost.heuristic(n, goal).unwrap_or(0.0),
+        )
+    }
+
+    // returns cost of path points and path points.
+    fn find_path_inner<'a, E, H>(
+        &self,
+        context: &'a mut NavSearchContext,
+        from: NavVec3,
+        to: NavVec3,
+        mut edge_cost: E,
+        heuristic: H,
+    ) -> Option<(Scalar, &'a [NavVec3])>
+    where
+        E: FnMut(usize, usize, Scalar) -> Option<Scalar>,
+        H: FnMut(usize, usize) -> Scalar,
+    {
+        let start_index = self.find_closest_connection(from)?;
+        let start_point = self.spatials[start_index].closest_point(from);
+        let end_index = self.find_closest_connection(to)?;
+        let end_point = self.spatials[end_index].closest_point(to);
+        if start_index == end_index || start_point.same_as(end_point) {
+            let cost = if start_point.same_as(end_point) {
+                0.0
+            } else {
+                self.direct_cost(&mut edge_cost, start_index, start_point, end_point)?
+            };
+            context.points.clear();
+            context.points.push(start_point);
+            if !start_point.same_as(end_point) {
+                context.points.push(end_point);
+            }
+            return Some((cost, &context.points));
+        }
+        let start_vertice = self.closest_vertice(start_index, start_point);
+        let end_vertice = self.closest_vertice(end_index, end_point);
+        let (_, cost) = search_graph(
+            self,
+            context,
+            start_vertice,
+            &[end_vertice],
+            &mut edge_cost,
+            heuristic,
+        )?;
+        let cost = self.join_path_ends(
+            context,
+            (start_index, start_point),
+            (end_index, end_point),
+            cost,
+            &mut edge_cost,
+        )?;
+        Some((cost, &context.points))
+    }
+
+    // cost of moving between two points lying on the same connection.
+    fn direct_cost<E>(
+        &self,
+        edge_cost: &mut E,
+        index: usize,
+        from: NavVec3,
+        to: NavVec3,
+    ) -> Option<Scalar>
+    where
+        E: FnMut(usize, usize, Scalar) -> Option<Scalar>,
+    {
+        let connection = self.connections[index];
+        let (a, b) = (connection.0 as usize, connection.1 as usize);
+        let distance = (to - from).sqr_magnitude();
+        let origin = self.vertices[a];
+        if (from - origin).sqr_magnitude() <= (to - origin).sqr_magnitude() {
+            edge_cost(a, b, distance)
+        } else {
+            edge_cost(b, a, distance)
+        }
+    }
+
+    // turns path between vertices stored in context into path points going from start point to
+    // end point (both given with connections they lie on). Path going along start or end
+    // connection gets cut at the point, otherwise the point is joined with part of its
+    // connection. Returns path cost updated with those changes.
+    fn join_path_ends<E>(
+        &self,
+        context: &mut NavSearchContext,
+        start: (usize, NavVec3),
+        end: (usize, NavVec3),
+        mut cost: Scalar,
+        edge_cost: &mut E,
+    ) -> Option<Scalar>
+    where
+        E: FnMut(usize, usize, Scalar) -> Option<Scalar>,
+    {
+        let NavSearchContext { path, points, .. } = context;
+        let (start_index, start_point) = start;
+        let (end_index, end_point) = end;
+        let count = path.len();
+        let first = *path.first()?;
+        let last = *path.last()?;
+        let cut_start = count > 1 && self.is_connection(start_index, first, path[1]);
+        let cut_end = count > 1
+            && !(cut_start && count == 2)
+            && self.is_connection(end_index, path[count - 2], last);
+        points.clear();
+        points.push(start_point);
+        if cut_start {
+            let next = path[1];
+            cost += edge_cost(
+                first,
+                next,
+                (self.vertices[next] - start_point).sqr_magnitude(),
+            )? - edge_cost(first, next, self.weight(first, next))?;
+        } else if !start_point.same_as(self.vertices[first]) {
+            let other = self.other_vertice(start_index, first);
+            cost += edge_cost(
+                other,
+                first,
+                (self.vertices[first] - start_point).sqr_magnitude(),
+            )?;
+        }
+        let inner = if cut_start { 1 } else { 0 }..if cut_end { count - 1 } else { count };
+        for index in &path[inner] {
+            let point = self.vertices[*index];
+            if !points.last()?.same_as(point) {
+                points.push(point);
+            }
+        }
+        if cut_end {
+            let previous = path[count - 2];
+            cost += edge_cost(
+                previous,
+                last,
+                (end_point - self.vertices[previous]).sqr_magnitude(),
+            )? - edge_cost(previous, last, self.weight(previous, last))?;
+        } else if !end_point.same_as(self.vertices[last]) {
+            let other = self.other_vertice(end_index, last);
+            cost += edge_cost(
+                last,
+                other,
+                (end_point - self.vertices[last]).sqr_magnitude(),
+            )?;
+        }
+        if !points.last()?.same_as(end_point) {
+            points.push(end_point);
+        }
+        Some(cost.max(0.0))
+    }
+
+    #[inline]
+    fn is_connection(&self, index: usize, a: usize, b: usize) -> bool {
+        let connection = self.connections[index];
+        let (c, d) = (connection.0 as usize, connection.1 as usize);
+        (c, d) == (a, b) || (c, d) == (b, a)
+    }
+
+    #[inline]
+    fn other_vertice(&self, index: usize, vertice: usize) -> usize {
+        let connection = self.connections[index];
+        if connection.0 as usize == vertice {
+            connection.1 as usize
+        } else {
+            connection.0 as usize
+        }
+    }
+
+    #[inline]
+    fn weight(&self, a: usize, b: usize) -> Scalar {
+        (self.vertices[b] - self.vertices[a]).sqr_magnitude()
+    }
+
+    fn closest_vertice(&self, index: usize, point: NavVec3) -> usize {
+        let connection = self.connections[index];
+        let a = self.vertices[connection.0 as usize];
+        let b = self.vertices[connection.1 as usize];
+        if (a - point).sqr_magnitude() < (b - point).sqr_magnitude() {
+            connection.0 as usize
+        } else {
+            connection.1 as usize
+        }
+    }
+
+    // weight is squared distance, while costs are measured in distance units.
+    #[inline]
+    fn connection_cost(&self, model: NavCostModel, a: usize, b: usize, weight: Scalar) -> Scalar {
+        model.combine(weight.sqrt(), self.costs[a], self.costs[b])
+    }
+
+    pub fn neighbors(&self, index: usize) -> Option<impl Iterator<Item = usize> + '_> {
+        if index >= self.vertices.len() {
+            return None;
+        }
+        Some(
+            self.graph
+                .neighbors(NodeIndex::new(index))
+                .map(|node| node.index()),
+        )
     }
 
     pub fn find_islands(&self) -> Vec<Vec<NavVec3>> {
+        self.find_vertice_islands()
+            .into_iter()
+            .map(|v| v.into_iter().map(|n| self.vertices[n]).collect::<Vec<_>>())
+            .collect()
+    }
+
+    pub fn find_vertice_islands(&self) -> Vec<Vec<usize>> {
         tarjan_scc(&self.graph)
             .into_iter()
-            .map(|v| {
-                v.into_iter()
-                    .filter_map(|n| self.nodes_map.get(&n).map(|i| self.vertices[*i]))
-                    .collect::<Vec<_>>()
-            })
+            .map(|v| v.into_iter().map(|n| n.index()).collect::<Vec<_>>())
             .filter(|v| !v.is_empty())
             .collect()
     }
 }
+
+impl Serialize for NavNet {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Self::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for NavNet {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut result = Self::deserialize(deserializer)?;
+        result.graph = Self::build_graph(
+            result.vertices.len(),
+            &result.connections,
+            &result.distances,
+        );
+        Ok(result)
+    }
+}
+
+impl NavGraph for NavNet {
+    fn node_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    fn neighbors(&self, node: usize) -> impl Iterator<Item = (usize, Scalar)> + '_ {
+        self.graph
+            .edges(NodeIndex::new(node))
+            .map(|e| (e.target().index(), *e.weight()))
+    }
+
+    fn edge_cost(&self, from: usize, to: usize, weight: Scalar) -> Option<Scalar> {
+        Some(self.connection_cost(self.cost_model, from, to, weight))
+    }
+
+    fn node_position(&self, node: usize) -> Option<NavVec3> {
+        self.vertices.get(node).copied()
+    }
+}
+
+impl Navigable for NavNet {
+    type Node = usize;
+    type Point = NavVec3;
+
+    fn find_path_custom<F>(&self, from: NavVec3, to: NavVec3, filter: F) -> Option<NavPath<NavVec3>>
+    where
+        F: FnMut(usize, usize, Scalar) -> bool,
+    {
+        self.find_path_with_cost(from, to, &NavFilterCost::new(filter))
+    }
+
+    fn find_islands(&self) -> Vec<Vec<usize>> {
+        self.find_vertice_islands()
+    }
+
+    fn neighbors(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        self.neighbors(index).into_iter().flatten()
+    }
+
+    fn node_cost(&self, index: usize) -> Option<Scalar> {
+        self.costs.get(index).copied()
+    }
+
+    fn set_node_cost(&mut self, index: usize, cost: Scalar) -> Option<Scalar> {
+        self.set_vertice_cost(index, cost)
+    }
+
+    fn cost_model(&self) -> NavCostModel {
+        self.cost_model()
+    }
+
+    fn set_cost_model(&mut self, model: NavCostModel) {
+        self.set_cost_model(model);
+    }
+}
@@ -0,0 +1,548 @@
+use crate::{
+    nav_graph::search_graph, nav_search::flood_islands, Error, NavCostModel, NavGraph, NavPath,
+    NavResult, NavSearchContext, NavTriangle, NavVec3, Navigable, Scalar, ZERO_TRESHOLD,
+};
+use petgraph::{graph::NodeIndex, visit::EdgeRef, Graph, Undirected};
+use serde::{Deserialize, Serialize};
+use typid::ID;
+
+/// Nav octree identifier.
+pub type NavOctreeID = ID<NavOctree>;
+
+/// Free space box of nav octree leaf.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NavOctreeNode {
+    pub min: NavVec3,
+    pub max: NavVec3,
+}
+
+impl NavOctreeNode {
+    #[inline]
+    pub fn center(&self) -> NavVec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    #[inline]
+    pub fn contains(&self, point: NavVec3) -> bool {
+        point.x >= self.min.x
+            && point.x <= self.max.x
+            && point.y >= self.min.y
+            && point.y <= self.max.y
+            && point.z >= self.min.z
+            && point.z <= self.max.z
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum NavOctreeCell {
+    // children cells indices, child index bits select upper half along X, Y and Z axis.
+    Branch([u32; 8]),
+    // free leaf node index.
+    Free(u32),
+    Blocked,
+}
+
+/// Sparse octree of free space for flying and swimming agents.
+///
+/// Bounds get subdivided only where obstacle triangles are, so open space is covered by few big
+/// leaves and only cells close to obstacles reach maximal depth. Cells of maximal depth that
+/// touch any triangle are blocked, the rest becomes graph of free leaves connected through
+/// shared faces.
+///
+/// # Example
+/// ```
+/// use navmesh::*;
+///
+/// // wall on X = 5 plane with hole above Z = 6.
+/// let vertices = vec![
+///     (5.0, -1.0, -1.0).into(),
+///     (5.0, 11.0, -1.0).into(),
+///     (5.0, 11.0, 6.0).into(),
+///     (5.0, -1.0, 6.0).into(),
+/// ];
+/// let triangles = vec![(0, 1, 2).into(), (2, 3, 0).into()];
+/// let octree = NavOctree::new(
+///     vertices,
+///     triangles,
+///     ((0.0, 0.0, 0.0).into(), (10.0, 10.0, 10.0).into()),
+///     5,
+///     0.0,
+/// )
+/// .unwrap();
+/// let path = octree
+///     .find_path((1.0, 5.0, 1.0).into(), (9.0, 5.0, 1.0).into())
+///     .unwrap()
+///     .points;
+/// assert_eq!(path.first(), Some(&(1.0, 5.0, 1.0).into()));
+/// assert_eq!(path.last(), Some(&(9.0, 5.0, 1.0).into()));
+/// assert!(path.iter().any(|point| point.z > 6.0));
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NavOctree {
+    id: NavOctreeID,
+    bounds: NavOctreeNode,
+    // root cell is the first one.
+    cells: Vec<NavOctreeCell>,
+    nodes: Vec<NavOctreeNode>,
+    // graph node indices are the same as nodes indices.
+    graph: Graph<(), Scalar, Undirected>,
+}
+
+impl NavOctree {
+    /// Create new nav octree from obstacle triangles.
+    ///
+    /// # Arguments
+    /// * `vertices` - list of obstacle vertices points.
+    /// * `triangles` - list of vertices indices that produces obstacle triangles.
+    /// * `bounds` - minimal and maximal corner of navigable volume.
+    /// * `max_depth` - maximal number of subdivisions of bounds.
+    /// * `margin` - distance from triangles at which cells become blocked (agent radius).
+    ///
+    /// # Returns
+    /// `Ok` with nav octree object or `Err` with `Error::TriangleVerticeIndexOutOfBounds` if
+    /// input data is invalid.
+    pub fn new(
+        vertices: Vec<NavVec3>,
+        triangles: Vec<NavTriangle>,
+        bounds: (NavVec3, NavVec3),
+        max_depth: usize,
+        margin: Scalar,
+    ) -> NavResult<Self> {
+        let triangles = triangles
+            .iter()
+            .enumerate()
+            .map(|(i, triangle)| {
+                let indices = [triangle.first, triangle.second, triangle.third];
+                let mut result = [NavVec3::default(); 3];
+                for (local, index) in indices.into_iter().enumerate() {
+                    result[local] = *vertices.get(index as usize).ok_or(
+                        Error::TriangleVerticeIndexOutOfBounds(i as u32, local as u8, index),
+                    )?;
+                }
+                Ok(result)
+            })
+            .collect::<NavResult<Vec<_>>>()?;
+        let bounds = NavOctreeNode {
+            min: bounds.0.min(bounds.1),
+            max: bounds.0.max(bounds.1),
+        };
+        let mut result = Self {
+            id: ID::default(),
+            bounds,
+            cells: vec![],
+            nodes: vec![],
+            graph: Graph::default(),
+        };
+        let all = (0..triangles.len()).collect::<Vec<_>>();
+        result.subdivide(bounds, &triangles, all, max_depth, margin.max(0.0));
+        for _ in 0..result.nodes.len() {
+            result.graph.add_node(());
+        }
+        let mut neighbors = vec![];
+        for index in 0..result.nodes.len() {
+            neighbors.clear();
+            result.find_face_neighbors(index, &mut neighbors);
+            for other in &neighbors {
+                let distance =
+                    (result.nodes[index].center() - result.nodes[*other].center()).magnitude();
+                result
+                    .graph
+                    .add_edge(NodeIndex::new(index), NodeIndex::new(*other), distance);
+            }
+        }
+        Ok(result)
+    }
+
+    #[inline]
+    pub fn id(&self) -> NavOctreeID {
+        self.id
+    }
+
+    #[inline]
+    pub fn bounds(&self) -> NavOctreeNode {
+        self.bounds
+    }
+
+    /// Free space leaves.
+    #[inline]
+    pub fn nodes(&self) -> &[NavOctreeNode] {
+        &self.nodes
+    }
+
+    pub fn neighbors(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        self.graph
+            .neighbors(NodeIndex::new(index))
+            .map(|node| node.index())
+    }
+
+    /// Free leaf containing given point or `None` if point is blocked or out of bounds.
+    pub fn find_node(&self, point: NavVec3) -> Option<usize> {
+        if !self.bounds.contains(point) {
+            return None;
+        }
+        let mut cell = 0;
+        let mut bounds = self.bounds;
+        loop {
+            match &self.cells[cell] {
+                NavOctreeCell::Branch(children) => {
+                    let center = bounds.center();
+                    let child = (point.x >= center.x) as usize
+                        | ((point.y >= center.y) as usize) << 1
+                        | ((point.z >= center.z) as usize) << 2;
+                    bounds = Self::child_bounds(bounds, child);
+                    cell = children[child] as usize;
+                }
+                NavOctreeCell::Free(index) => return Some(*index as usize),
+                NavOctreeCell::Blocked => return None,
+            }
+        }
+    }
+
+    /// Tells if segment between two points does not pass through blocked cells.
+    pub fn line_of_sight(&self, from: NavVec3, to: NavVec3) -> bool {
+        self.is_segment_free(0, self.bounds, from, to)
+    }
+
+    /// Find path between two points going through free leaves and pulled tight around
+    /// obstacles.
+    pub fn find_path(&self, from: NavVec3, to: NavVec3) -> Option<NavPath<NavVec3>> {
+        self.find_path_custom(from, to, |_, _, _| true)
+    }
+
+    // filter params: first leaf index, second leaf index, distance between leaves centers.
+    // connections rejected by filter cannot be traversed.
+    pub fn find_path_custom<F>(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        mut filter: F,
+    ) -> Option<NavPath<NavVec3>>
+    where
+        F: FnMut(usize, usize, Scalar) -> bool,
+    {
+        let start_index = self.find_node(from)?;
+        let end_index = self.find_node(to)?;
+        let mut context = NavSearchContext::default();
+        search_graph(
+            self,
+            &mut context,
+            start_index,
+            &[end_index],
+            |a, b, w| if filter(a, b, w) { Some(w) } else { None },
+            |n, goal| NavGraph::heuristic(self, n, goal),
+        )?;
+        // going through shared faces keeps every segment inside of free leaves.
+        let mut points = Vec::with_capacity(context.path.len() * 2 + 1);
+        points.push(from);
+        for pair in context.path.windows(2) {
+            let a = &self.nodes[pair[0]];
+            let b = &self.nodes[pair[1]];
+            points.push((a.min.max(b.min) + a.max.min(b.max)) * 0.5);
+            if pair[1] != end_index {
+                points.push(b.center());
+            }
+        }
+        points.push(to);
+        // nodes have no costs, so cost of pulled path is its length.
+        let mut path = NavPath::from_points(self.string_pull(&points), 0.0);
+        path.cost = path.length;
+        Some(path)
+    }
+
+    /// Groups of free leaves indices connected with each other.
+    pub fn find_islands(&self) -> Vec<Vec<usize>> {
+        flood_islands(self.nodes.len(), |_| true, |n| self.neighbors(n))
+    }
+
+    /// Remove points of path that can be skipped without passing through blocked cells.
+    pub fn string_pull(&self, points: &[NavVec3]) -> Vec<NavVec3> {
+        if points.len() < 3 {
+            return points.to_vec();
+        }
+        let mut result = vec![points[0]];
+        let mut current = 0;
+        while current < points.len() - 1 {
+            let next = (current + 2..points.len())
+                .rev()
+                .find(|next| self.line_of_sight(points[current], points[*next]))
+                .unwrap_or(current + 1);
+            result.push(points[next]);
+            current = next;
+        }
+        result
+    }
+
+    fn subdivide(
+        &mut self,
+        bounds: NavOctreeNode,
+        triangles: &[[NavVec3; 3]],
+        candidates: Vec<usize>,
+        depth: usize,
+        margin: Scalar,
+    ) -> usize {
+        let cell = self.cells.len();
+        let center = bounds.center();
+        let half = (bounds.max - bounds.min) * 0.5 + margin;
+        let touching = candidates
+            .into_iter()
+            .filter(|i| {
+                let [a, b, c] = triangles[*i];
+                triangle_box_overlap(center, half, a, b, c)
+            })
+            .collect::<Vec<_>>();
+        if touching.is_empty() {
+            self.cells
+                .push(NavOctreeCell::Free(self.nodes.len() as u32));
+            self.nodes.push(bounds);
+            return cell;
+        }
+        if depth == 0 {
+            self.cells.push(NavOctreeCell::Blocked);
+            return cell;
+        }
+        self.cells.push(NavOctreeCell::Branch([0; 8]));
+        let mut children = [0; 8];
+        for (child, result) in children.iter_mut().enumerate() {
+            let bounds = Self::child_bounds(bounds, child);
+            *result = self.subdivide(bounds, triangles, touching.clone(), depth - 1, margin) as u32;
+        }
+        self.cells[cell] = NavOctreeCell::Branch(children);
+        cell
+    }
+
+    fn child_bounds(bounds: NavOctreeNode, child: usize) -> NavOctreeNode {
+        let center = bounds.center();
+        let pick = |bit: usize, low: Scalar, mid: Scalar, high: Scalar| {
+            if child & bit == 0 {
+                (low, mid)
+            } else {
+                (mid, high)
+            }
+        };
+        let (min_x, max_x) = pick(1, bounds.min.x, center.x, bounds.max.x);
+        let (min_y, max_y) = pick(2, bounds.min.y, center.y, bounds.max.y);
+        let (min_z, max_z) = pick(4, bounds.min.z, center.z, bounds.max.z);
+        NavOctreeNode {
+            min: NavVec3::new(min_x, min_y, min_z),
+            max: NavVec3::new(max_x, max_y, max_z),
+        }
+    }
+
+    // free leaves touching faces of given leaf on its upper side along each axis, so every
+    // pair of neighbours is found once.
+    fn find_face_neighbors(&self, index: usize, result: &mut Vec<usize>) {
+        let node = self.nodes[index];
+        let threshold = ZERO_TRESHOLD * (self.bounds.max - self.bounds.min).magnitude();
+        for axis in 0..3 {
+            self.collect_face_neighbors(0, self.bounds, &node, axis, threshold, result);
+        }
+    }
+
+    fn collect_face_neighbors(
+        &self,
+        cell: usize,
+        bounds: NavOctreeNode,
+        node: &NavOctreeNode,
+        axis: usize,
+        threshold: Scalar,
+        result: &mut Vec<usize>,
+    ) {
+        let plane = component(node.max, axis);
+        if component(bounds.min, axis) > plane + threshold
+            || component(bounds.max, axis) < plane - threshold
+        {
+            return;
+        }
+        for other in (0..3).filter(|other| *other != axis) {
+            if component(bounds.min, other) >= component(node.max, other) - threshold
+                || component(bounds.max, other) <= component(node.min, other) + threshold
+            {
+                return;
+            }
+        }
+        match &self.cells[cell] {
+            NavOctreeCell::Branch(children) => {
+                for (child, index) in children.iter().enumerate() {
+                    let bounds = Self::child_bounds(bounds, child);
+                    self.collect_face_neighbors(
+                        *index as usize,
+                        bounds,
+                        node,
+                        axis,
+                        threshold,
+                        result,
+                    );
+                }
+            }
+            NavOctreeCell::Free(index) => {
+                if (component(bounds.min, axis) - plane).abs() <= threshold {
+                    result.push(*index as usize);
+                }
+            }
+            NavOctreeCell::Blocked => {}
+        }
+    }
+
+    fn is_segment_free(
+        &self,
+        cell: usize,
+        bounds: NavOctreeNode,
+        from: NavVec3,
+        to: NavVec3,
+    ) -> bool {
+        match &self.cells[cell] {
+            NavOctreeCell::Free(_) => true,
+            NavOctreeCell::Blocked => !segment_box_overlap(from, to, bounds.min, bounds.max),
+            NavOctreeCell::Branch(children) => {
+                if !segment_box_overlap(from, to, bounds.min, bounds.max) {
+                    return true;
+                }
+                children.iter().enumerate().all(|(child, index)| {
+                    let bounds = Self::child_bounds(bounds, child);
+                    self.is_segment_free(*index as usize, bounds, from, to)
+                })
+            }
+        }
+    }
+}
+
+// connection weights are distances between leaves centers.
+impl NavGraph for NavOctree {
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn neighbors(&self, node: usize) -> impl Iterator<Item = (usize, Scalar)> + '_ {
+        self.graph
+            .edges(NodeIndex::new(node))
+            .map(|e| (e.target().index(), *e.weight()))
+    }
+
+    fn heuristic(&self, node: usize, goal: usize) -> Scalar {
+        (self.nodes[goal].center() - self.nodes[node].center()).magnitude()
+    }
+
+    fn node_position(&self, node: usize) -> Option<NavVec3> {
+        self.nodes.get(node).map(|node| node.center())
+    }
+}
+
+// leaves have no costs, so every one of them costs 1 and costs cannot be changed.
+impl Navigable for NavOctree {
+    type Node = usize;
+    type Point = NavVec3;
+
+    fn find_path(&self, from: NavVec3, to: NavVec3) -> Option<NavPath<NavVec3>> {
+        self.find_path(from, to)
+    }
+
+    fn find_path_custom<F>(&self, from: NavVec3, to: NavVec3, filter: F) -> Option<NavPath<NavVec3>>
+    where
+        F: FnMut(usize, usize, Scalar) -> bool,
+    {
+        self.find_path_custom(from, to, filter)
+    }
+
+    fn find_islands(&self) -> Vec<Vec<usize>> {
+        self.find_islands()
+    }
+
+    fn neighbors(&self, node: usize) -> impl Iterator<Item = usize> + '_ {
+        (node < self.nodes.len())
+            .then(|| self.neighbors(node))
+            .into_iter()
+            .flatten()
+    }
+
+    fn node_cost(&self, node: usize) -> Option<Scalar> {
+        self.nodes.get(node).map(|_| 1.0)
+    }
+
+    fn set_node_cost(&mut self, _node: usize, _cost: Scalar) -> Option<Scalar> {
+        None
+    }
+
+    fn cost_model(&self) -> NavCostModel {
+        NavCostModel::default()
+    }
+
+    fn set_cost_model(&mut self, _model: NavCostModel) {}
+}
+
+#[inline]
+fn component(v: NavVec3, axis: usize) -> Scalar {
+    match axis {
+        0 => v.x,
+        1 => v.y,
+        _ => v.z,
+    }
+}
+
+// separating axis test of triangle and box given by center and half extents.
+fn triangle_box_overlap(
+    center: NavVec3,
+    half: NavVec3,
+    a: NavVec3,
+    b: NavVec3,
+    c: NavVec3,
+) -> bool {
+    let vertices = [a - center, b - center, c - center];
+    let separated = |axis: NavVec3| {
+        let radius = half.x * axis.x.abs() + half.y * axis.y.abs() + half.z * axis.z.abs();
+        let projections = vertices.map(|v| v.dot(axis));
+        let min = projections[0].min(projections[1]).min(projections[2]);
+        let max = projections[0].max(projections[1]).max(projections[2]);
+        min > radius || max < -radius
+    };
+    let units = [
+        NavVec3::new(1.0, 0.0, 0.0),
+        NavVec3::new(0.0, 1.0, 0.0),
+        NavVec3::new(0.0, 0.0, 1.0),
+    ];
+    if units.iter().any(|axis| separated(*axis)) {
+        return false;
+    }
+    let edges = [
+        vertices[1] - vertices[0],
+        vertices[2] - vertices[1],
+        vertices[0] - vertices[2],
+    ];
+    let normal = edges[0].cross(edges[1]);
+    if normal.sqr_magnitude() > 0.0 && separated(normal) {
+        return false;
+    }
+    for unit in &units {
+        for edge in &edges {
+            let axis = unit.cross(*edge);
+            if axis.sqr_magnitude() > 0.0 && separated(axis) {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+fn segment_box_overlap(from: NavVec3, to: NavVec3, min: NavVec3, max: NavVec3) -> bool {
+    let direction = to - from;
+    let mut enter: Scalar = 0.0;
+    let mut exit: Scalar = 1.0;
+    for axis in 0..3 {
+        let start = component(from, axis);
+        let delta = component(direction, axis);
+        let (low, high) = (component(min, axis), component(max, axis));
+        if delta.abs() < ZERO_TRESHOLD {
+            if start < low || start > high {
+                return false;
+            }
+        } else {
+            let a = (low - start) / delta;
+            let b = (high - start) / delta;
+            enter = enter.max(a.min(b));
+            exit = exit.min(a.max(b));
+            if enter > exit {
+                return false;
+            }
+        }
+    }
+    true
+}
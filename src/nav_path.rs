@@ -0,0 +1,79 @@
+use crate::{NavVec3, Scalar};
+use serde::{Deserialize, Serialize};
+
+/// Path found by navigation structure query.
+///
+/// `cost` is the value minimized by search, so it includes cells, areas and connections costs
+/// under cost model used by query, while `length` is plain geometric length of path (measured
+/// in cells for grids).
+///
+/// # Example
+/// ```
+/// use navmesh::*;
+///
+/// let mut grid = NavGrid::new(3, 1, vec![true; 3]).unwrap();
+/// grid.set_cell_cost(1, 0, 3.0);
+/// let path = grid.find_path((0, 0), (2, 0)).unwrap();
+/// assert_eq!(path.points, vec![(0, 0), (1, 0), (2, 0)]);
+/// assert_eq!(path.length, 2.0);
+/// assert_eq!(path.cost, 6.0);
+/// assert!(!path.partial);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NavPath<T> {
+    /// Path nodes from start to end.
+    pub points: Vec<T>,
+    pub cost: Scalar,
+    pub length: Scalar,
+    /// Tells if target could not be reached and path ends at node closest to it instead.
+    pub partial: bool,
+}
+
+impl<T> NavPath<T> {
+    pub fn new(points: Vec<T>, cost: Scalar, length: Scalar) -> Self {
+        Self {
+            points,
+            cost,
+            length,
+            partial: false,
+        }
+    }
+
+    /// Convert path nodes, keeping cost, length and partial flag.
+    pub fn map<U, F>(self, f: F) -> NavPath<U>
+    where
+        F: FnMut(T) -> U,
+    {
+        NavPath {
+            points: self.points.into_iter().map(f).collect(),
+            cost: self.cost,
+            length: self.length,
+            partial: self.partial,
+        }
+    }
+}
+
+impl NavPath<NavVec3> {
+    pub(crate) fn from_points(points: Vec<NavVec3>, cost: Scalar) -> Self {
+        let length = path_length(&points, |a, b| (*b - *a).magnitude());
+        Self::new(points, cost, length)
+    }
+}
+
+// sum of distances between consecutive path nodes.
+pub(crate) fn path_length<T, F>(points: &[T], mut distance: F) -> Scalar
+where
+    F: FnMut(&T, &T) -> Scalar,
+{
+    points
+        .windows(2)
+        .map(|pair| distance(&pair[0], &pair[1]))
+        .sum()
+}
+
+// euclidean distance between grid cells of given coordinates differences, in cells.
+#[inline]
+pub(crate) fn cells_distance(dx: usize, dy: usize) -> Scalar {
+    let (dx, dy) = (dx as Scalar, dy as Scalar);
+    (dx * dx + dy * dy).sqrt()
+}
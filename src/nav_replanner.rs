@@ -0,0 +1,302 @@
+use crate::{
+    nav_path::{cells_distance, path_length},
+    NavCostModel, NavGrid, NavGridID, NavPath, Scalar,
+};
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+// relative tolerance of keys comparison, so rounding errors of summed costs do not stop search
+// before cells lying on the path are repaired.
+const KEY_TOLERANCE: Scalar = 1e-4;
+
+type NavReplannerKey = (Scalar, Scalar);
+
+#[derive(Debug, Copy, Clone)]
+struct NavReplannerItem {
+    key: NavReplannerKey,
+    index: usize,
+}
+
+impl PartialEq for NavReplannerItem {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for NavReplannerItem {}
+
+impl PartialOrd for NavReplannerItem {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NavReplannerItem {
+    // reversed so binary heap pops item with smallest key first.
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare_keys(other.key, self.key)
+    }
+}
+
+fn compare_keys(a: NavReplannerKey, b: NavReplannerKey) -> Ordering {
+    a.0.partial_cmp(&b.0)
+        .unwrap_or(Ordering::Equal)
+        .then(a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+}
+
+// cells with keys tied with start cell key might still lie on the path, so they get repaired too.
+fn is_key_after(a: NavReplannerKey, b: NavReplannerKey) -> bool {
+    a.0 - b.0 > KEY_TOLERANCE * b.0.abs().max(1.0)
+}
+
+/// Incremental path planner over `NavGrid` (D* Lite).
+///
+/// Planner searches backward from goal and keeps its search state between queries, so after
+/// cells get edited with `NavGrid::set_cell_walkable` or `NavGrid::set_cell_cost` and planner
+/// is told about it with `cells_changed`, only the part of search affected by these changes is
+/// repaired. Start cell can move along the way (agent following its path) without losing
+/// search state.
+///
+/// # Example
+/// ```
+/// use navmesh::*;
+///
+/// let mut grid = NavGrid::new(5, 3, vec![true; 15]).unwrap();
+/// let mut planner = NavGridReplanner::new(&grid, (0, 1), (4, 1)).unwrap();
+/// assert_eq!(planner.find_path(&grid).unwrap().points.len(), 5);
+///
+/// // agent moves one cell and discovers obstacle in front of it.
+/// planner.set_from(&grid, (1, 1)).unwrap();
+/// grid.set_cell_walkable(2, 1, false);
+/// planner.cells_changed(&grid, &[(2, 1)]);
+/// let path = planner.find_path(&grid).unwrap().points;
+/// assert_eq!(path.first(), Some(&(1, 1)));
+/// assert_eq!(path.last(), Some(&(4, 1)));
+/// assert_eq!(path.len(), 6);
+/// ```
+#[derive(Debug, Clone)]
+pub struct NavGridReplanner {
+    grid: NavGridID,
+    cols: usize,
+    from: usize,
+    to: usize,
+    // start cell used when keys modifier was last updated.
+    last_from: usize,
+    // keys modifier accumulating heuristic changes caused by start cell moves.
+    modifier: Scalar,
+    cost_model: NavCostModel,
+    heuristic_scale: Scalar,
+    // cost of reaching goal from cell.
+    costs: Vec<Scalar>,
+    // one step lookahead cost of reaching goal from cell.
+    lookahead: Vec<Scalar>,
+    // key of every open cell, used to skip outdated heap items.
+    keys: Vec<Option<NavReplannerKey>>,
+    open: BinaryHeap<NavReplannerItem>,
+    affected: Vec<usize>,
+}
+
+impl NavGridReplanner {
+    /// Create planner of path between two cells of given grid.
+    pub fn new(grid: &NavGrid, from: (usize, usize), to: (usize, usize)) -> Option<Self> {
+        let from = grid.index(from.0, from.1)?;
+        let to = grid.index(to.0, to.1)?;
+        let mut result = Self {
+            grid: grid.id(),
+            cols: grid.cols(),
+            from,
+            to,
+            last_from: from,
+            modifier: 0.0,
+            cost_model: grid.cost_model(),
+            heuristic_scale: grid.heuristic_scale(),
+            costs: vec![],
+            lookahead: vec![],
+            keys: vec![],
+            open: BinaryHeap::new(),
+            affected: vec![],
+        };
+        result.reset(grid);
+        Some(result)
+    }
+
+    #[inline]
+    pub fn from(&self) -> (usize, usize) {
+        (self.from % self.cols, self.from / self.cols)
+    }
+
+    #[inline]
+    pub fn to(&self) -> (usize, usize) {
+        (self.to % self.cols, self.to / self.cols)
+    }
+
+    /// Move start cell, keeping search state. Returns `None` if cell is out of grid or grid is
+    /// not the one planner was made for.
+    pub fn set_from(&mut self, grid: &NavGrid, from: (usize, usize)) -> Option<()> {
+        if grid.id() != self.grid {
+            return None;
+        }
+        let from = grid.index(from.0, from.1)?;
+        if from != self.from {
+            self.from = from;
+            self.modifier += self.heuristic(grid, self.last_from, from);
+            self.last_from = from;
+        }
+        Some(())
+    }
+
+    /// Tell planner that walkability or cost of given cells has changed. Does nothing if grid is
+    /// not the one planner was made for.
+    pub fn cells_changed(&mut self, grid: &NavGrid, cells: &[(usize, usize)]) {
+        if grid.id() != self.grid {
+            return;
+        }
+        if self.needs_reset(grid) {
+            self.reset(grid);
+            return;
+        }
+        let mut affected = std::mem::take(&mut self.affected);
+        affected.clear();
+        for (col, row) in cells {
+            if let Some(index) = grid.index(*col, *row) {
+                grid.affected_cells(index, &mut affected);
+            }
+        }
+        for index in &affected {
+            self.update_cell(grid, *index);
+        }
+        self.affected = affected;
+    }
+
+    /// Find path from start cell to goal cell, repairing search state where it is needed.
+    ///
+    /// Returns `None` if goal cannot be reached or grid is not the one planner was made for.
+    pub fn find_path(&mut self, grid: &NavGrid) -> Option<NavPath<(usize, usize)>> {
+        if grid.id() != self.grid {
+            return None;
+        }
+        if self.needs_reset(grid) {
+            self.reset(grid);
+        }
+        if !grid.cells()[self.from] || !grid.cells()[self.to] {
+            return None;
+        }
+        self.compute(grid);
+        if self.costs[self.from] == Scalar::INFINITY {
+            return None;
+        }
+        let mut result = Vec::with_capacity(16);
+        let mut current = self.from;
+        result.push(self.from());
+        while current != self.to {
+            let (next, cost) = grid
+                .successors(current)
+                .map(|(index, cost)| (index, cost + self.costs[index]))
+                .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))?;
+            if cost == Scalar::INFINITY || result.len() > self.costs.len() {
+                return None;
+            }
+            current = next;
+            result.push((current % self.cols, current / self.cols));
+        }
+        let length = path_length(&result, |a, b| {
+            cells_distance(a.0.abs_diff(b.0), a.1.abs_diff(b.1))
+        });
+        Some(NavPath::new(result, self.costs[self.from], length))
+    }
+
+    // heuristic scale depends on cell costs so it has to stay admissible after edits.
+    fn needs_reset(&self, grid: &NavGrid) -> bool {
+        grid.cost_model() != self.cost_model || grid.heuristic_scale() < self.heuristic_scale
+    }
+
+    fn reset(&mut self, grid: &NavGrid) {
+        let count = grid.cells().len();
+        self.last_from = self.from;
+        self.modifier = 0.0;
+        self.cost_model = grid.cost_model();
+        self.heuristic_scale = grid.heuristic_scale();
+        self.costs.clear();
+        self.costs.resize(count, Scalar::INFINITY);
+        self.lookahead.clear();
+        self.lookahead.resize(count, Scalar::INFINITY);
+        self.keys.clear();
+        self.keys.resize(count, None);
+        self.open.clear();
+        self.lookahead[self.to] = 0.0;
+        self.push_open(grid, self.to);
+    }
+
+    fn compute(&mut self, grid: &NavGrid) {
+        while let Some(item) = self.open.pop() {
+            if self.keys[item.index] != Some(item.key) {
+                continue;
+            }
+            let from_key = self.key(grid, self.from);
+            if is_key_after(item.key, from_key)
+                && self.lookahead[self.from] == self.costs[self.from]
+            {
+                self.open.push(item);
+                break;
+            }
+            let index = item.index;
+            let key = self.key(grid, index);
+            if compare_keys(item.key, key) == Ordering::Less {
+                self.push_open(grid, index);
+                continue;
+            }
+            self.keys[index] = None;
+            if self.costs[index] > self.lookahead[index] {
+                self.costs[index] = self.lookahead[index];
+            } else {
+                self.costs[index] = Scalar::INFINITY;
+                self.update_cell(grid, index);
+            }
+            let mut affected = std::mem::take(&mut self.affected);
+            affected.clear();
+            affected.extend(grid.predecessors(index));
+            for other in &affected {
+                self.update_cell(grid, *other);
+            }
+            self.affected = affected;
+        }
+    }
+
+    fn update_cell(&mut self, grid: &NavGrid, index: usize) {
+        if index != self.to {
+            self.lookahead[index] = grid
+                .successors(index)
+                .map(|(other, cost)| cost + self.costs[other])
+                .fold(Scalar::INFINITY, Scalar::min);
+        }
+        if self.costs[index] != self.lookahead[index] {
+            self.push_open(grid, index);
+        } else {
+            self.keys[index] = None;
+        }
+    }
+
+    fn push_open(&mut self, grid: &NavGrid, index: usize) {
+        let key = self.key(grid, index);
+        self.keys[index] = Some(key);
+        self.open.push(NavReplannerItem { key, index });
+    }
+
+    fn key(&self, grid: &NavGrid, index: usize) -> NavReplannerKey {
+        let cost = self.costs[index].min(self.lookahead[index]);
+        (
+            cost + self.heuristic(grid, self.from, index) + self.modifier,
+            cost,
+        )
+    }
+
+    fn heuristic(&self, grid: &NavGrid, a: usize, b: usize) -> Scalar {
+        match grid.connectivity() {
+            Some(connectivity) => {
+                let a = (a % self.cols, a / self.cols);
+                let b = (b % self.cols, b / self.cols);
+                connectivity.distance(a, b) * self.heuristic_scale
+            }
+            None => 0.0,
+        }
+    }
+}
@@ -0,0 +1,467 @@
+use crate::{nav_mesh::NavMeshPathNode, NavVec3, Scalar};
+
+const CLOSED: usize = usize::MAX;
+/// Goal node index of target that cannot be reached.
+pub(crate) const NO_GOAL: usize = usize::MAX;
+
+/// Reusable path finding scratch buffers.
+///
+/// Searches performed through `*_with_context` methods keep their node arrays, open list and
+/// result paths inside this object, so once it grows to the size of the biggest queried
+/// navigation structure no further allocations are made. Keep one context per thread and pass
+/// it into every query.
+///
+/// Node states are stamped with search generation, so starting new search does not need to
+/// clear node arrays - bumping generation invalidates all states left by previous search.
+///
+/// # Example
+/// ```
+/// use navmesh::*;
+///
+/// let grid = NavGrid::new(3, 3, vec![true; 9]).unwrap();
+/// let mut context = NavSearchContext::default();
+/// let path = grid
+///     .find_path_with_context(&mut context, (0, 0), (2, 0), &NavDefaultCost)
+///     .unwrap()
+///     .collect::<Vec<_>>();
+/// assert_eq!(path, vec![(0, 0), (1, 0), (2, 0)]);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct NavSearchContext {
+    pub(crate) nodes: Vec<NavSearchNode>,
+    pub(crate) generation: u32,
+    // binary heap of node indices ordered by node estimate.
+    pub(crate) open: Vec<usize>,
+    pub(crate) path: Vec<usize>,
+    pub(crate) points: Vec<NavVec3>,
+    pub(crate) mesh_nodes: Vec<NavMeshPathNode>,
+    // goal node index of every target of multi-goal search.
+    pub(crate) goals: Vec<usize>,
+}
+
+impl NavSearchContext {
+    /// Create search context with buffers preallocated for given nodes count.
+    ///
+    /// # Arguments
+    /// * `count` - number of nodes (triangles, vertices, cells or portals) of biggest queried
+    ///   navigation structure.
+    pub fn with_capacity(count: usize) -> Self {
+        Self {
+            nodes: Vec::with_capacity(count),
+            generation: 0,
+            open: Vec::with_capacity(count),
+            path: Vec::with_capacity(count),
+            points: Vec::with_capacity(count),
+            mesh_nodes: Vec::with_capacity(count),
+            goals: vec![],
+        }
+    }
+
+    /// Node indices of last found path.
+    #[inline]
+    pub fn path(&self) -> &[usize] {
+        &self.path
+    }
+
+    fn reset(&mut self, count: usize) {
+        if self.nodes.len() < count {
+            self.nodes.resize(count, NavSearchNode::default());
+        }
+        self.generation = self.generation.wrapping_add(1);
+        if self.generation == 0 {
+            // generation wrapped around so old stamps might look valid again.
+            for node in &mut self.nodes {
+                node.generation = 0;
+            }
+            self.generation = 1;
+        }
+        self.open.clear();
+        self.path.clear();
+    }
+
+    // cost and parent of node visited by last search.
+    pub(crate) fn visited(&self, index: usize) -> Option<(Scalar, usize)> {
+        let node = self.nodes.get(index)?;
+        if node.generation == self.generation {
+            Some((node.cost, node.parent))
+        } else {
+            None
+        }
+    }
+
+    // stores path from start of last search to node visited by it, returning its cost.
+    pub(crate) fn trace_path(&mut self, index: usize) -> Option<Scalar> {
+        let (cost, mut parent) = self.visited(index)?;
+        self.path.clear();
+        self.path.push(index);
+        let mut index = index;
+        while parent != index {
+            index = parent;
+            self.path.push(index);
+            parent = self.visited(index)?.1;
+        }
+        self.path.reverse();
+        Some(cost)
+    }
+
+    #[inline]
+    fn is_visited(&self, index: usize) -> bool {
+        self.nodes[index].generation == self.generation
+    }
+
+    fn push_open(&mut self, index: usize) {
+        self.open.push(index);
+        self.nodes[index].open_index = self.open.len() - 1;
+        self.sift_up(self.open.len() - 1);
+    }
+
+    fn pop_open(&mut self) -> Option<usize> {
+        if self.open.is_empty() {
+            return None;
+        }
+        let result = self.open.swap_remove(0);
+        if !self.open.is_empty() {
+            self.nodes[self.open[0]].open_index = 0;
+            self.sift_down(0);
+        }
+        self.nodes[result].open_index = CLOSED;
+        Some(result)
+    }
+
+    fn sift_up(&mut self, mut position: usize) {
+        let index = self.open[position];
+        let estimate = self.nodes[index].estimate;
+        while position > 0 {
+            let parent = (position - 1) / 2;
+            let parent_index = self.open[parent];
+            if self.nodes[parent_index].estimate <= estimate {
+                break;
+            }
+            self.open[position] = parent_index;
+            self.nodes[parent_index].open_index = position;
+            position = parent;
+        }
+        self.open[position] = index;
+        self.nodes[index].open_index = position;
+    }
+
+    fn sift_down(&mut self, mut position: usize) {
+        let index = self.open[position];
+        let estimate = self.nodes[index].estimate;
+        loop {
+            let left = position * 2 + 1;
+            if left >= self.open.len() {
+                break;
+            }
+            let right = left + 1;
+            let child = if right < self.open.len()
+                && self.nodes[self.open[right]].estimate < self.nodes[self.open[left]].estimate
+            {
+                right
+            } else {
+                left
+            };
+            let child_index = self.open[child];
+            if estimate <= self.nodes[child_index].estimate {
+                break;
+            }
+            self.open[position] = child_index;
+            self.nodes[child_index].open_index = position;
+            position = child;
+        }
+        self.open[position] = index;
+        self.nodes[index].open_index = position;
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+pub(crate) struct NavSearchNode {
+    generation: u32,
+    cost: Scalar,
+    estimate: Scalar,
+    parent: usize,
+    // position in open list or `CLOSED` when node got expanded.
+    open_index: usize,
+}
+
+/// A* search over nodes identified by dense indices in range `0..count`.
+///
+/// `neighbors` yields pairs of (node index, edge weight), `cost` turns edge weight into
+/// traversal cost (`None` makes edge impassable) and `heuristic` estimates cost to the goal.
+/// Neighbours outside of `0..count` range are skipped. Found path is stored in `context.path`.
+pub(crate) fn astar<N, I, C, H, G>(
+    context: &mut NavSearchContext,
+    count: usize,
+    start: usize,
+    is_goal: G,
+    neighbors: N,
+    cost: C,
+    heuristic: H,
+) -> Option<Scalar>
+where
+    N: Fn(usize) -> I,
+    I: Iterator<Item = (usize, Scalar)>,
+    C: FnMut(usize, usize, Scalar) -> Option<Scalar>,
+    H: FnMut(usize) -> Scalar,
+    G: FnMut(usize) -> bool,
+{
+    astar_pruned(
+        context,
+        count,
+        start,
+        is_goal,
+        |n, _| neighbors(n),
+        cost,
+        heuristic,
+    )
+}
+
+/// A* search where `neighbors` gets both node index and index of its parent (which is the node
+/// itself for start node), so successors can be pruned based on direction of travel.
+pub(crate) fn astar_pruned<N, I, C, H, G>(
+    context: &mut NavSearchContext,
+    count: usize,
+    start: usize,
+    mut is_goal: G,
+    neighbors: N,
+    mut cost: C,
+    mut heuristic: H,
+) -> Option<Scalar>
+where
+    N: Fn(usize, usize) -> I,
+    I: Iterator<Item = (usize, Scalar)>,
+    C: FnMut(usize, usize, Scalar) -> Option<Scalar>,
+    H: FnMut(usize) -> Scalar,
+    G: FnMut(usize) -> bool,
+{
+    if start >= count {
+        return None;
+    }
+    context.reset(count);
+    context.nodes[start] = NavSearchNode {
+        generation: context.generation,
+        cost: 0.0,
+        estimate: heuristic(start),
+        parent: start,
+        open_index: 0,
+    };
+    context.push_open(start);
+    while let Some(index) = context.pop_open() {
+        let node_cost = context.nodes[index].cost;
+        if is_goal(index) {
+            let mut index = index;
+            context.path.push(index);
+            while index != start {
+                index = context.nodes[index].parent;
+                context.path.push(index);
+            }
+            context.path.reverse();
+            return Some(node_cost);
+        }
+        let parent = context.nodes[index].parent;
+        for (next, weight) in neighbors(index, parent) {
+            if next >= count {
+                continue;
+            }
+            let visited = context.is_visited(next);
+            if visited && context.nodes[next].open_index == CLOSED {
+                continue;
+            }
+            let weight = match cost(index, next, weight) {
+                Some(weight) => weight,
+                None => continue,
+            };
+            let next_cost = node_cost + weight;
+            if !visited {
+                context.nodes[next] = NavSearchNode {
+                    generation: context.generation,
+                    cost: next_cost,
+                    estimate: next_cost + heuristic(next),
+                    parent: index,
+                    open_index: 0,
+                };
+                context.push_open(next);
+            } else if next_cost < context.nodes[next].cost {
+                let node = &mut context.nodes[next];
+                node.estimate += next_cost - node.cost;
+                node.cost = next_cost;
+                node.parent = index;
+                let position = node.open_index;
+                context.sift_up(position);
+            }
+        }
+    }
+    None
+}
+
+/// Groups of nodes connected with each other, flooded from every node accepted by `is_node`.
+///
+/// `neighbors` has to be symmetric (node is neighbour of its neighbours).
+pub(crate) fn flood_islands<A, N, I>(count: usize, mut is_node: A, neighbors: N) -> Vec<Vec<usize>>
+where
+    A: FnMut(usize) -> bool,
+    N: Fn(usize) -> I,
+    I: Iterator<Item = usize>,
+{
+    let mut visited = vec![false; count];
+    let mut stack = vec![];
+    let mut result = vec![];
+    for start in 0..count {
+        if visited[start] || !is_node(start) {
+            continue;
+        }
+        visited[start] = true;
+        stack.push(start);
+        let mut island = vec![];
+        while let Some(index) = stack.pop() {
+            island.push(index);
+            for next in neighbors(index) {
+                if next < count && !visited[next] {
+                    visited[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+        result.push(island);
+    }
+    result
+}
+
+/// Dijkstra search that visits every node reachable from `start`.
+///
+/// Costs and parents of visited nodes can be read with `NavSearchContext::visited`.
+pub(crate) fn dijkstra<N, I, C>(
+    context: &mut NavSearchContext,
+    count: usize,
+    start: usize,
+    neighbors: N,
+    cost: C,
+) where
+    N: Fn(usize) -> I,
+    I: Iterator<Item = (usize, Scalar)>,
+    C: FnMut(usize, usize, Scalar) -> Option<Scalar>,
+{
+    astar(context, count, start, |_| false, neighbors, cost, |_| 0.0);
+}
+
+/// A* search that terminates at first reached node of `goals` list.
+///
+/// `heuristic` gets node index and goal node index - for multiple goals the lowest estimate is
+/// used. Entries equal to `NO_GOAL` are skipped. Returns position of reached goal in `goals`
+/// list and path cost.
+pub(crate) fn astar_any<N, I, C, H>(
+    context: &mut NavSearchContext,
+    count: usize,
+    start: usize,
+    goals: &[usize],
+    neighbors: N,
+    cost: C,
+    mut heuristic: H,
+) -> Option<(usize, Scalar)>
+where
+    N: Fn(usize) -> I,
+    I: Iterator<Item = (usize, Scalar)>,
+    C: FnMut(usize, usize, Scalar) -> Option<Scalar>,
+    H: FnMut(usize, usize) -> Scalar,
+{
+    if goals.iter().all(|goal| *goal == NO_GOAL) {
+        return None;
+    }
+    let cost = astar(
+        context,
+        count,
+        start,
+        |n| goals.contains(&n),
+        neighbors,
+        cost,
+        |n| {
+            goals
+                .iter()
+                .filter(|goal| **goal != NO_GOAL)
+                .map(|goal| heuristic(n, *goal))
+                .fold(Scalar::MAX, Scalar::min)
+        },
+    )?;
+    let last = *context.path.last()?;
+    let winner = goals.iter().position(|goal| *goal == last)?;
+    Some((winner, cost))
+}
+
+/// Theta* search - successor takes parent of expanded node as its own parent whenever there is
+/// line of sight between them, so found path is not limited to graph edges.
+///
+/// `distance` is used both as traversal cost and heuristic. Path waypoints are stored in
+/// `context.path`.
+pub(crate) fn theta_star<N, I, L, D>(
+    context: &mut NavSearchContext,
+    count: usize,
+    start: usize,
+    goal: usize,
+    neighbors: N,
+    mut line_of_sight: L,
+    mut distance: D,
+) -> Option<Scalar>
+where
+    N: Fn(usize) -> I,
+    I: Iterator<Item = usize>,
+    L: FnMut(usize, usize) -> bool,
+    D: FnMut(usize, usize) -> Scalar,
+{
+    if start >= count || goal >= count {
+        return None;
+    }
+    context.reset(count);
+    context.nodes[start] = NavSearchNode {
+        generation: context.generation,
+        cost: 0.0,
+        estimate: distance(start, goal),
+        parent: start,
+        open_index: 0,
+    };
+    context.push_open(start);
+    while let Some(index) = context.pop_open() {
+        if index == goal {
+            let mut index = index;
+            context.path.push(index);
+            while index != start {
+                index = context.nodes[index].parent;
+                context.path.push(index);
+            }
+            context.path.reverse();
+            return Some(context.nodes[goal].cost);
+        }
+        let parent = context.nodes[index].parent;
+        for next in neighbors(index) {
+            if next >= count {
+                continue;
+            }
+            let visited = context.is_visited(next);
+            if visited && context.nodes[next].open_index == CLOSED {
+                continue;
+            }
+            let (from, next_cost) = if parent != index && line_of_sight(parent, next) {
+                (parent, context.nodes[parent].cost + distance(parent, next))
+            } else {
+                (index, context.nodes[index].cost + distance(index, next))
+            };
+            if !visited {
+                context.nodes[next] = NavSearchNode {
+                    generation: context.generation,
+                    cost: next_cost,
+                    estimate: next_cost + distance(next, goal),
+                    parent: from,
+                    open_index: 0,
+                };
+                context.push_open(next);
+            } else if next_cost < context.nodes[next].cost {
+                let node = &mut context.nodes[next];
+                node.estimate += next_cost - node.cost;
+                node.cost = next_cost;
+                node.parent = from;
+                let position = node.open_index;
+                context.sift_up(position);
+            }
+        }
+    }
+    None
+}
@@ -129,7 +129,7 @@ impl NavVec3 {
 
     pub fn raycast_line(from: Self, to: Self, a: Self, b: Self, normal: Self) -> Option<Self> {
         let p = Self::raycast_plane(from, to, a, normal)?;
-        let t = p.project(a, b).max(0.0).min(1.0);
+        let t = p.project(a, b).clamp(0.0, 1.0);
         Some(Self::unproject(a, b, t))
     }
 
@@ -0,0 +1,663 @@
+use crate::{
+    nav_graph::search_graph, nav_path::path_length, nav_search::flood_islands, Error, NavCost,
+    NavCostModel, NavGraph, NavGridTransform, NavPath, NavResult, NavSearchContext, NavVec3,
+    Navigable, Scalar, SCALAR_SQRT_2,
+};
+use serde::{Deserialize, Serialize};
+use typid::ID;
+
+// offsets of all 26 neighbours, connectivity decides how many axes they can change at once.
+const OFFSETS: [(isize, isize, isize); 26] = {
+    let mut result = [(0, 0, 0); 26];
+    let mut index = 0;
+    let mut i = 0;
+    while i < 27 {
+        if i != 13 {
+            result[index] = (
+                i as isize % 3 - 1,
+                i as isize / 3 % 3 - 1,
+                i as isize / 9 - 1,
+            );
+            index += 1;
+        }
+        i += 1;
+    }
+    result
+};
+
+/// Nav voxel grid identifier.
+pub type NavVoxelGridID = ID<NavVoxelGrid>;
+
+/// Neighbourhood of cells connected in `NavVoxelGrid`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NavVoxelConnectivity {
+    /// Neighbours sharing face.
+    #[default]
+    Six,
+    /// Neighbours sharing face or edge.
+    Eighteen,
+    /// Neighbours sharing face, edge or corner.
+    TwentySix,
+}
+
+impl NavVoxelConnectivity {
+    /// Shortest distance between cells when moving with this connectivity - manhattan distance
+    /// for `Six` and 3D octile distance for others.
+    pub fn distance(self, from: (usize, usize, usize), to: (usize, usize, usize)) -> Scalar {
+        let mut d = [
+            from.0.abs_diff(to.0),
+            from.1.abs_diff(to.1),
+            from.2.abs_diff(to.2),
+        ];
+        d.sort_unstable();
+        let [low, mid, high] = d;
+        match self {
+            Self::Six => (low + mid + high) as Scalar,
+            Self::Eighteen => {
+                if high >= low + mid {
+                    (high - low - mid) as Scalar + (low + mid) as Scalar * SCALAR_SQRT_2
+                } else {
+                    let sum = low + mid + high;
+                    (sum / 2) as Scalar * SCALAR_SQRT_2 + (sum % 2) as Scalar
+                }
+            }
+            Self::TwentySix => {
+                let sqrt_3 = (3.0 as Scalar).sqrt();
+                (high - mid) as Scalar
+                    + (mid - low) as Scalar * SCALAR_SQRT_2
+                    + low as Scalar * sqrt_3
+            }
+        }
+    }
+
+    // moves with given number of changed axes at most.
+    fn max_axes(self) -> usize {
+        match self {
+            Self::Six => 1,
+            Self::Eighteen => 2,
+            Self::TwentySix => 3,
+        }
+    }
+}
+
+/// 3D grid of cells for flying and swimming agents.
+///
+/// Walkability is stored as bitset and costs are allocated only once any cell cost differs
+/// from default one. Diagonal moves require every cell they pass by to be walkable, so paths
+/// never squeeze through edges or corners of blocked cells.
+///
+/// # Example
+/// ```
+/// use navmesh::*;
+///
+/// let mut grid =
+///     NavVoxelGrid::filled(3, 3, 3, true, NavVoxelConnectivity::TwentySix).unwrap();
+/// assert_eq!(grid.find_path((0, 0, 0), (2, 2, 2)).unwrap().points.len(), 3);
+/// grid.set_cell_walkable((1, 1, 1), false);
+/// assert_eq!(grid.find_path((0, 0, 0), (2, 2, 2)).unwrap().points.len(), 5);
+/// let path = grid
+///     .find_path_world((0.5, 0.5, 0.5).into(), (2.5, 0.5, 0.5).into())
+///     .unwrap()
+///     .points;
+/// assert_eq!(path.last(), Some(&(2.5, 0.5, 0.5).into()));
+/// ```
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct NavVoxelGrid {
+    id: NavVoxelGridID,
+    cols: usize,
+    rows: usize,
+    layers: usize,
+    connectivity: NavVoxelConnectivity,
+    // walkability bitset.
+    cells: Vec<u64>,
+    // empty until any cell gets cost other than 1.
+    costs: Vec<Scalar>,
+    #[serde(default)]
+    cost_model: NavCostModel,
+    // lowest cell cost, used to scale heuristic.
+    min_cost: Scalar,
+    #[serde(default)]
+    transform: Option<NavGridTransform>,
+}
+
+impl NavVoxelGrid {
+    pub fn new(cols: usize, rows: usize, layers: usize, cells: Vec<bool>) -> NavResult<Self> {
+        Self::with_connectivity(cols, rows, layers, cells, NavVoxelConnectivity::Six)
+    }
+
+    pub fn with_connectivity(
+        cols: usize,
+        rows: usize,
+        layers: usize,
+        cells: Vec<bool>,
+        connectivity: NavVoxelConnectivity,
+    ) -> NavResult<Self> {
+        if cols * rows * layers != cells.len() {
+            return Err(Error::CellsCountDoesNotMatchColsRowsLayers(
+                cells.len(),
+                cols,
+                rows,
+                layers,
+            ));
+        }
+        let mut result = Self::filled(cols, rows, layers, false, connectivity)?;
+        for (index, walkable) in cells.into_iter().enumerate() {
+            if walkable {
+                result.cells[index / 64] |= 1 << (index % 64);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Create grid with all cells being either walkable or not.
+    pub fn filled(
+        cols: usize,
+        rows: usize,
+        layers: usize,
+        walkable: bool,
+        connectivity: NavVoxelConnectivity,
+    ) -> NavResult<Self> {
+        if cols == 0 || rows == 0 || layers == 0 {
+            return Err(Error::EmptyVoxels(cols, rows, layers));
+        }
+        let count = cols * rows * layers;
+        let mut cells = vec![if walkable { u64::MAX } else { 0 }; count.div_ceil(64)];
+        if walkable && count % 64 != 0 {
+            *cells.last_mut().unwrap() = (1 << (count % 64)) - 1;
+        }
+        Ok(Self {
+            id: NavVoxelGridID::new(),
+            cols,
+            rows,
+            layers,
+            connectivity,
+            cells,
+            costs: vec![],
+            cost_model: NavCostModel::default(),
+            min_cost: 1.0,
+            transform: None,
+        })
+    }
+
+    #[inline]
+    pub fn id(&self) -> NavVoxelGridID {
+        self.id
+    }
+
+    #[inline]
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    #[inline]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    #[inline]
+    pub fn layers(&self) -> usize {
+        self.layers
+    }
+
+    #[inline]
+    pub fn connectivity(&self) -> NavVoxelConnectivity {
+        self.connectivity
+    }
+
+    pub fn is_cell_walkable(&self, (col, row, layer): (usize, usize, usize)) -> Option<bool> {
+        let index = self.index(col, row, layer)?;
+        Some(self.walkable(index))
+    }
+
+    pub fn set_cell_walkable(
+        &mut self,
+        (col, row, layer): (usize, usize, usize),
+        walkable: bool,
+    ) -> Option<bool> {
+        let index = self.index(col, row, layer)?;
+        let old = self.walkable(index);
+        if walkable {
+            self.cells[index / 64] |= 1 << (index % 64);
+        } else {
+            self.cells[index / 64] &= !(1 << (index % 64));
+        }
+        Some(old)
+    }
+
+    /// Set walkability of cells in box starting at `from` cell with `size` cells along each axis.
+    pub fn fill_box(
+        &mut self,
+        from: (usize, usize, usize),
+        size: (usize, usize, usize),
+        walkable: bool,
+    ) {
+        let to_col = (from.0 + size.0).min(self.cols);
+        let to_row = (from.1 + size.1).min(self.rows);
+        let to_layer = (from.2 + size.2).min(self.layers);
+        for layer in from.2..to_layer {
+            for row in from.1..to_row {
+                for col in from.0..to_col {
+                    self.set_cell_walkable((col, row, layer), walkable);
+                }
+            }
+        }
+    }
+
+    pub fn cell_cost(&self, (col, row, layer): (usize, usize, usize)) -> Option<Scalar> {
+        let index = self.index(col, row, layer)?;
+        Some(self.cost(index))
+    }
+
+    pub fn set_cell_cost(
+        &mut self,
+        (col, row, layer): (usize, usize, usize),
+        cost: Scalar,
+    ) -> Option<Scalar> {
+        let index = self.index(col, row, layer)?;
+        let old = self.cost(index);
+        let cost = cost.max(0.0);
+        if self.costs.is_empty() {
+            if cost == 1.0 {
+                return Some(old);
+            }
+            self.costs = vec![1.0; self.cols * self.rows * self.layers];
+        }
+        self.costs[index] = cost;
+        if cost < self.min_cost {
+            self.min_cost = cost;
+        } else if old <= self.min_cost {
+            self.min_cost = self.costs.iter().copied().fold(Scalar::MAX, Scalar::min);
+        }
+        Some(old)
+    }
+
+    #[inline]
+    pub fn cost_model(&self) -> NavCostModel {
+        self.cost_model
+    }
+
+    #[inline]
+    pub fn set_cost_model(&mut self, model: NavCostModel) {
+        self.cost_model = model;
+    }
+
+    #[inline]
+    pub fn transform(&self) -> Option<NavGridTransform> {
+        self.transform
+    }
+
+    /// Set world placement of grid. Layers go along Z axis, cells keep `cell_size` along it too.
+    ///
+    /// # Returns
+    /// `Ok` or `Err` with `Error::InvalidCellSize` if transform cell size is not positive.
+    pub fn set_transform(&mut self, transform: Option<NavGridTransform>) -> NavResult<()> {
+        if let Some(transform) = &transform {
+            transform.validate()?;
+        }
+        self.transform = transform;
+        Ok(())
+    }
+
+    /// World position of given cell center. Grid without transform uses default one.
+    pub fn cell_to_world(&self, (col, row, layer): (usize, usize, usize)) -> NavVec3 {
+        let transform = self.transform.unwrap_or_default();
+        let mut result = transform.local_to_world(col as Scalar + 0.5, row as Scalar + 0.5);
+        result.z += (layer as Scalar + 0.5) * transform.cell_size;
+        result
+    }
+
+    /// Cell containing given world position or `None` if position is outside of grid.
+    pub fn world_to_cell(&self, point: NavVec3) -> Option<(usize, usize, usize)> {
+        let transform = self.transform.unwrap_or_default();
+        let (x, y) = transform.world_to_local(point);
+        let z = (point.z - transform.origin.z) / transform.cell_size;
+        if x < 0.0 || y < 0.0 || z < 0.0 {
+            return None;
+        }
+        let cell = (x as usize, y as usize, z as usize);
+        self.index(cell.0, cell.1, cell.2)?;
+        Some(cell)
+    }
+
+    pub fn neighbors(
+        &self,
+        (col, row, layer): (usize, usize, usize),
+    ) -> Option<impl Iterator<Item = (usize, usize, usize)> + '_> {
+        let index = self.index(col, row, layer)?;
+        if !self.walkable(index) {
+            return None;
+        }
+        Some(
+            self.neighbor_indices(index)
+                .filter_map(move |(index, _)| self.coord(index)),
+        )
+    }
+
+    pub fn find_path(
+        &self,
+        from: (usize, usize, usize),
+        to: (usize, usize, usize),
+    ) -> Option<NavPath<(usize, usize, usize)>> {
+        self.find_path_custom(from, to, |_, _, _| true)
+    }
+
+    pub fn find_path_world(&self, from: NavVec3, to: NavVec3) -> Option<NavPath<NavVec3>> {
+        let from = self.world_to_cell(from)?;
+        let to = self.world_to_cell(to)?;
+        let path = self.find_path(from, to)?;
+        let points = path
+            .points
+            .into_iter()
+            .map(|cell| self.cell_to_world(cell))
+            .collect();
+        Some(NavPath::from_points(points, path.cost))
+    }
+
+    // filter params: first cell, second cell, connection cost.
+    // connections rejected by filter cannot be traversed.
+    pub fn find_path_custom<F>(
+        &self,
+        from: (usize, usize, usize),
+        to: (usize, usize, usize),
+        mut filter: F,
+    ) -> Option<NavPath<(usize, usize, usize)>>
+    where
+        F: FnMut((usize, usize, usize), (usize, usize, usize), Scalar) -> bool,
+    {
+        let mut context = NavSearchContext::default();
+        let cost = self.find_path_inner(
+            &mut context,
+            from,
+            to,
+            |a, b, w| {
+                let cost = self.connection_cost(self.cost_model, a, b, w);
+                if filter(self.coord(a).unwrap(), self.coord(b).unwrap(), cost) {
+                    Some(cost)
+                } else {
+                    None
+                }
+            },
+            |n, goal| self.heuristic(self.cost_model, n, goal),
+        )?;
+        Some(self.make_path(&context.path, cost))
+    }
+
+    // cost params: first cell, second cell.
+    pub fn find_path_with_cost<C>(
+        &self,
+        from: (usize, usize, usize),
+        to: (usize, usize, usize),
+        cost: &C,
+    ) -> Option<NavPath<(usize, usize, usize)>>
+    where
+        C: NavCost<(usize, usize, usize)>,
+    {
+        let mut context = NavSearchContext::default();
+        let cost = self.find_path_cost_inner(&mut context, from, to, cost)?;
+        Some(self.make_path(&context.path, cost))
+    }
+
+    // cost params: first cell, second cell.
+    pub fn find_path_with_context<'a, C>(
+        &'a self,
+        context: &'a mut NavSearchContext,
+        from: (usize, usize, usize),
+        to: (usize, usize, usize),
+        cost: &C,
+    ) -> Option<impl Iterator<Item = (usize, usize, usize)> + 'a>
+    where
+        C: NavCost<(usize, usize, usize)>,
+    {
+        self.find_path_cost_inner(context, from, to, cost)?;
+        Some(context.path.iter().filter_map(move |n| self.coord(*n)))
+    }
+
+    pub fn find_islands(&self) -> Vec<Vec<(usize, usize, usize)>> {
+        flood_islands(
+            self.cols * self.rows * self.layers,
+            |n| self.walkable(n),
+            |n| self.neighbor_indices(n).map(|(n, _)| n),
+        )
+        .into_iter()
+        .map(|island| island.into_iter().filter_map(|n| self.coord(n)).collect())
+        .collect()
+    }
+
+    // leaves found path in context and returns its cost.
+    fn find_path_cost_inner<C>(
+        &self,
+        context: &mut NavSearchContext,
+        from: (usize, usize, usize),
+        to: (usize, usize, usize),
+        cost: &C,
+    ) -> Option<Scalar>
+    where
+        C: NavCost<(usize, usize, usize)>,
+    {
+        let model = cost.cost_model().unwrap_or(self.cost_model);
+        self.find_path_inner(
+            context,
+            from,
+            to,
+            |a, b, w| {
+                let (ca, cb) = (self.coord(a).unwrap(), self.coord(b).unwrap());
+                cost.edge_cost(ca, cb, self.connection_cost(model, a, b, w))
+            },
+            |n, goal| {
+                cost.heuristic(self.coord(n).unwrap(), self.coord(goal).unwrap())
+                    .unwrap_or_else(|| self.heuristic(model, n, goal))
+            },
+        )
+    }
+
+    fn make_path(&self, path: &[usize], cost: Scalar) -> NavPath<(usize, usize, usize)> {
+        let points = path
+            .iter()
+            .filter_map(|n| self.coord(*n))
+            .collect::<Vec<_>>();
+        let length = path_length(&points, |a, b| {
+            NavVoxelConnectivity::TwentySix.distance(*a, *b)
+        });
+        NavPath::new(points, cost, length)
+    }
+
+    pub fn index(&self, col: usize, row: usize, layer: usize) -> Option<usize> {
+        if col < self.cols && row < self.rows && layer < self.layers {
+            Some((layer * self.rows + row) * self.cols + col)
+        } else {
+            None
+        }
+    }
+
+    pub fn coord(&self, index: usize) -> Option<(usize, usize, usize)> {
+        let col = index % self.cols;
+        let row = (index / self.cols) % self.rows;
+        let layer = index / (self.cols * self.rows);
+        if layer < self.layers {
+            Some((col, row, layer))
+        } else {
+            None
+        }
+    }
+
+    fn find_path_inner<E, H>(
+        &self,
+        context: &mut NavSearchContext,
+        from: (usize, usize, usize),
+        to: (usize, usize, usize),
+        edge_cost: E,
+        heuristic: H,
+    ) -> Option<Scalar>
+    where
+        E: FnMut(usize, usize, Scalar) -> Option<Scalar>,
+        H: FnMut(usize, usize) -> Scalar,
+    {
+        let start_index = self.index(from.0, from.1, from.2)?;
+        let end_index = self.index(to.0, to.1, to.2)?;
+        if !self.walkable(start_index) || !self.walkable(end_index) {
+            return None;
+        }
+        let (_, cost) = search_graph(
+            self,
+            context,
+            start_index,
+            &[end_index],
+            edge_cost,
+            heuristic,
+        )?;
+        Some(cost)
+    }
+
+    // walkable neighbours of cell with lengths of moves to them.
+    fn neighbor_indices(&self, index: usize) -> impl Iterator<Item = (usize, Scalar)> + '_ {
+        let (col, row, layer) = self.coord(index).unwrap();
+        let max_axes = self.connectivity.max_axes();
+        OFFSETS.iter().filter_map(move |&(dc, dr, dl)| {
+            let axes = (dc != 0) as usize + (dr != 0) as usize + (dl != 0) as usize;
+            if axes > max_axes {
+                return None;
+            }
+            let target = self.index_signed(col, row, layer, (dc, dr, dl))?;
+            if !self.walkable(target) {
+                return None;
+            }
+            // diagonal move must not pass by blocked cells.
+            let sides = [
+                (dc, 0, 0),
+                (0, dr, 0),
+                (0, 0, dl),
+                (dc, dr, 0),
+                (dc, 0, dl),
+                (0, dr, dl),
+            ];
+            for side in sides {
+                if side == (0, 0, 0) || side == (dc, dr, dl) {
+                    continue;
+                }
+                let side = self.index_signed(col, row, layer, side)?;
+                if !self.walkable(side) {
+                    return None;
+                }
+            }
+            let weight = match axes {
+                1 => 1.0,
+                2 => SCALAR_SQRT_2,
+                _ => (3.0 as Scalar).sqrt(),
+            };
+            Some((target, weight))
+        })
+    }
+
+    fn index_signed(
+        &self,
+        col: usize,
+        row: usize,
+        layer: usize,
+        (dc, dr, dl): (isize, isize, isize),
+    ) -> Option<usize> {
+        let col = col.checked_add_signed(dc)?;
+        let row = row.checked_add_signed(dr)?;
+        let layer = layer.checked_add_signed(dl)?;
+        self.index(col, row, layer)
+    }
+
+    #[inline]
+    fn walkable(&self, index: usize) -> bool {
+        self.cells[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    #[inline]
+    fn cost(&self, index: usize) -> Scalar {
+        self.costs.get(index).copied().unwrap_or(1.0)
+    }
+
+    #[inline]
+    fn connection_cost(&self, model: NavCostModel, a: usize, b: usize, weight: Scalar) -> Scalar {
+        model.combine(weight, self.cost(a), self.cost(b))
+    }
+
+    fn heuristic(&self, model: NavCostModel, a: usize, b: usize) -> Scalar {
+        let distance = self
+            .connectivity
+            .distance(self.coord(a).unwrap(), self.coord(b).unwrap());
+        distance * model.heuristic_scale(self.min_cost)
+    }
+}
+
+impl Navigable for NavVoxelGrid {
+    type Node = (usize, usize, usize);
+    type Point = (usize, usize, usize);
+
+    fn find_path(
+        &self,
+        from: (usize, usize, usize),
+        to: (usize, usize, usize),
+    ) -> Option<NavPath<(usize, usize, usize)>> {
+        self.find_path(from, to)
+    }
+
+    fn find_path_custom<F>(
+        &self,
+        from: (usize, usize, usize),
+        to: (usize, usize, usize),
+        filter: F,
+    ) -> Option<NavPath<(usize, usize, usize)>>
+    where
+        F: FnMut((usize, usize, usize), (usize, usize, usize), Scalar) -> bool,
+    {
+        self.find_path_custom(from, to, filter)
+    }
+
+    fn find_islands(&self) -> Vec<Vec<(usize, usize, usize)>> {
+        self.find_islands()
+    }
+
+    fn neighbors(
+        &self,
+        cell: (usize, usize, usize),
+    ) -> impl Iterator<Item = (usize, usize, usize)> + '_ {
+        self.neighbors(cell).into_iter().flatten()
+    }
+
+    fn node_cost(&self, cell: (usize, usize, usize)) -> Option<Scalar> {
+        self.cell_cost(cell)
+    }
+
+    fn set_node_cost(&mut self, cell: (usize, usize, usize), cost: Scalar) -> Option<Scalar> {
+        self.set_cell_cost(cell, cost)
+    }
+
+    fn cost_model(&self) -> NavCostModel {
+        self.cost_model()
+    }
+
+    fn set_cost_model(&mut self, model: NavCostModel) {
+        self.set_cost_model(model);
+    }
+}
+
+impl NavGraph for NavVoxelGrid {
+    fn node_count(&self) -> usize {
+        self.cols * self.rows * self.layers
+    }
+
+    // cells that are not walkable cannot be left.
+    fn neighbors(&self, node: usize) -> impl Iterator<Item = (usize, Scalar)> + '_ {
+        (node < self.node_count() && self.walkable(node))
+            .then(|| self.neighbor_indices(node))
+            .into_iter()
+            .flatten()
+    }
+
+    fn edge_cost(&self, from: usize, to: usize, weight: Scalar) -> Option<Scalar> {
+        Some(self.connection_cost(self.cost_model, from, to, weight))
+    }
+
+    fn heuristic(&self, node: usize, goal: usize) -> Scalar {
+        self.heuristic(self.cost_model, node, goal)
+    }
+
+    fn node_position(&self, node: usize) -> Option<NavVec3> {
+        self.coord(node).map(|cell| self.cell_to_world(cell))
+    }
+}
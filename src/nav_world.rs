@@ -0,0 +1,311 @@
+use crate::{
+    nav_path::path_length, NavFreeGrid, NavGrid, NavIslandPortal, NavIslands, NavMesh, NavNet,
+    NavPath, NavPathMode, NavQuery, NavVec3,
+};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+use std::{collections::HashMap, hash::Hash};
+use typid::ID;
+
+/// Nav world identifier.
+pub type NavWorldID = ID<NavWorld<(), ()>>;
+
+/// Fine grained navigation structure of single island of nav world.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum NavWorldIsland {
+    /// Searched with `NavQuery::Accuracy` and `NavPathMode::Accuracy`.
+    Mesh(NavMesh),
+    Net(NavNet),
+    /// Searched between cells containing world positions.
+    Grid(NavGrid),
+    /// Searched between cells containing world positions.
+    FreeGrid(NavFreeGrid),
+}
+
+impl NavWorldIsland {
+    /// Find path between world positions.
+    ///
+    /// Path cost is measured in world units (world distance scaled by costs), so grid path
+    /// costs, measured in cells, get multiplied by grid cell size. Path between the same
+    /// positions is single point with no cost.
+    pub fn find_path(&self, from: NavVec3, to: NavVec3) -> Option<NavPath<NavVec3>> {
+        if from.same_as(to) {
+            return Some(NavPath::new(vec![from], 0.0, 0.0));
+        }
+        match self {
+            Self::Mesh(mesh) => mesh.find_path(from, to, NavQuery::Accuracy, NavPathMode::Accuracy),
+            Self::Net(net) => net.find_path(from, to),
+            Self::Grid(grid) => {
+                let mut path = grid.find_path_world(from, to)?;
+                path.cost *= grid.transform().unwrap_or_default().cell_size;
+                Some(path)
+            }
+            Self::FreeGrid(grid) => {
+                let mut path = grid.find_path_world(from, to)?;
+                path.cost *= grid.transform().unwrap_or_default().cell_size;
+                Some(path)
+            }
+        }
+    }
+}
+
+impl From<NavMesh> for NavWorldIsland {
+    fn from(mesh: NavMesh) -> Self {
+        Self::Mesh(mesh)
+    }
+}
+
+impl From<NavNet> for NavWorldIsland {
+    fn from(net: NavNet) -> Self {
+        Self::Net(net)
+    }
+}
+
+impl From<NavGrid> for NavWorldIsland {
+    fn from(grid: NavGrid) -> Self {
+        Self::Grid(grid)
+    }
+}
+
+impl From<NavFreeGrid> for NavWorldIsland {
+    fn from(grid: NavFreeGrid) -> Self {
+        Self::FreeGrid(grid)
+    }
+}
+
+/// Hierarchical navigation over islands (buildings, levels) connected with portals.
+///
+/// Path is first routed through `NavIslands` and then refined on every visited island with its
+/// own navigation structure, between world locations of portals. Island nodes without portal
+/// (`portal: None`) stand for island itself, so coarse graph is expected to connect them with
+/// island portals, the same way as when using `NavIslands` alone. Moving between portals of two
+/// different islands (doors, stairs, elevators) is a straight segment.
+///
+/// # Example
+/// ```
+/// use navmesh::*;
+///
+/// let portal = |island, portal| NavIslandPortal { island, portal };
+/// let islands = NavIslands::new(
+///     vec![
+///         NavIslandsConnection {
+///             from: portal(0, None),
+///             to: portal(0, Some(0)),
+///             distance: 1.0,
+///         },
+///         NavIslandsConnection {
+///             from: portal(0, Some(0)),
+///             to: portal(1, Some(0)),
+///             distance: 1.0,
+///         },
+///         NavIslandsConnection {
+///             from: portal(1, Some(0)),
+///             to: portal(1, None),
+///             distance: 1.0,
+///         },
+///     ],
+///     true,
+/// );
+/// let mut world = NavWorld::new(islands);
+/// world.set_island(0, NavGrid::new(3, 1, vec![true; 3]).unwrap());
+/// let mut grid = NavGrid::new(3, 1, vec![true; 3]).unwrap();
+/// grid.set_transform(Some(NavGridTransform {
+///     origin: (3.0, 0.0, 0.0).into(),
+///     ..Default::default()
+/// }))
+/// .unwrap();
+/// world.set_island(1, grid);
+/// world.set_portal_location(portal(0, Some(0)), (2.5, 0.5, 0.0).into());
+/// world.set_portal_location(portal(1, Some(0)), (3.5, 0.5, 0.0).into());
+///
+/// let path = world
+///     .find_path(&0, (0.5, 0.5, 0.0).into(), &1, (5.5, 0.5, 0.0).into())
+///     .unwrap();
+/// assert_eq!(path.points.first(), Some(&(0.5, 0.5, 0.0).into()));
+/// assert_eq!(path.points.last(), Some(&(5.5, 0.5, 0.0).into()));
+/// assert_eq!(path.length, 5.0);
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NavWorld<Island, Portal>
+where
+    Island: std::fmt::Debug + Clone + Eq + Hash + Send + Sync,
+    Portal: std::fmt::Debug + Clone + Eq + Hash + Send + Sync,
+{
+    id: NavWorldID,
+    #[serde(bound(
+        deserialize = "Island: Serialize + DeserializeOwned, Portal: Serialize + DeserializeOwned"
+    ))]
+    islands: NavIslands<Island, Portal>,
+    // maps are stored as lists of pairs, so keys do not need to be strings (JSON).
+    #[serde(
+        bound(deserialize = "Island: Serialize + DeserializeOwned"),
+        serialize_with = "serialize_pairs",
+        deserialize_with = "deserialize_pairs"
+    )]
+    areas: HashMap<Island, NavWorldIsland>,
+    #[serde(
+        bound(
+            deserialize = "Island: Serialize + DeserializeOwned, Portal: Serialize + DeserializeOwned"
+        ),
+        serialize_with = "serialize_pairs",
+        deserialize_with = "deserialize_pairs"
+    )]
+    locations: HashMap<NavIslandPortal<Island, Portal>, NavVec3>,
+}
+
+impl<Island, Portal> NavWorld<Island, Portal>
+where
+    Island: std::fmt::Debug + Clone + Eq + Hash + Send + Sync,
+    Portal: std::fmt::Debug + Clone + Eq + Hash + Send + Sync,
+{
+    pub fn new(islands: NavIslands<Island, Portal>) -> Self {
+        Self {
+            id: NavWorldID::new(),
+            islands,
+            areas: Default::default(),
+            locations: Default::default(),
+        }
+    }
+
+    #[inline]
+    pub fn id(&self) -> NavWorldID {
+        self.id
+    }
+
+    #[inline]
+    pub fn islands(&self) -> &NavIslands<Island, Portal> {
+        &self.islands
+    }
+
+    #[inline]
+    pub fn islands_mut(&mut self) -> &mut NavIslands<Island, Portal> {
+        &mut self.islands
+    }
+
+    /// Set navigation structure of island, returning previous one.
+    pub fn set_island<T>(&mut self, island: Island, nav: T) -> Option<NavWorldIsland>
+    where
+        T: Into<NavWorldIsland>,
+    {
+        self.areas.insert(island, nav.into())
+    }
+
+    pub fn remove_island(&mut self, island: &Island) -> Option<NavWorldIsland> {
+        self.areas.remove(island)
+    }
+
+    pub fn island(&self, island: &Island) -> Option<&NavWorldIsland> {
+        self.areas.get(island)
+    }
+
+    pub fn island_mut(&mut self, island: &Island) -> Option<&mut NavWorldIsland> {
+        self.areas.get_mut(island)
+    }
+
+    /// Set world location of portal, returning previous one.
+    pub fn set_portal_location(
+        &mut self,
+        portal: NavIslandPortal<Island, Portal>,
+        location: NavVec3,
+    ) -> Option<NavVec3> {
+        self.locations.insert(portal, location)
+    }
+
+    pub fn remove_portal_location(
+        &mut self,
+        portal: &NavIslandPortal<Island, Portal>,
+    ) -> Option<NavVec3> {
+        self.locations.remove(portal)
+    }
+
+    pub fn portal_location(&self, portal: &NavIslandPortal<Island, Portal>) -> Option<NavVec3> {
+        self.locations.get(portal).copied()
+    }
+
+    /// Find path between points lying on given islands.
+    ///
+    /// Path cost is the sum of costs of paths found on islands and lengths of segments between
+    /// islands, all measured in world units (see `NavWorldIsland::find_path`).
+    ///
+    /// # Returns
+    /// `Some` with continuous path through all visited islands or `None` if either coarse route
+    /// or path on any of its islands cannot be found (also when island navigation structure or
+    /// portal location is missing).
+    pub fn find_path(
+        &self,
+        from_island: &Island,
+        from: NavVec3,
+        to_island: &Island,
+        to: NavVec3,
+    ) -> Option<NavPath<NavVec3>> {
+        if from_island == to_island {
+            if let Some(path) = self.island(from_island)?.find_path(from, to) {
+                return Some(path);
+            }
+        }
+        let start = NavIslandPortal {
+            island: from_island.clone(),
+            portal: None,
+        };
+        let end = NavIslandPortal {
+            island: to_island.clone(),
+            portal: None,
+        };
+        let route = self.islands.find_path(&start, &end)?;
+        let mut points = vec![from];
+        let mut cost = 0.0;
+        let mut island = from_island;
+        let mut position = from;
+        for portal in route.points.iter().filter(|p| p.portal.is_some()) {
+            let location = self.portal_location(portal)?;
+            if &portal.island == island {
+                let path = self.island(island)?.find_path(position, location)?;
+                cost += path.cost;
+                Self::extend_points(&mut points, path.points);
+            } else {
+                cost += (location - position).magnitude();
+                Self::extend_points(&mut points, vec![location]);
+                island = &portal.island;
+            }
+            position = location;
+        }
+        if island != to_island {
+            return None;
+        }
+        let path = self.island(island)?.find_path(position, to)?;
+        cost += path.cost;
+        Self::extend_points(&mut points, path.points);
+        let length = path_length(&points, |a, b| (*b - *a).magnitude());
+        Some(NavPath::new(points, cost, length))
+    }
+
+    // joins paths without repeating points they share.
+    fn extend_points(points: &mut Vec<NavVec3>, other: Vec<NavVec3>) {
+        let mut other = other.into_iter().peekable();
+        if let (Some(last), Some(first)) = (points.last(), other.peek()) {
+            if last.same_as(*first) {
+                other.next();
+            }
+        }
+        points.extend(other);
+    }
+}
+
+fn serialize_pairs<K, V, S>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    K: Serialize,
+    V: Serialize,
+    S: Serializer,
+{
+    serializer.collect_seq(map)
+}
+
+fn deserialize_pairs<'de, K, V, D>(deserializer: D) -> Result<HashMap<K, V>, D::Error>
+where
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Ok(Vec::<(K, V)>::deserialize(deserializer)?
+        .into_iter()
+        .collect())
+}